@@ -2,17 +2,37 @@ use path_clean::clean;
 use std::env::args;
 use std::{io, path::PathBuf};
 
+use crate::proto::compiler::ts::compile_options::{CompileOptions, EnumStyle, LongType};
+
+/// How errors are printed to stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(super) enum ErrorFormat {
+    /// Source snippets with underlined ranges
+    #[default]
+    Human,
+    /// One JSON object per line for each diagnostic
+    Json,
+}
+
 #[derive(Debug)]
 pub(super) struct CliArguments {
-    pub proto_folder_path: PathBuf,
+    /// .proto files and folders to compile
+    pub input_paths: Vec<PathBuf>,
+    /// Folders which imports are searched in, given with `-I` or `--proto_path`
+    pub proto_paths: Vec<PathBuf>,
     pub out_folder_path: PathBuf,
+    pub error_format: ErrorFormat,
+    pub compile_options: CompileOptions,
 }
 
 impl Default for CliArguments {
     fn default() -> Self {
         Self {
-            proto_folder_path: PathBuf::from("."),
+            input_paths: Vec::new(),
+            proto_paths: Vec::new(),
             out_folder_path: PathBuf::from("./out"),
+            error_format: ErrorFormat::default(),
+            compile_options: CompileOptions::default(),
         }
     }
 }
@@ -21,44 +41,133 @@ impl std::fmt::Display for CliArguments {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
             f,
-            "proto_folder_path: {:?}\nout_folder_path: {:?}",
-            self.proto_folder_path, self.out_folder_path
+            "input_paths: {:?}\nproto_paths: {:?}\nout_folder_path: {:?}\nerror_format: {:?}\ncompile_options: {:?}",
+            self.input_paths,
+            self.proto_paths,
+            self.out_folder_path,
+            self.error_format,
+            self.compile_options
         )
     }
 }
 
 enum ParseState {
-    ProtoFolderPath,
+    InputPath,
+    ProtoPath,
     OutFolderPath,
+    ErrorFormat,
+    LongType,
+    EnumStyle,
 }
 impl Default for ParseState {
     fn default() -> Self {
-        ProtoFolderPath
+        InputPath
     }
 }
 use ParseState::*;
 
-/// It takes first argument as the relative or absolute path
-/// to the folder containing the proto files.
-/// It returns absolute path to the folder.
+/// It takes positional arguments as relative or absolute paths
+/// to proto files or folders containing them, current folder by default.
+/// `-I <folder>` and `--proto_path <folder>` add folders to search imports in,
+/// `-I<folder>` and `--proto_path=<folder>` forms are accepted as well.
+/// `--long-type <long|bigint|string|number>` chooses representation of 64-bit integers.
+/// `--enum-style <enum|const-enum|string-union|as-const>` chooses how enums are declared.
+/// `--oneof-unions` generates `oneof` groups as discriminated unions by `$case`.
 pub(crate) fn get_proto_folder_path() -> io::Result<CliArguments> {
     let mut res = CliArguments::default();
     let mut state = ParseState::default();
-    for arg in args() {
+    for arg in args().skip(1) {
+        if arg == "-I" || arg == "--proto_path" {
+            state = ParseState::ProtoPath;
+            continue;
+        }
+        if let Some(path) = arg
+            .strip_prefix("--proto_path=")
+            .or_else(|| arg.strip_prefix("-I"))
+        {
+            res.proto_paths.push(PathBuf::from(clean(path)));
+            continue;
+        }
         if arg == "--out" {
             state = ParseState::OutFolderPath;
             continue;
         }
+        if arg == "--error-format" {
+            state = ParseState::ErrorFormat;
+            continue;
+        }
+        if arg == "--oneof-unions" {
+            res.compile_options.oneof_unions = true;
+            continue;
+        }
+        if arg == "--long-type" {
+            state = ParseState::LongType;
+            continue;
+        }
+        if arg == "--enum-style" {
+            state = ParseState::EnumStyle;
+            continue;
+        }
         match state {
-            ProtoFolderPath => {
-                res.proto_folder_path = PathBuf::from(clean(&arg));
+            InputPath => {
+                res.input_paths.push(PathBuf::from(clean(&arg)));
+            }
+            ProtoPath => {
+                res.proto_paths.push(PathBuf::from(clean(&arg)));
+                state = ParseState::default();
             }
             OutFolderPath => {
                 res.out_folder_path = PathBuf::from(clean(&arg));
                 state = ParseState::default();
             }
+            ParseState::ErrorFormat => {
+                res.error_format = match arg.as_str() {
+                    "human" => ErrorFormat::Human,
+                    "json" => ErrorFormat::Json,
+                    _ => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("Unknown error format {}, expected human or json", arg),
+                        ))
+                    }
+                };
+                state = ParseState::default();
+            }
+            ParseState::LongType => {
+                res.compile_options.long_type = match LongType::from_name(&arg) {
+                    Some(long_type) => long_type,
+                    None => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!(
+                                "Unknown long type {}, expected long, bigint, string or number",
+                                arg
+                            ),
+                        ))
+                    }
+                };
+                state = ParseState::default();
+            }
+            ParseState::EnumStyle => {
+                res.compile_options.enum_style = match EnumStyle::from_name(&arg) {
+                    Some(enum_style) => enum_style,
+                    None => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!(
+                                "Unknown enum style {}, expected enum, const-enum, string-union or as-const",
+                                arg
+                            ),
+                        ))
+                    }
+                };
+                state = ParseState::default();
+            }
         }
     }
 
+    if res.input_paths.is_empty() {
+        res.input_paths.push(PathBuf::from("."));
+    }
     Ok(res)
 }
@@ -5,12 +5,17 @@ use std::process;
 
 use args::get_proto_folder_path;
 use args::CliArguments;
+use args::ErrorFormat;
 use proto::compiler::ts::ast::Folder;
 use proto::compiler::ts::commit_folder::commit_folder;
 use proto::compiler::ts::scope_to_folder::root_scope_to_folder;
-use proto::folder::read_proto_folder;
+use proto::diagnostic::render_diagnostics;
+use proto::diagnostic::render_json_diagnostics;
+use proto::error::ProtoError;
+use proto::folder::read_proto_inputs;
 
 use proto::package::read_root_scope;
+use proto::validation::validate_root_scope;
 
 fn main() -> () {
     let args = match get_proto_folder_path() {
@@ -26,26 +31,40 @@ fn main() -> () {
 
 fn run(args: CliArguments) {
     let CliArguments {
-        proto_folder_path,
+        input_paths,
+        proto_paths,
         out_folder_path,
+        error_format,
+        compile_options,
     } = args;
 
-    let proto_folder = match read_proto_folder(proto_folder_path) {
+    let proto_folder = match read_proto_inputs(&input_paths, &proto_paths) {
         Err(e) => {
-            eprintln!("{}", e);
+            report(&e, error_format);
             process::exit(2);
         }
         Ok(r) => r,
     };
 
-    let root_scope = match read_root_scope(&proto_folder.files) {
+    let mut root_scope = match read_root_scope(&proto_folder.files, &proto_folder.proto_paths) {
         Err(e) => {
-            eprintln!("{}", e);
+            report(&e, error_format);
             process::exit(3);
         }
         Ok(r) => r,
     };
 
+    if let Some(warnings) = ProtoError::from_errors(std::mem::take(&mut root_scope.warnings)) {
+        report(&warnings, error_format);
+    }
+
+    root_scope.options = compile_options;
+
+    if let Err(e) = validate_root_scope(&root_scope) {
+        report(&e, error_format);
+        process::exit(3);
+    }
+
     let root_file_name: String = out_folder_path
         .file_name()
         .map(|s| s.to_string_lossy())
@@ -54,7 +73,7 @@ fn run(args: CliArguments) {
 
     let folder: Folder = match root_scope_to_folder(&root_scope, root_file_name) {
         Err(e) => {
-            eprintln!("{}", e);
+            report(&e, error_format);
             process::exit(4);
         }
         Ok(r) => r,
@@ -63,8 +82,15 @@ fn run(args: CliArguments) {
     match commit_folder(&folder) {
         Ok(_) => {}
         Err(e) => {
-            eprintln!("{}", e);
+            report(&e, error_format);
             process::exit(4);
         }
     }
 }
+
+fn report(error: &ProtoError, error_format: ErrorFormat) {
+    match error_format {
+        ErrorFormat::Human => eprintln!("{}", render_diagnostics(error)),
+        ErrorFormat::Json => eprintln!("{}", render_json_diagnostics(error)),
+    }
+}
@@ -1,9 +1,12 @@
 pub(super) mod folder;
 pub(super) mod package;
 pub(super) mod error;
+pub(super) mod diagnostic;
+mod features;
 pub(super) mod compiler;
 mod syntax;
 mod lexems;
 mod id_generator;
 mod proto_scope;
 mod protopath;
+pub(super) mod validation;
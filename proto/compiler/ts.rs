@@ -1,6 +1,10 @@
 pub(crate) mod ast;
+mod ast_builders;
 pub(crate) mod commit_folder;
+pub(crate) mod compile_options;
 mod constants;
+#[cfg(test)]
+mod codegen_test;
 mod decode_compiler;
 mod encode_basic_repeated_type_field;
 mod encode_basic_type_field;
@@ -9,17 +13,28 @@ mod encode_compiler;
 mod encode_enum_field;
 mod encode_map_field;
 mod encode_message_expr;
+mod ensure_function;
 mod ensure_import;
+mod extension_compiler;
 mod enum_compiler;
+mod enum_helpers;
+mod enum_style;
 mod file_name_to_folder_name;
 mod file_to_folder;
+mod from_json_compiler;
 mod get_relative_import;
 mod has_property;
 mod is_reserved;
 mod is_safe_id;
+mod js_doc_comment;
+mod json_helpers;
+mod json_runtime;
+mod long_type;
 mod message_name_to_encode_type_name;
+mod one_of_union;
 pub(crate) mod scope_to_folder;
 mod render_file;
 mod to_js_string;
+mod to_json_compiler;
 mod ts_path;
 mod types_compiler;
@@ -143,6 +143,7 @@ impl ImportDeclaration {
 #[derive(Debug)]
 pub(crate) enum Modifier {
     Export,
+    Const,
 }
 
 #[derive(Debug)]
@@ -178,10 +179,17 @@ impl From<i64> for EnumValue {
     }
 }
 
+/// Documentation comment, rendered as `/** ... */` before a declaration or a member
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct JsDocComment {
+    pub text: Rc<str>,
+}
+
 #[derive(Debug)]
 pub(crate) struct EnumMember {
     pub name: Identifier,
     pub value: Option<EnumValue>,
+    pub comment: Option<JsDocComment>,
 }
 
 #[derive(Debug)]
@@ -189,6 +197,26 @@ pub(crate) struct EnumDeclaration {
     pub modifiers: Vec<Modifier>,
     pub name: Identifier,
     pub members: Vec<EnumMember>,
+    pub comment: Option<JsDocComment>,
+}
+
+/// Type alias, e.g. `export type Color = "RED" | "GREEN"`
+#[derive(Debug)]
+pub(crate) struct TypeAliasDeclaration {
+    pub modifiers: Vec<Modifier>,
+    pub name: Identifier,
+    pub alias_type: Type,
+    pub comment: Option<JsDocComment>,
+}
+
+/// Constant declared at the top level of a file, e.g. `export const ColorNames = { ... }`
+#[derive(Debug)]
+pub(crate) struct ConstDeclaration {
+    pub modifiers: Vec<Modifier>,
+    pub name: Identifier,
+    pub const_type: Option<Type>,
+    pub initializer: Expression,
+    pub comment: Option<JsDocComment>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -236,6 +264,8 @@ impl From<Vec<Type>> for UnionType {
 pub(crate) enum Type {
     Number,
     Null,
+    Undefined,
+    Void,
     Never,
     Boolean,
     String,
@@ -243,6 +273,18 @@ pub(crate) enum Type {
     ArrayType(Box<Type>),
     Record(Box<Type>, Box<Type>),
     TypeReference(Vec<Rc<Identifier>>),
+    /// String literal type, e.g. `"ok"`
+    StringLiteral(Rc<str>),
+    /// Object type with required properties, e.g. `{ $case: "ok"; ok: Ok }`
+    ObjectLiteral(Vec<(Rc<str>, Type)>),
+    /// Type query of a value, e.g. `typeof Color`
+    Query(Rc<Identifier>),
+    /// Union of property names of the type, e.g. `keyof typeof Color`
+    KeyOf(Box<Type>),
+    /// Type of properties of the object type, e.g. `(typeof Color)[keyof typeof Color]`
+    IndexedAccess(Box<Type>, Box<Type>),
+    /// Return type of type guards, e.g. `value is Color`
+    Predicate(Rc<Identifier>, Box<Type>),
     Any,
 }
 
@@ -273,10 +315,18 @@ impl Type {
             Type::Number => false,
             Type::Never => false,
             Type::Null => false,
+            Type::Undefined => false,
+            Type::Void => false,
             Type::Boolean => false,
             Type::String => false,
             Type::TypeReference(_) => false,
             Type::Record(_, _) => false,
+            Type::StringLiteral(_) => false,
+            Type::ObjectLiteral(_) => false,
+            Type::Query(_) => true,
+            Type::KeyOf(_) => true,
+            Type::IndexedAccess(_, _) => false,
+            Type::Predicate(_, _) => true,
             Type::Any => false,
         }
     }
@@ -315,6 +365,7 @@ pub(crate) struct PropertySignature {
     pub name: Identifier,
     pub property_type: Type,
     pub optional: bool,
+    pub comment: Option<JsDocComment>,
 }
 
 impl PropertySignature {
@@ -323,6 +374,7 @@ impl PropertySignature {
             name: name.into(),
             property_type,
             optional: false,
+            comment: None,
         }
     }
     pub fn new_optional(name: Rc<str>, property_type: Type) -> Self {
@@ -348,6 +400,7 @@ pub(crate) struct InterfaceDeclaration {
     pub modifiers: Vec<Modifier>,
     pub name: Identifier,
     pub members: Vec<InterfaceMember>,
+    pub comment: Option<JsDocComment>,
 }
 
 impl InterfaceDeclaration {
@@ -356,6 +409,7 @@ impl InterfaceDeclaration {
             modifiers: vec![],
             name: name.into(),
             members: Vec::new(),
+            comment: None,
         }
     }
     pub fn new_exported(name: Rc<str>) -> Self {
@@ -435,7 +489,11 @@ pub(crate) enum BinaryOperator {
     LessThan,
     InstanceOf,
     StrictEqual,
+    StrictNotEqual,
     Plus,
+    Minus,
+    Multiply,
+    Divide,
     UnsignedRightShift,
     Assign,
 }
@@ -461,6 +519,10 @@ impl From<&BinaryOperator> for &str {
             BinaryOperator::InstanceOf => "instanceof",
             BinaryOperator::Plus => "+",
             BinaryOperator::StrictEqual => "===",
+            BinaryOperator::StrictNotEqual => "!==",
+            BinaryOperator::Minus => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
             BinaryOperator::UnsignedRightShift => ">>>",
             BinaryOperator::BinaryAnd => "&",
             BinaryOperator::Assign => "=",
@@ -533,6 +595,7 @@ impl PropertyAccessExpression {
             Expression::PrefixUnaryExpression(_) => true,
             Expression::ConditionalExpression(_) => true,
             Expression::Typeof(_) => true,
+            Expression::AsConst(_) => true,
         }
     }
 }
@@ -640,6 +703,8 @@ pub(crate) enum Expression {
     PrefixUnaryExpression(PrefixUnaryExpression),
     ConditionalExpression(ConditionalExpression),
     Typeof(Rc<Expression>),
+    /// Literal with the narrowest type, e.g. `{ RED: 0 } as const`
+    AsConst(Rc<Expression>),
 }
 
 impl Expression {
@@ -1103,6 +1168,8 @@ pub(crate) enum Statement {
     Empty,
     ImportDeclaration(Box<ImportDeclaration>),
     EnumDeclaration(Box<EnumDeclaration>),
+    TypeAliasDeclaration(Box<TypeAliasDeclaration>),
+    ConstDeclaration(Box<ConstDeclaration>),
     InterfaceDeclaration(Box<InterfaceDeclaration>),
     FunctionDeclaration(Box<FunctionDeclaration>),
     ReturnStatement(Option<Expression>),
@@ -1114,6 +1181,7 @@ pub(crate) enum Statement {
     While(Rc<WhileStatement>),
     Break,
     Switch(Box<SwitchStatement>),
+    Throw(Expression),
 }
 
 impl Default for Statement {
@@ -1181,6 +1249,16 @@ impl From<EnumDeclaration> for Statement {
         Statement::EnumDeclaration(Box::new(enum_declaration))
     }
 }
+impl From<TypeAliasDeclaration> for Statement {
+    fn from(type_alias_declaration: TypeAliasDeclaration) -> Self {
+        Statement::TypeAliasDeclaration(Box::new(type_alias_declaration))
+    }
+}
+impl From<ConstDeclaration> for Statement {
+    fn from(const_declaration: ConstDeclaration) -> Self {
+        Statement::ConstDeclaration(Box::new(const_declaration))
+    }
+}
 impl From<ImportDeclaration> for Statement {
     fn from(import_declaration: ImportDeclaration) -> Self {
         Statement::ImportDeclaration(Box::new(import_declaration))
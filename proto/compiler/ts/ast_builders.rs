@@ -0,0 +1,167 @@
+use std::rc::Rc;
+
+use super::{
+    ast::{self, BinaryOperator, Call, ElementAccess, ExpressionChain, MethodCall},
+    constants::PROTOBUF_MODULE,
+    ensure_import::ensure_import,
+};
+
+pub(super) fn import_util(file: &mut ast::File) -> Rc<ast::Expression> {
+    ensure_import(
+        file,
+        ast::ImportDeclaration::import(
+            vec![ast::ImportSpecifier::new(Rc::new("util".into()))],
+            PROTOBUF_MODULE.into(),
+        ),
+    );
+    id_expr("util")
+}
+
+pub(super) fn id_expr(name: &str) -> Rc<ast::Expression> {
+    Rc::new(ast::Expression::from(name))
+}
+
+pub(super) fn string_expr(text: &str) -> Rc<ast::Expression> {
+    Rc::new(ast::Expression::StringLiteral(text.into()))
+}
+
+pub(super) fn number_expr(value: f64) -> Rc<ast::Expression> {
+    Rc::new(value.into())
+}
+
+pub(super) fn object_expr(members: Vec<(&str, Rc<ast::Expression>)>) -> ast::Expression {
+    ast::Expression::ObjectLiteralExpression(
+        members
+            .into_iter()
+            .map(|(name, value)| {
+                Rc::new(ast::ObjectLiteralMember::PropertyAssignment(
+                    Rc::new(name.into()),
+                    value,
+                ))
+            })
+            .collect(),
+    )
+}
+
+pub(super) fn declare_const(name: &str, initializer: ast::Expression) -> ast::Statement {
+    ast::VariableDeclarationList::declare_const(Rc::new(name.into()), initializer).into()
+}
+
+pub(super) fn declare_typed(
+    name: &str,
+    t: ast::Type,
+    initializer: ast::Expression,
+) -> ast::Statement {
+    ast::VariableDeclarationList::declare_typed_const(Rc::new(name.into()), t.into(), initializer)
+        .into()
+}
+
+pub(super) fn assign(
+    target: impl Into<Rc<ast::Expression>>,
+    value: impl Into<Rc<ast::Expression>>,
+) -> ast::Statement {
+    ast::Statement::Expression(
+        BinaryOperator::Assign
+            .apply(target.into(), value.into())
+            .into(),
+    )
+}
+
+pub(super) fn if_block(
+    condition: impl Into<Rc<ast::Expression>>,
+    statements: Vec<ast::Statement>,
+) -> ast::IfStatement {
+    ast::IfStatement {
+        expression: condition.into(),
+        then_statement: block(statements).into(),
+        else_statement: None,
+    }
+}
+
+pub(super) fn if_else_block(
+    condition: impl Into<Rc<ast::Expression>>,
+    statements: Vec<ast::Statement>,
+    else_statements: Vec<ast::Statement>,
+) -> ast::IfStatement {
+    ast::IfStatement {
+        else_statement: Some(block(else_statements).into()),
+        ..if_block(condition, statements)
+    }
+}
+
+pub(super) fn block(statements: Vec<ast::Statement>) -> ast::Statement {
+    ast::Statement::Block(ast::Block {
+        statements: statements.into_iter().map(Rc::new).collect(),
+    })
+}
+
+pub(super) fn return_statement(value: ast::Expression) -> ast::Statement {
+    value.into_return_statement()
+}
+
+/// `throw new Error(message)`
+pub(super) fn throw_error(message: impl Into<Rc<ast::Expression>>) -> ast::Statement {
+    let mut error = ast::NewExpression::new(id_expr("Error"));
+    error.add_argument(message.into());
+    ast::Statement::Throw(error.into())
+}
+
+pub(super) fn binary(
+    operator: BinaryOperator,
+    left: impl Into<Rc<ast::Expression>>,
+    right: impl Into<Rc<ast::Expression>>,
+) -> ast::Expression {
+    operator.apply(left.into(), right.into())
+}
+
+pub(super) fn is_not_null(value: impl Into<Rc<ast::Expression>>) -> ast::Expression {
+    binary(BinaryOperator::WeakNotEqual, value, ast::Expression::Null)
+}
+
+/// `typeof value === "{type_name}"`
+pub(super) fn is_type_of(value: &Rc<ast::Expression>, type_name: &str) -> ast::Expression {
+    binary(
+        BinaryOperator::StrictEqual,
+        value.type_of(),
+        string_expr(type_name),
+    )
+}
+
+pub(super) fn is_array(value: &Rc<ast::Expression>) -> ast::Expression {
+    id_expr("Array").method_call("isArray", vec![Rc::clone(value)])
+}
+
+/// `for (let {i} = 0; {i} < {array}.length; ++{i}) { ... }`
+pub(super) fn for_each(
+    i: &str,
+    array: &Rc<ast::Expression>,
+    statements: Vec<ast::Statement>,
+) -> ast::Statement {
+    let mut for_stmt = ast::ForStatement::for_each(Rc::new(i.into()), Rc::clone(array));
+    for_stmt.statement = Box::new(block(statements));
+    for_stmt.into()
+}
+
+/// `{object}[{keys}[{i}]]`
+pub(super) fn key_element(object: &Rc<ast::Expression>, keys: &str, i: &str) -> ast::Expression {
+    object.element(id_expr(keys).element(id_expr(i)).into())
+}
+
+/// `Object.keys({object})`
+pub(super) fn object_keys(object: &Rc<ast::Expression>) -> ast::Expression {
+    id_expr("Object").method_call("keys", vec![Rc::clone(object)])
+}
+
+/// `{name}(...args)` of a global function
+pub(super) fn call_global(name: &str, args: Vec<Rc<ast::Expression>>) -> ast::Expression {
+    id_expr(name).call(args)
+}
+
+/// `new {name}(...args)`
+pub(super) fn new_expr(name: &str, args: Vec<Rc<ast::Expression>>) -> ast::Expression {
+    let mut new_expr = ast::NewExpression::new(id_expr(name));
+    for arg in args {
+        new_expr.add_argument(arg);
+    }
+    new_expr.into()
+}
@@ -0,0 +1,71 @@
+//! Helpers of tests which check generated code
+use std::collections::BTreeMap;
+
+use crate::proto::{
+    error::ProtoError,
+    id_generator::IdGenerator,
+    lexems::read_lexems,
+    package::{ProtoFile, ProtoVersion},
+    proto_scope::builder::{ScopeBuilder, ScopeBuilderTrait},
+    syntax::parse_package,
+};
+
+use super::{
+    ast::{Folder, FolderEntry},
+    compile_options::CompileOptions,
+    scope_to_folder::root_scope_to_folder,
+};
+
+/// Compiles `test.proto` and returns generated files by their paths,
+/// e.g. `test/Hello/decode.ts`
+pub(super) fn compile(
+    content: &str,
+    options: CompileOptions,
+) -> Result<BTreeMap<String, String>, ProtoError> {
+    compile_files(&[("test.proto", content)], options)
+}
+
+/// Compiles files given as pairs of import path and content
+pub(super) fn compile_files(
+    files: &[(&str, &str)],
+    options: CompileOptions,
+) -> Result<BTreeMap<String, String>, ProtoError> {
+    let mut id_generator = IdGenerator::new();
+    let builder = ScopeBuilder::new_ref();
+    for (name, content) in files {
+        let lexems = read_lexems(name, content)?;
+        let mut file = ProtoFile {
+            version: ProtoVersion::Proto3,
+            declarations: vec![],
+            imports: vec![],
+            path: vec![],
+            name: (*name).into(),
+            import_path: (*name).into(),
+            options: vec![],
+        };
+        parse_package(&mut id_generator, &lexems, &mut file)?;
+        builder.load(file)?;
+    }
+    let mut root = builder.finish()?;
+    root.options = options;
+    let folder = root_scope_to_folder(&root, "out".into())?;
+    let mut res = BTreeMap::new();
+    collect_files(&folder, "", &mut res);
+    Ok(res)
+}
+
+fn collect_files(folder: &Folder, prefix: &str, res: &mut BTreeMap<String, String>) {
+    for entry in &folder.entries {
+        match entry {
+            FolderEntry::Folder(subfolder) => {
+                collect_files(subfolder, &format!("{}{}/", prefix, subfolder.name), res)
+            }
+            FolderEntry::File(file) => {
+                res.insert(
+                    format!("{}{}.ts", prefix, file.name),
+                    String::from(file.as_ref()),
+                );
+            }
+        }
+    }
+}
@@ -10,12 +10,12 @@ pub(crate) fn commit_folder(folder: &super::ast::Folder) -> Result<(), ProtoErro
     let folder_name = folder.name.to_string();
     let destination_path = Path::new(&folder_name);
     if destination_path.exists() {
-        remove_dir_all(&destination_path).map_err(ProtoError::IOError)?;
+        remove_dir_all(destination_path).map_err(|e| ProtoError::io(destination_path, e))?;
     }
-    create_dir(destination_path).map_err(ProtoError::IOError)?;
+    create_dir(destination_path).map_err(|e| ProtoError::io(destination_path, e))?;
     destination_path
         .canonicalize()
-        .map_err(ProtoError::IOError)?;
+        .map_err(|e| ProtoError::io(destination_path, e))?;
     write_folder(&destination_path, folder)
 }
 
@@ -24,17 +24,17 @@ fn write_folder(dist: &Path, folder: &super::ast::Folder) -> Result<(), ProtoErr
         match entry {
             super::ast::FolderEntry::Folder(subfolder) => {
                 let destination_path = dist.join(&subfolder.name.to_string());
-                create_dir(&destination_path).map_err(ProtoError::IOError)?;
+                create_dir(&destination_path).map_err(|e| ProtoError::io(&destination_path, e))?;
                 write_folder(&destination_path, subfolder)?;
             }
             super::ast::FolderEntry::File(file) => {
                 let out_file_path = dist.join(format!("{}.ts", &file.name));
-                let mut out_file =
-                    std::fs::File::create(out_file_path).map_err(ProtoError::IOError)?;
+                let mut out_file = std::fs::File::create(&out_file_path)
+                    .map_err(|e| ProtoError::io(&out_file_path, e))?;
                 let content: String = file.as_ref().into();
                 out_file
                     .write_all(content.as_bytes())
-                    .map_err(ProtoError::IOError)?;
+                    .map_err(|e| ProtoError::io(&out_file_path, e))?;
             }
         }
     }
@@ -0,0 +1,61 @@
+/// How 64-bit integer fields are represented in generated code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum LongType {
+    /// `util.Long` of protobufjs, numbers are accepted on encoding
+    #[default]
+    Long,
+    /// Native `bigint`
+    BigInt,
+    /// Decimal strings
+    String,
+    /// Numbers, which lose precision above 2^53
+    Number,
+}
+
+impl LongType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "long" => Some(Self::Long),
+            "bigint" => Some(Self::BigInt),
+            "string" => Some(Self::String),
+            "number" => Some(Self::Number),
+            _ => None,
+        }
+    }
+}
+
+/// How enums are declared in generated code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum EnumStyle {
+    /// `export enum` with numeric values
+    #[default]
+    Enum,
+    /// `export const enum` with numeric values, which are inlined by TypeScript
+    ConstEnum,
+    /// Union of string literals of value names, e.g. `"RED" | "GREEN"`
+    StringUnion,
+    /// Object of numeric values `as const` and the union type of its values
+    AsConst,
+}
+
+impl EnumStyle {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "enum" => Some(Self::Enum),
+            "const-enum" => Some(Self::ConstEnum),
+            "string-union" => Some(Self::StringUnion),
+            "as-const" => Some(Self::AsConst),
+            _ => None,
+        }
+    }
+}
+
+/// Options of generated code, which are chosen by CLI arguments
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CompileOptions {
+    pub long_type: LongType,
+    /// Options of each `oneof` are a discriminated union property of the group
+    /// instead of independent optional properties
+    pub oneof_unions: bool,
+    pub enum_style: EnumStyle,
+}
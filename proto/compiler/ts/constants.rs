@@ -1,6 +1,16 @@
 pub(super) const PROTOBUF_MODULE: &'static str = "protobufjs/minimal";
 pub(super) const ENCODE_FUNCTION_NAME: &'static str = "encode";
 pub(super) const DECODE_FUNCTION_NAME: &'static str = "decode";
+pub(super) const TO_JSON_FUNCTION_NAME: &'static str = "toJSON";
+pub(super) const FROM_JSON_FUNCTION_NAME: &'static str = "fromJSON";
+/// File at the root of the output folder with helpers shared by `toJSON` and `fromJSON` files
+pub(super) const JSON_RUNTIME_FILE_NAME: &str = "jsonRuntime";
+/// Message property holding known extension fields by field number
+pub(super) const EXTENSIONS_PROPERTY: &'static str = "$extensions";
+/// Discriminant of `oneof` group unions, which holds name of the set option
+pub(super) const ONE_OF_CASE_PROPERTY: &str = "$case";
+/// Property of unknown values of open enums, which holds the number read from the wire
+pub(super) const UNRECOGNIZED_PROPERTY: &str = "UNRECOGNIZED";
 
 // {
 //     long: {
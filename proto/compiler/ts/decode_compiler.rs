@@ -2,7 +2,7 @@ use std::{ops::Deref, rc::Rc};
 
 use crate::proto::{
     compiler::ts::{ast::ElementAccess, ts_path::TsPath},
-    error::ProtoError,
+    error::{located_error, ErrorCode, ProtoError},
     package,
     proto_scope::{root_scope::RootScope, ProtoScope},
 };
@@ -12,9 +12,13 @@ use super::{
         self, BinaryOperator, Block, CaseClause, ExpressionChain, MethodCall, ObjectLiteralMember,
         Prop, StatementList, StatementPlacer, VariableDeclarationList,
     },
-    constants::{DECODE_FUNCTION_NAME, PROTOBUF_MODULE},
+    constants::{DECODE_FUNCTION_NAME, EXTENSIONS_PROPERTY, PROTOBUF_MODULE},
     ensure_import::ensure_import,
+    enum_style::{enum_literal, enum_reader},
     get_relative_import::get_relative_import_string,
+    has_property::has_property,
+    long_type::{default_value_expression, is_long_object, long_default_literal, read_basic_value},
+    one_of_union::{one_of_case_value, union_one_of_group},
 };
 
 pub(super) fn compile_decode(
@@ -30,6 +34,7 @@ pub(super) fn compile_decode(
     let message_type_id: Rc<ast::Identifier> = ast::Identifier::from(message_scope.name()).into();
     let reader_parameter_id: Rc<ast::Identifier> = ast::Identifier::from("reader").into();
     let length_parameter_id: Rc<ast::Identifier> = ast::Identifier::from("length").into();
+    let end_tag_parameter_id: Rc<ast::Identifier> = ast::Identifier::from("endTag").into();
     let reader_var_id: Rc<ast::Identifier> = ast::Identifier::from("r").into();
     let end_var_id: Rc<ast::Identifier> = ast::Identifier::from("end").into();
     let tag_var_id: Rc<ast::Identifier> = ast::Identifier::from("tag").into();
@@ -86,6 +91,12 @@ pub(super) fn compile_decode(
         ast::Type::Number,
     ));
 
+    // Group is decoded up to its end group tag instead of known length
+    decode_function_declaration.add_param(ast::Parameter::new_optional(
+        &end_tag_parameter_id,
+        ast::Type::Number,
+    ));
+
     decode_function_declaration.returns(ast::Type::from_id(&message_type_id));
 
     let reader_parameter_expr = ast::Expression::Identifier(Rc::clone(&reader_parameter_id)).into();
@@ -135,7 +146,7 @@ pub(super) fn compile_decode(
         .into(),
     ));
 
-    let default_message_value = get_default_message_value(message_scope);
+    let default_message_value = get_default_message_value(root, &mut file, message_scope)?;
 
     decode_function_declaration.push_statement(ast::Statement::VariableStatement(
         ast::VariableDeclarationList::declare_typed_const(
@@ -166,6 +177,17 @@ pub(super) fn compile_decode(
 
         let tag_var_expr = Rc::new(tag_var_id.into());
 
+        while_loop.push_statement(ast::Statement::IfStatement(ast::IfStatement {
+            expression: BinaryOperator::StrictEqual
+                .apply(
+                    Rc::clone(&tag_var_expr),
+                    Rc::new(Rc::clone(&end_tag_parameter_id).into()),
+                )
+                .into(),
+            then_statement: ast::Statement::Break.into(),
+            else_statement: None,
+        }));
+
         {
             let mut switch_stmt = while_loop.place(ast::SwitchStatement::new(
                 BinaryOperator::UnsignedRightShift
@@ -188,11 +210,22 @@ pub(super) fn compile_decode(
                 .get_message_declaration()
                 .map(|d| d.get_fields())
                 .unwrap_or_else(Vec::new);
-            if fields.iter().any(|f| match &f.field_type {
-                package::Type::Repeated(_) => true,
-                package::Type::Map(_, _) => true,
-                _ => false,
-            }) {
+            let extensions = root
+                .extensions
+                .get(&message_scope.id().unwrap())
+                .map(|e| &e[..])
+                .unwrap_or_default();
+            let extension_fields = extensions.iter().map(|e| &e.field);
+            if fields
+                .iter()
+                .copied()
+                .chain(extension_fields)
+                .any(|f| match &f.field_type {
+                    package::Type::Repeated(_) => true,
+                    package::Type::Map(_, _) => true,
+                    _ => f.is_required(),
+                })
+            {
                 let utils_import = ast::ImportDeclaration::import(
                     vec![ast::ImportSpecifier {
                         name: Rc::clone(&util_id),
@@ -203,17 +236,60 @@ pub(super) fn compile_decode(
 
                 ensure_import(&mut file, utils_import);
             }
-            for field in fields {
-                let name = field.json_name();
+            let extensions_expr: Rc<ast::Expression> =
+                ast::Expression::from(Rc::clone(&message_var_id))
+                    .into_prop(EXTENSIONS_PROPERTY)
+                    .into();
+            // Known extensions are decoded into `$extensions` by field number
+            let field_targets = fields
+                .into_iter()
+                .map(|field| {
+                    let field_value_ref: Rc<ast::Expression> =
+                        ast::Expression::from(Rc::clone(&message_var_id))
+                            .into_prop(&field.json_name())
+                            .into();
+                    let one_of_ref: Option<Rc<ast::Expression>> = message_scope
+                        .get_message_declaration()
+                        .and_then(|d| union_one_of_group(root, d, field))
+                        .map(|one_of| {
+                            ast::Expression::from(Rc::clone(&message_var_id))
+                                .into_prop(&one_of.name)
+                                .into()
+                        });
+                    (field, field_value_ref, one_of_ref)
+                })
+                .chain(extensions.iter().map(|extension| {
+                    let field = &extension.field;
+                    let field_value_ref: Rc<ast::Expression> =
+                        extensions_expr.element(Rc::new(field.tag.into())).into();
+                    (field, field_value_ref, None)
+                }));
+            for (field, field_value_ref, one_of_ref) in field_targets {
                 let id = field.tag;
+                let json_name = field.json_name();
+                // Options of `oneof` unions are stored together with `$case`
+                let assign_value = |value: Rc<ast::Expression>| match &one_of_ref {
+                    Some(one_of_ref) => BinaryOperator::Assign.apply(
+                        Rc::clone(one_of_ref),
+                        one_of_case_value(&json_name, value).into(),
+                    ),
+                    None => BinaryOperator::Assign.apply(Rc::clone(&field_value_ref), value),
+                };
+                // Numbers of string union enums are converted into names
+                let enum_reader = enum_reader(root, message_scope, &mut file, &field.field_type);
+                let read_enum = |value: Rc<ast::Expression>| match &enum_reader {
+                    Some(enum_reader) => enum_reader.apply(value),
+                    None => value,
+                };
+                let closed_enum_values = match &field.field_type {
+                    package::Type::Repeated(t) => closed_enum_values(root, t),
+                    package::Type::Map(_, vt) => closed_enum_values(root, vt),
+                    t => closed_enum_values(root, t),
+                };
                 let field_type = match &field.field_type {
                     package::Type::Enum(_) => &package::Type::Int32,
                     t => t,
                 };
-                let field_value_ref: Rc<ast::Expression> =
-                    ast::Expression::from(Rc::clone(&message_var_id))
-                        .into_prop(&name)
-                        .into();
                 let mut case_clause = ast::CaseClause::new(Rc::new(id.into()));
 
                 match field_type {
@@ -223,17 +299,12 @@ pub(super) fn compile_decode(
                             import_decode_func(&root, &message_scope, &mut file, *m_id);
 
                         case_clause.push_statement(
-                            ast::BinaryOperator::Assign
-                                .apply(
-                                    Rc::clone(&field_value_ref),
-                                    decode_func_expr
-                                        .into_call(vec![
-                                            Rc::clone(&reader_var_expr),
-                                            reader_var_expr.method_call("uint32", vec![]).into(),
-                                        ])
-                                        .into(),
-                                )
-                                .into(),
+                            assign_value(
+                                decode_func_expr
+                                    .into_call(decode_message_args(&reader_var_expr, field))
+                                    .into(),
+                            )
+                            .into(),
                         );
                     }
                     package::Type::Repeated(t) => {
@@ -266,12 +337,38 @@ pub(super) fn compile_decode(
 
                         match element_type.packed_wire_type() {
                             Some(_) => {
-                                let parse_element_expr = Rc::new(field_value_ref.method_call(
-                                    "push",
-                                    vec![reader_var_expr
-                                        .method_call(&element_type.to_string(), vec![])
-                                        .into()],
-                                ));
+                                let read_element = read_enum(
+                                    read_basic_value(
+                                        root,
+                                        &mut file,
+                                        &reader_var_expr,
+                                        &element_type,
+                                    )
+                                    .into(),
+                                );
+                                let parse_element = |statements: &mut dyn StatementList| {
+                                    match &closed_enum_values {
+                                        Some(values) => decode_closed_enum_value(
+                                            statements,
+                                            &reader_var_expr,
+                                            values,
+                                            |value| {
+                                                field_value_ref
+                                                    .method_call("push", vec![read_enum(value)])
+                                            },
+                                        ),
+                                        None => {
+                                            statements.push_statement(ast::Statement::Expression(
+                                                field_value_ref
+                                                    .method_call(
+                                                        "push",
+                                                        vec![Rc::clone(&read_element)],
+                                                    )
+                                                    .into(),
+                                            ))
+                                        }
+                                    }
+                                };
 
                                 let mut packed_block = Block::new();
 
@@ -300,9 +397,14 @@ pub(super) fn compile_decode(
                                         .into(),
                                 );
 
-                                element_while.push_statement(ast::Statement::Expression(
-                                    Rc::clone(&parse_element_expr),
-                                ));
+                                parse_element(&mut element_while);
+                                let mut parse_element_block = Block::new();
+                                parse_element(&mut parse_element_block);
+                                let parse_element_stmt: Rc<ast::Statement> =
+                                    match &parse_element_block.statements[..] {
+                                        [single] => Rc::clone(single),
+                                        _ => Rc::new(parse_element_block.into()),
+                                    };
 
                                 packed_block.push_statement(element_while.into());
 
@@ -321,12 +423,7 @@ pub(super) fn compile_decode(
                                             )
                                             .into(),
                                         then_statement: Rc::new(packed_block.into()),
-                                        else_statement: Some(
-                                            ast::Statement::Expression(Rc::clone(
-                                                &parse_element_expr,
-                                            ))
-                                            .into(),
-                                        ),
+                                        else_statement: Some(parse_element_stmt),
                                     }
                                     .into(),
                                 );
@@ -342,24 +439,21 @@ pub(super) fn compile_decode(
                                         field_value_ref.method_call(
                                             "push",
                                             vec![decode_func
-                                                .into_call(vec![
-                                                    Rc::clone(&reader_var_expr),
-                                                    reader_var_expr
-                                                        .method_call("uint32", vec![])
-                                                        .into(),
-                                                ])
+                                                .into_call(decode_message_args(
+                                                    &reader_var_expr,
+                                                    field,
+                                                ))
                                                 .into()],
                                         ),
                                     ))
                                 }
                                 basic => {
-                                    let basic_str = basic.to_string();
+                                    let read_element =
+                                        read_basic_value(root, &mut file, &reader_var_expr, basic);
                                     case_clause.push_statement(ast::Statement::from(
                                         field_value_ref.method_call(
                                             "push",
-                                            vec![reader_var_expr
-                                                .method_call(&basic_str, vec![])
-                                                .into()],
+                                            vec![read_enum(read_element.into())],
                                         ),
                                     ))
                                 }
@@ -415,7 +509,7 @@ pub(super) fn compile_decode(
                                 VariableDeclarationList::declare_typed_let(
                                     Rc::clone(&key_id),
                                     ast::Type::Any.into(),
-                                    kt.default_expression().into(),
+                                    default_value_expression(root, kt),
                                 )
                                 .into(),
                             )
@@ -426,7 +520,7 @@ pub(super) fn compile_decode(
                                 VariableDeclarationList::declare_typed_let(
                                     Rc::clone(&val_id),
                                     ast::Type::Any.into(),
-                                    value_type.default_expression().into(),
+                                    default_value_expression(root, &value_type),
                                 )
                                 .into(),
                             )
@@ -474,13 +568,11 @@ pub(super) fn compile_decode(
                                     ));
 
                                 let mut key_case = CaseClause::new(Rc::new(1.into()));
-                                let kt_string = kt.to_string();
+                                let read_key =
+                                    read_basic_value(root, &mut file, &reader_var_expr, kt);
                                 key_case.push_statement(ast::Statement::Expression(
                                     BinaryOperator::Assign
-                                        .apply(
-                                            Rc::clone(&key_expr),
-                                            reader_var_expr.method_call(&kt_string, vec![]).into(),
-                                        )
+                                        .apply(Rc::clone(&key_expr), read_key.into())
                                         .into(),
                                 ));
                                 key_case.push_statement(ast::Statement::Break);
@@ -512,15 +604,15 @@ pub(super) fn compile_decode(
                                     }
                                     basic => {
                                         assert!(basic.is_basic());
-                                        let b_str = basic.to_string();
+                                        let read_value = read_basic_value(
+                                            root,
+                                            &mut file,
+                                            &reader_var_expr,
+                                            basic,
+                                        );
                                         val_case.push_statement(ast::Statement::Expression(
                                             BinaryOperator::Assign
-                                                .apply(
-                                                    Rc::clone(&val_expr),
-                                                    reader_var_expr
-                                                        .method_call(&b_str, vec![])
-                                                        .into(),
-                                                )
+                                                .apply(Rc::clone(&val_expr), read_value.into())
                                                 .into(),
                                         ));
                                     }
@@ -530,52 +622,74 @@ pub(super) fn compile_decode(
                             }
                         }
 
+                        // `Long` keys are converted into hashes like protobufjs does
                         match kt.long_wire_type() {
-                            Some(_) => case_clause.push_statement(ast::Statement::Expression(
-                                BinaryOperator::Assign
-                                    .apply(
-                                        Rc::clone(&key_expr),
-                                        ast::Expression::conditional(
-                                            BinaryOperator::StrictEqual
-                                                .apply(
-                                                    key_expr.type_of().into(),
-                                                    Rc::new(
-                                                        ast::StringLiteral::new("object".into())
-                                                            .into(),
-                                                    ),
-                                                )
-                                                .into(),
-                                            util_expr
-                                                .method_call(
-                                                    "longToHash",
-                                                    vec![Rc::clone(&key_expr)],
-                                                )
-                                                .into(),
+                            Some(_) if is_long_object(root) => {
+                                case_clause.push_statement(ast::Statement::Expression(
+                                    BinaryOperator::Assign
+                                        .apply(
                                             Rc::clone(&key_expr),
+                                            ast::Expression::conditional(
+                                                BinaryOperator::StrictEqual
+                                                    .apply(
+                                                        key_expr.type_of().into(),
+                                                        Rc::new(
+                                                            ast::StringLiteral::new(
+                                                                "object".into(),
+                                                            )
+                                                            .into(),
+                                                        ),
+                                                    )
+                                                    .into(),
+                                                util_expr
+                                                    .method_call(
+                                                        "longToHash",
+                                                        vec![Rc::clone(&key_expr)],
+                                                    )
+                                                    .into(),
+                                                Rc::clone(&key_expr),
+                                            )
+                                            .into(),
                                         )
                                         .into(),
-                                    )
-                                    .into(),
-                            )),
-                            None => {}
+                                ))
+                            }
+                            _ => {}
                         }
-                        case_clause.push_statement(ast::Statement::Expression(
+                        let assign_entry_stmt = ast::Statement::Expression(
                             BinaryOperator::Assign
                                 .apply(
                                     field_value_ref.element(Rc::clone(&key_expr)).into(),
-                                    Rc::clone(&val_expr),
+                                    read_enum(Rc::clone(&val_expr)),
                                 )
                                 .into(),
-                        ));
-                    }
-                    basic => case_clause.push_statement(
-                        ast::BinaryOperator::Assign
-                            .apply(
-                                Rc::clone(&field_value_ref),
-                                Rc::new(reader_var_expr.method_call(&basic.to_string(), vec![])),
-                            )
+                        );
+                        case_clause.push_statement(match &closed_enum_values {
+                            // Entry with unknown value of closed enum is skipped as a whole
+                            Some(values) => ast::IfStatement {
+                                expression: is_known_enum_value(Rc::clone(&val_expr), values)
+                                    .into(),
+                                then_statement: assign_entry_stmt.into(),
+                                else_statement: None,
+                            }
                             .into(),
-                    ),
+                            None => assign_entry_stmt,
+                        });
+                    }
+                    basic => match &closed_enum_values {
+                        Some(values) => decode_closed_enum_value(
+                            &mut case_clause,
+                            &reader_var_expr,
+                            values,
+                            |value| assign_value(read_enum(value)),
+                        ),
+                        None => {
+                            let read_value =
+                                read_basic_value(root, &mut file, &reader_var_expr, basic);
+                            case_clause
+                                .push_statement(assign_value(read_enum(read_value.into())).into())
+                        }
+                    },
                 }
 
                 case_clause.push_statement(ast::Statement::Break);
@@ -585,6 +699,40 @@ pub(super) fn compile_decode(
         }
     }
 
+    let message_var_expr: Rc<ast::Expression> =
+        ast::Expression::from(Rc::clone(&message_var_id)).into();
+    for field in message_scope
+        .get_message_declaration()
+        .map(|d| d.get_fields())
+        .unwrap_or_default()
+    {
+        if !field.is_required() {
+            continue;
+        }
+        let json_name = field.json_name();
+        let is_missing_expr = has_property(
+            Rc::clone(&message_var_expr),
+            ast::Identifier::from(Rc::clone(&json_name)).into(),
+        )
+        .not();
+        let protocol_error_expr = util_expr.prop("ProtocolError").into_call(vec![
+            Rc::new(ast::Expression::StringLiteral(
+                format!("missing required '{}'", json_name).into(),
+            )),
+            Rc::new(ast::Expression::ObjectLiteralExpression(vec![Rc::new(
+                ObjectLiteralMember::PropertyAssignment(
+                    Rc::new("instance".into()),
+                    Rc::clone(&message_var_expr),
+                ),
+            )])),
+        ]);
+        decode_function_declaration.push_statement(ast::Statement::IfStatement(ast::IfStatement {
+            expression: is_missing_expr.into(),
+            then_statement: ast::Statement::Throw(protocol_error_expr).into(),
+            else_statement: None,
+        }));
+    }
+
     decode_function_declaration
         .push_statement(ast::Expression::from(message_var_id).into_return_statement());
 
@@ -596,21 +744,211 @@ pub(super) fn compile_decode(
     Ok(())
 }
 
-fn get_default_message_value(message_scope: &ProtoScope) -> ast::Expression {
-    ast::Expression::ObjectLiteralExpression(
-        message_scope
-            .get_message_declaration()
-            .unwrap()
-            .get_fields()
-            .into_iter()
-            .map(|f| {
-                let n = f.json_name();
-                let default_value = f.field_type.default_expression();
-                ObjectLiteralMember::PropertyAssignment(Rc::new(n.into()), default_value.into())
-                    .into()
-            })
+/// Arguments of nested message decode call: length prefix or end group tag
+fn decode_message_args(
+    reader_var_expr: &Rc<ast::Expression>,
+    field: &package::Field,
+) -> Vec<Rc<ast::Expression>> {
+    if field.delimited {
+        let end_group_tag = field.tag << 3 | package::Type::END_GROUP_WIRE_TYPE as i64;
+        vec![
+            Rc::clone(reader_var_expr),
+            ast::Expression::Undefined.into(),
+            Rc::new((end_group_tag as f64).into()),
+        ]
+    } else {
+        vec![
+            Rc::clone(reader_var_expr),
+            reader_var_expr.method_call("uint32", vec![]).into(),
+        ]
+    }
+}
+
+/// Known values of closed enum referenced by `field_type`,
+/// other values are skipped on decoding
+fn closed_enum_values(root: &RootScope, field_type: &package::Type) -> Option<Vec<i64>> {
+    let enum_id = match field_type {
+        package::Type::Enum(enum_id) => *enum_id,
+        _ => return None,
+    };
+    match root.get_declaration(enum_id)?.deref() {
+        ProtoScope::Enum(e) if e.closed => {
+            Some(e.entries.iter().map(|entry| entry.value).collect())
+        }
+        _ => None,
+    }
+}
+
+fn is_known_enum_value(value: Rc<ast::Expression>, known_values: &[i64]) -> ast::Expression {
+    let known_values_expr = ast::Expression::ArrayLiteralExpression(
+        known_values
+            .iter()
+            .map(|known_value| Rc::new((*known_value as f64).into()))
             .collect(),
-    )
+    );
+    Rc::new(known_values_expr).method_call("includes", vec![value])
+}
+
+/// Reads closed enum value and passes it to `store` if it is known
+fn decode_closed_enum_value(
+    statements: &mut dyn StatementList,
+    reader_var_expr: &Rc<ast::Expression>,
+    known_values: &[i64],
+    store: impl Fn(Rc<ast::Expression>) -> ast::Expression,
+) {
+    let value_id: Rc<ast::Identifier> = ast::Identifier::from("value").into();
+    let value_expr: Rc<ast::Expression> = ast::Expression::from(Rc::clone(&value_id)).into();
+    statements.push_statement(
+        VariableDeclarationList::declare_const(
+            value_id,
+            reader_var_expr.method_call("int32", vec![]),
+        )
+        .into(),
+    );
+    statements.push_statement(
+        ast::IfStatement {
+            expression: is_known_enum_value(Rc::clone(&value_expr), known_values).into(),
+            then_statement: ast::Statement::Expression(store(value_expr).into()).into(),
+            else_statement: None,
+        }
+        .into(),
+    );
+}
+
+pub(super) fn get_default_message_value(
+    root: &RootScope,
+    file: &mut ast::File,
+    message_scope: &ProtoScope,
+) -> Result<ast::Expression, ProtoError> {
+    let mut members = Vec::new();
+    let message_declaration = message_scope.get_message_declaration().unwrap();
+    for f in message_declaration.get_fields() {
+        // Absent oneof options tell which one of them is set
+        if f.is_optional() || f.is_required() || message_declaration.is_one_of_field(f) {
+            continue;
+        }
+        let n = f.json_name();
+        let default_value = match f.default_value() {
+            Some(value) => custom_default_expression(root, file, f, value)?,
+            None => default_value_expression(root, &f.field_type),
+        };
+        members.push(
+            ObjectLiteralMember::PropertyAssignment(Rc::new(n.into()), default_value.into()).into(),
+        );
+    }
+    if root.extensions.contains_key(&message_scope.id().unwrap()) {
+        members.push(
+            ObjectLiteralMember::PropertyAssignment(
+                Rc::new(EXTENSIONS_PROPERTY.into()),
+                ast::Expression::ObjectLiteralExpression(vec![]).into(),
+            )
+            .into(),
+        );
+    }
+    Ok(ast::Expression::ObjectLiteralExpression(members))
+}
+
+/// Converts `[default = ...]` of a proto2 field into the initial value
+fn custom_default_expression(
+    root: &RootScope,
+    file: &mut ast::File,
+    field: &package::Field,
+    value: &package::OptionValue,
+) -> Result<ast::Expression, ProtoError> {
+    use package::{OptionValue, Type};
+    let expr = match (&field.field_type, value) {
+        (Type::Bool, OptionValue::Bool(true)) => ast::Expression::True,
+        (Type::Bool, OptionValue::Bool(false)) => ast::Expression::False,
+        (Type::String, OptionValue::String(_)) => match value.as_str() {
+            Some(s) => ast::Expression::StringLiteral(s.into()),
+            None => {
+                return Err(located_error(
+                    ErrorCode::InvalidDefault,
+                    format!(
+                        "Default value of string field {} is not valid UTF-8",
+                        field.name
+                    ),
+                    field.range.as_ref(),
+                ))
+            }
+        },
+        (Type::Bytes, OptionValue::String(s)) => {
+            let mut new_expr = ast::NewExpression::new(ast::Expression::from("Uint8Array").into());
+            new_expr.add_argument(
+                ast::Expression::ArrayLiteralExpression(
+                    s.iter().map(|b| Rc::new((*b as f64).into())).collect(),
+                )
+                .into(),
+            );
+            new_expr.into()
+        }
+        (Type::Enum(enum_id), OptionValue::Identifier(name)) => {
+            let value = root
+                .get_declaration(*enum_id)
+                .and_then(|scope| match scope.deref() {
+                    ProtoScope::Enum(e) => e
+                        .entries
+                        .iter()
+                        .find(|entry| entry.name == *name)
+                        .map(|entry| entry.value),
+                    _ => None,
+                });
+            match value {
+                Some(value) => enum_literal(root, *enum_id, value),
+                None => {
+                    return Err(located_error(
+                        ErrorCode::InvalidDefault,
+                        format!(
+                            "Enum value {} of default for field {} is not found",
+                            name, field.name
+                        ),
+                        field.range.as_ref(),
+                    ))
+                }
+            }
+        }
+        (Type::Double | Type::Float, OptionValue::Float(f)) if f.is_nan() => {
+            ast::Expression::from("NaN")
+        }
+        (Type::Double | Type::Float, OptionValue::Float(f)) if f.is_infinite() => {
+            ast::Expression::from(if *f > 0f64 { "Infinity" } else { "-Infinity" })
+        }
+        (Type::Double | Type::Float, OptionValue::Float(f)) => (*f).into(),
+        (Type::Double | Type::Float, OptionValue::Identifier(id)) if id.deref() == "inf" => {
+            ast::Expression::from("Infinity")
+        }
+        (Type::Double | Type::Float, OptionValue::Identifier(id)) if id.deref() == "nan" => {
+            ast::Expression::from("NaN")
+        }
+        (t, OptionValue::Int(i)) if t.int_range().is_some_and(|range| !range.contains(i)) => {
+            return Err(located_error(
+                ErrorCode::InvalidDefault,
+                format!(
+                    "Default value {} is out of range of {} field {}",
+                    i,
+                    t.to_string(),
+                    field.name
+                ),
+                field.range.as_ref(),
+            ))
+        }
+        (t, OptionValue::Int(i)) if t.long_wire_type().is_some() => {
+            long_default_literal(root, file, t, *i)
+        }
+        (t, OptionValue::Int(i))
+            if t.is_basic() && !matches!(t, Type::Bool | Type::String | Type::Bytes) =>
+        {
+            (*i as f64).into()
+        }
+        _ => {
+            return Err(located_error(
+                ErrorCode::InvalidDefault,
+                format!("Invalid default value {} for field {}", value, field.name),
+                field.range.as_ref(),
+            ))
+        }
+    };
+    Ok(expr)
 }
 
 fn import_decode_func(
@@ -650,3 +988,70 @@ fn import_decode_func(
         None => DECODE_FUNCTION_NAME.into(),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::proto::{
+        compiler::ts::{
+            codegen_test::compile,
+            compile_options::{CompileOptions, LongType},
+        },
+        error::ErrorCode,
+    };
+
+    #[test]
+    fn compiles_defaults_beyond_int64_and_utf8() {
+        let files = compile(
+            r#"
+            syntax = "proto2";
+            message Hello {
+                optional uint64 a = 1 [default = 18446744073709551615];
+                optional bytes b = 2 [default = "\xff\0"];
+                optional int64 c = 3 [default = -9223372036854775808];
+            }
+        "#,
+            CompileOptions {
+                long_type: LongType::String,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let decode = &files["test/Hello/decode.ts"];
+        assert!(decode.contains("a: \"18446744073709551615\","));
+        assert!(decode.contains("b: new Uint8Array([255, 0]),"));
+        assert!(decode.contains("c: \"-9223372036854775808\","));
+    }
+
+    #[test]
+    fn escapes_string_defaults() {
+        let files = compile(
+            r#"
+            syntax = "proto2";
+            message Hello {
+                optional string s = 1 [default = "hi\n\"there\"\t\\ \u2028"];
+            }
+        "#,
+            CompileOptions::default(),
+        )
+        .unwrap();
+        let decode = &files["test/Hello/decode.ts"];
+        assert!(
+            decode.contains("    s: \"hi\\n\\\"there\\\"\\t\\\\ \\u2028\",\n"),
+            "{}",
+            decode
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_defaults() {
+        for field in [
+            r#"optional string s = 1 [default = "\xff"];"#,
+            "optional int32 i = 1 [default = 2147483648];",
+            "optional uint64 u = 1 [default = -1];",
+        ] {
+            let content = format!("syntax = \"proto2\";\nmessage Hello {{ {} }}", field);
+            let error = compile(&content, CompileOptions::default()).unwrap_err();
+            assert_eq!(error.code(), Some(ErrorCode::InvalidDefault), "{}", field);
+        }
+    }
+}
@@ -3,14 +3,24 @@ use std::rc::Rc;
 use crate::proto::{
     compiler::ts::ast::{ElementAccess, MethodCall, MethodChain, StatementList},
     package::{self},
+    proto_scope::root_scope::RootScope,
 };
 
-use super::ast::{self, ForStatement, Prop};
+use super::{
+    ast::{self, ForStatement, Prop},
+    enum_style::EnumConversion,
+    long_type::write_basic_value,
+};
 
+#[allow(clippy::too_many_arguments)]
 pub(super) fn encode_basic_repeated_type_field(
+    root: &RootScope,
+    file: &mut ast::File,
     field_value: &Rc<ast::Expression>,
     field_type: &package::Type,
+    enum_writer: Option<&EnumConversion>,
     field_tag: i64,
+    packed: bool,
     writer_var: &Rc<ast::Identifier>,
 ) -> ast::Statement {
     let field_exists_expression = ast::BinaryOperator::LogicalAnd
@@ -27,9 +37,25 @@ pub(super) fn encode_basic_repeated_type_field(
         package::Type::Message(_) => unreachable!(),
         package::Type::Repeated(_) => unreachable!(),
         package::Type::Map(_, _) => unreachable!(),
-        basic => match basic.packed_wire_type() {
-            Some(_) => encode_packed_elements(&field_value, basic, field_tag, &writer_var),
-            None => encode_non_packed_elements(&field_value, basic, field_tag, &writer_var),
+        basic => match basic.packed_wire_type().filter(|_| packed) {
+            Some(_) => encode_packed_elements(
+                root,
+                file,
+                field_value,
+                basic,
+                enum_writer,
+                field_tag,
+                writer_var,
+            ),
+            None => encode_non_packed_elements(
+                root,
+                file,
+                field_value,
+                basic,
+                enum_writer,
+                field_tag,
+                writer_var,
+            ),
         },
     };
 
@@ -41,8 +67,11 @@ pub(super) fn encode_basic_repeated_type_field(
 }
 
 fn encode_non_packed_elements(
+    root: &RootScope,
+    file: &mut ast::File,
     field_value: &Rc<ast::Expression>,
     element_type: &package::Type,
+    enum_writer: Option<&EnumConversion>,
     field_tag: i64,
     writer_var: &Rc<ast::Identifier>,
 ) -> ast::Statement {
@@ -66,7 +95,11 @@ fn encode_non_packed_elements(
     let i_id = Rc::new(ast::Identifier::new("i"));
     let i_id_expr = Rc::new(Rc::clone(&i_id).into());
 
-    let element_value_expr: Rc<ast::Expression> = field_value.element(i_id_expr).into();
+    let element_expr: Rc<ast::Expression> = field_value.element(i_id_expr).into();
+    let element_value_expr = match enum_writer {
+        Some(enum_writer) => enum_writer.apply(element_expr),
+        None => write_basic_value(root, file, element_type, element_expr),
+    };
 
     let type_str = element_type.to_string();
     let encode_element_expr: Rc<ast::Expression> = Rc::new(tag_encoding_expr)
@@ -81,8 +114,11 @@ fn encode_non_packed_elements(
     ast::Statement::Block(res)
 }
 fn encode_packed_elements(
+    root: &RootScope,
+    file: &mut ast::File,
     field_value: &Rc<ast::Expression>,
     element_type: &package::Type,
+    enum_writer: Option<&EnumConversion>,
     field_tag: i64,
     writer_var: &Rc<ast::Identifier>,
 ) -> ast::Statement {
@@ -110,7 +146,11 @@ fn encode_packed_elements(
     let i_id_expr = Rc::new(ast::Expression::Identifier(Rc::clone(&i_id)));
     let mut for_stmt = ForStatement::for_each(i_id, Rc::clone(&field_value));
 
-    let element_value_expr: Rc<ast::Expression> = field_value.element(i_id_expr).into();
+    let element_expr: Rc<ast::Expression> = field_value.element(i_id_expr).into();
+    let element_value_expr = match enum_writer {
+        Some(enum_writer) => enum_writer.apply(element_expr),
+        None => write_basic_value(root, file, element_type, element_expr),
+    };
 
     let type_str = element_type.to_string();
     let encode_element_expr: Rc<ast::Expression> = writer_expr
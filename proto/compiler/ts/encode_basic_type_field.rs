@@ -3,11 +3,18 @@ use std::rc::Rc;
 use crate::proto::{
     compiler::ts::has_property::has_property,
     package::{self},
+    proto_scope::root_scope::RootScope,
 };
 
-use super::ast::{self, Identifier, MethodCall};
+use super::{
+    ast::{self, Identifier, MethodCall},
+    long_type::write_basic_value,
+};
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn encode_basic_type_field(
+    root: &RootScope,
+    file: &mut ast::File,
     field_value: &Rc<ast::Expression>,
     message_parameter_id: &Rc<Identifier>,
     js_name_id: &Rc<Identifier>,
@@ -38,8 +45,8 @@ pub(crate) fn encode_basic_type_field(
     );
 
     let type_str = field_type.to_string();
-    let encode_field_stmt =
-        Rc::new(tag_encoding_expr).method_call(&type_str, vec![Rc::clone(&field_value)]);
+    let written_value = write_basic_value(root, file, field_type, Rc::clone(field_value));
+    let encode_field_stmt = Rc::new(tag_encoding_expr).method_call(&type_str, vec![written_value]);
     ast::Statement::IfStatement(ast::IfStatement {
         expression: field_exists_expression,
         then_statement: ast::Statement::from(ast::Block {
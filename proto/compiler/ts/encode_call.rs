@@ -1,6 +1,7 @@
 use std::rc::Rc;
 
-use super::ast::{self, MethodChain};
+use super::ast::{self, MethodCall, MethodChain};
+use crate::proto::package;
 
 pub(super) fn encode_call(
     encode_func_expr: ast::Expression,
@@ -24,3 +25,21 @@ pub(super) fn encode_call(
         .into_prop("ldelim")
         .into_call(vec![])
 }
+
+/// Encodes message between start and end group tags
+pub(super) fn encode_group_call(
+    encode_func_expr: ast::Expression,
+    writer_expr: Rc<ast::Expression>,
+    field_tag: i64,
+    field_value: Rc<ast::Expression>,
+) -> ast::Expression {
+    let start_group_tag = field_tag << 3 | package::Type::START_GROUP_WIRE_TYPE as i64;
+    let end_group_tag = field_tag << 3 | package::Type::END_GROUP_WIRE_TYPE as i64;
+    Rc::new(encode_func_expr.into_call(vec![
+        field_value,
+        writer_expr
+            .method_call("uint32", vec![Rc::new((start_group_tag as f64).into())])
+            .into(),
+    ]))
+    .method_call("uint32", vec![Rc::new((end_group_tag as f64).into())])
+}
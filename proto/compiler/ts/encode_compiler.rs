@@ -3,7 +3,8 @@ use std::{ops::Deref, rc::Rc};
 use crate::proto::{
     compiler::ts::{
         encode_basic_repeated_type_field::encode_basic_repeated_type_field,
-        encode_call::encode_call, encode_message_expr::encode_message_expr,
+        encode_call::{encode_call, encode_group_call},
+        encode_message_expr::encode_message_expr,
     },
     error::ProtoError,
     package::{self},
@@ -12,11 +13,12 @@ use crate::proto::{
 
 use super::{
     ast::{self, ElementAccess, Folder, MethodCall, Prop, Type, StatementList},
-    constants::{ENCODE_FUNCTION_NAME, PROTOBUF_MODULE},
+    constants::{ENCODE_FUNCTION_NAME, EXTENSIONS_PROPERTY, ONE_OF_CASE_PROPERTY, PROTOBUF_MODULE},
     encode_basic_type_field::encode_basic_type_field,
     encode_enum_field::encode_enum_field,
     encode_map_field::encode_map_field,
     ensure_import::ensure_import,
+    enum_style::enum_writer,
     has_property::has_property,
     message_name_to_encode_type_name::message_name_to_encode_type_name,
 };
@@ -68,7 +70,6 @@ pub(super) fn compile_encode(
     encode_func.returns(Type::reference(Rc::clone(&writer_type_id)).into());
 
     let writer_var = Rc::new(ast::Identifier { text: "w".into() });
-    let writer_var_expr = Rc::new(ast::Expression::Identifier(Rc::clone(&writer_var)));
 
     encode_func.push_statement(
         ast::Statement::from(ast::VariableDeclarationList::declare_const(
@@ -91,163 +92,349 @@ pub(super) fn compile_encode(
     };
 
     let fields = message_declaration.get_fields();
+    let mut encoded_one_of_groups = Vec::new();
 
     for field in fields {
+        if root.options.oneof_unions {
+            if let Some(one_of) = message_declaration.get_one_of_group(field) {
+                // The whole group is encoded in place of its first option
+                if !encoded_one_of_groups
+                    .iter()
+                    .any(|g| std::ptr::eq(*g, one_of))
+                {
+                    encoded_one_of_groups.push(one_of);
+                    encode_func.push_statement(encode_one_of_union(
+                        root,
+                        message_scope,
+                        &mut file,
+                        &message_parameter_id,
+                        &writer_var,
+                        one_of,
+                    )?);
+                }
+                continue;
+            }
+        }
         let js_name = field.json_name();
         let js_name_id: Rc<ast::Identifier> = ast::Identifier::new(&js_name).into();
         let message_expr: Rc<ast::Expression> = Rc::new(Rc::clone(&message_parameter_id).into());
         let field_value = Rc::new(message_expr.prop(&js_name));
-        match &field.field_type {
-            package::Type::Enum(_) => {
-                encode_func.push_statement(
-                    encode_enum_field(
-                        &message_parameter_id,
-                        &writer_var,
-                        &js_name_id,
-                        field_value,
-                        field.tag,
+        encode_func.push_statement(encode_field(
+            root,
+            message_scope,
+            &mut file,
+            &message_parameter_id,
+            &js_name_id,
+            field_value,
+            &writer_var,
+            field,
+        )?);
+    }
+
+    if let Some(extensions) = root.extensions.get(&message_declaration.id) {
+        // Known extensions are encoded from `$extensions` by field number
+        let extensions_id: Rc<ast::Identifier> = ast::Identifier::new("ext").into();
+        let extensions_expr: Rc<ast::Expression> = Rc::new(Rc::clone(&extensions_id).into());
+        let message_extensions_expr: Rc<ast::Expression> = Rc::new(
+            ast::Expression::from(Rc::clone(&message_parameter_id)).into_prop(EXTENSIONS_PROPERTY),
+        );
+        let mut extensions_block = ast::Block::new();
+        extensions_block.push_statement(
+            ast::VariableDeclarationList::declare_const(
+                Rc::clone(&extensions_id),
+                ast::Expression::from(Rc::clone(&message_parameter_id))
+                    .into_prop(EXTENSIONS_PROPERTY),
+            )
+            .into(),
+        );
+        for extension in extensions {
+            let field = &extension.field;
+            let tag_id: Rc<ast::Identifier> = ast::Identifier::new(&field.tag.to_string()).into();
+            let field_value = Rc::new(extensions_expr.element(Rc::new(field.tag.into())));
+            extensions_block.push_statement(encode_field(
+                root,
+                message_scope,
+                &mut file,
+                &extensions_id,
+                &tag_id,
+                field_value,
+                &writer_var,
+                field,
+            )?);
+        }
+        encode_func.push_statement(ast::Statement::IfStatement(ast::IfStatement {
+            expression: ast::BinaryOperator::WeakNotEqual
+                .apply(message_extensions_expr, ast::Expression::Null.into())
+                .into(),
+            then_statement: ast::Statement::from(extensions_block).into(),
+            else_statement: None,
+        }));
+    }
+
+    encode_func.push_statement(
+        ast::Expression::from(writer_var)
+            .into_return_statement()
+            .into(),
+    );
+
+    file.push_statement(encode_func.into());
+
+    message_folder.push_file(file);
+
+    Ok(())
+}
+
+/// Encodes `oneof` group stored as union discriminated by `$case`:
+/// `if (message.result != null) { const oneOf = message.result; if (oneOf.$case === "ok") ... }`
+fn encode_one_of_union(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    message_parameter_id: &Rc<ast::Identifier>,
+    writer_var: &Rc<ast::Identifier>,
+    one_of: &package::OneOfGroup,
+) -> Result<ast::Statement, ProtoError> {
+    let one_of_id: Rc<ast::Identifier> = ast::Identifier::new("oneOf").into();
+    let one_of_expr: Rc<ast::Expression> = Rc::new(Rc::clone(&one_of_id).into());
+    let group_value =
+        || ast::Expression::from(Rc::clone(message_parameter_id)).into_prop(&one_of.name);
+    let mut options = one_of.fields.iter().collect::<Vec<_>>();
+    options.sort_by_key(|option| option.tag);
+    let mut cases = None;
+    for option in options.into_iter().rev() {
+        let js_name = option.json_name();
+        let js_name_id: Rc<ast::Identifier> = ast::Identifier::new(&js_name).into();
+        let encode_option = encode_field(
+            root,
+            message_scope,
+            file,
+            &one_of_id,
+            &js_name_id,
+            Rc::new(one_of_expr.prop(&js_name)),
+            writer_var,
+            option,
+        )?;
+        let is_case = ast::BinaryOperator::StrictEqual.apply(
+            one_of_expr.prop(ONE_OF_CASE_PROPERTY).into(),
+            ast::Expression::StringLiteral(js_name.as_ref().into()).into(),
+        );
+        cases = Some(ast::Statement::IfStatement(ast::IfStatement {
+            expression: is_case.into(),
+            then_statement: ast::Statement::from(ast::Block {
+                statements: vec![encode_option.into()],
+            })
+            .into(),
+            else_statement: cases.map(Rc::new),
+        }));
+    }
+    let mut group_block = ast::Block::new();
+    group_block.push_statement(
+        ast::VariableDeclarationList::declare_const(one_of_id, group_value()).into(),
+    );
+    if let Some(cases) = cases {
+        group_block.push_statement(cases);
+    }
+    Ok(ast::Statement::IfStatement(ast::IfStatement {
+        expression: ast::BinaryOperator::WeakNotEqual
+            .apply(group_value().into(), ast::Expression::Null.into())
+            .into(),
+        then_statement: ast::Statement::from(group_block).into(),
+        else_statement: None,
+    }))
+}
+
+/// Encodes field stored in `owner_id` object under `js_name_id` property
+#[allow(clippy::too_many_arguments)]
+fn encode_field(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    owner_id: &Rc<ast::Identifier>,
+    js_name_id: &Rc<ast::Identifier>,
+    field_value: Rc<ast::Expression>,
+    writer_var: &Rc<ast::Identifier>,
+    field: &package::Field,
+) -> Result<ast::Statement, ProtoError> {
+    let writer_var_expr = Rc::new(ast::Expression::Identifier(Rc::clone(writer_var)));
+    let statement = match &field.field_type {
+        package::Type::Enum(_) => {
+            let enum_writer = enum_writer(root, message_scope, file, &field.field_type);
+            encode_enum_field(
+                owner_id,
+                writer_var,
+                js_name_id,
+                field_value,
+                enum_writer.as_ref(),
+                field.tag,
+            )
+        }
+        package::Type::Message(m_id) => {
+            let message_id = *m_id;
+
+            let field_exists_expression = ast::BinaryOperator::LogicalAnd
+                .apply(
+                    ast::BinaryOperator::WeakNotEqual
+                        .apply(Rc::clone(&field_value), ast::Expression::Null.into())
+                        .into(),
+                    has_property(
+                        ast::Expression::Identifier(Rc::clone(owner_id)).into(),
+                        Rc::clone(js_name_id),
                     )
                     .into(),
-                );
-            }
+                )
+                .into();
+            let message_encode_expr = encode_message_expr(root, message_scope, file, message_id);
+            let encode_call = if field.delimited {
+                encode_group_call
+            } else {
+                encode_call
+            };
+            let expr = encode_call(
+                message_encode_expr,
+                Rc::clone(&writer_var_expr),
+                field.tag,
+                field_value,
+            );
+
+            ast::Statement::IfStatement(ast::IfStatement {
+                expression: field_exists_expression,
+                then_statement: ast::Statement::Block(ast::Block {
+                    statements: vec![ast::Statement::Expression(expr.into()).into()],
+                })
+                .into(),
+                else_statement: None,
+            })
+        }
+        package::Type::Repeated(element_type) => match element_type.deref() {
             package::Type::Message(m_id) => {
                 let message_id = *m_id;
+                let message_encode_expr =
+                    encode_message_expr(root, message_scope, file, message_id);
 
-                let field_exists_expression = ast::BinaryOperator::LogicalAnd
+                let array_is_not_empty = ast::BinaryOperator::LogicalAnd
                     .apply(
                         ast::BinaryOperator::WeakNotEqual
                             .apply(Rc::clone(&field_value), ast::Expression::Null.into())
                             .into(),
-                        has_property(
-                            ast::Expression::Identifier(Rc::clone(&message_parameter_id)).into(),
-                            Rc::clone(&js_name_id),
-                        )
-                        .into(),
+                        field_value.prop("length").into(),
                     )
                     .into();
-                let message_encode_expr =
-                    encode_message_expr(&root, &message_scope, &mut file, message_id);
+
+                let i_id = ast::Identifier::from("i").into();
+                let i_id_expr = ast::Expression::from(Rc::clone(&i_id));
+
+                let mut for_stmt =
+                    ast::ForStatement::for_each(Rc::clone(&i_id), Rc::clone(&field_value));
+
+                let encode_call = if field.delimited {
+                    encode_group_call
+                } else {
+                    encode_call
+                };
                 let expr = encode_call(
                     message_encode_expr,
                     Rc::clone(&writer_var_expr),
                     field.tag,
-                    field_value,
+                    field_value.element(i_id_expr.into()).into(),
                 );
 
-                encode_func.push_statement(ast::Statement::IfStatement(ast::IfStatement {
-                    expression: field_exists_expression,
-                    then_statement: ast::Statement::Block(ast::Block {
-                        statements: vec![ast::Statement::Expression(expr.into()).into()],
-                    })
-                    .into(),
+                for_stmt.push_statement(ast::Statement::from(expr));
+
+                ast::Statement::IfStatement(ast::IfStatement {
+                    expression: array_is_not_empty,
+                    then_statement: ast::Statement::from(for_stmt).into(),
                     else_statement: None,
-                }));
+                })
             }
-            package::Type::Repeated(element_type) => match element_type.deref() {
-                package::Type::Message(m_id) => {
-                    let message_id = *m_id;
-                    let message_encode_expr =
-                        encode_message_expr(&root, &message_scope, &mut file, message_id);
-
-                    let array_is_not_empty = ast::BinaryOperator::LogicalAnd
-                        .apply(
-                            ast::BinaryOperator::WeakNotEqual
-                                .apply(Rc::clone(&field_value), ast::Expression::Null.into())
-                                .into(),
-                            field_value.prop("length").into(),
-                        )
-                        .into();
-
-                    let i_id = ast::Identifier::from("i").into();
-                    let i_id_expr = ast::Expression::from(Rc::clone(&i_id));
-
-                    let mut for_stmt =
-                        ast::ForStatement::for_each(Rc::clone(&i_id), Rc::clone(&field_value));
-
-                    let expr = encode_call(
-                        message_encode_expr,
-                        Rc::clone(&writer_var_expr),
-                        field.tag,
-                        field_value.element(i_id_expr.into()).into(),
-                    );
-
-                    for_stmt.push_statement(ast::Statement::from(expr));
-
-                    encode_func.push_statement(ast::Statement::IfStatement(ast::IfStatement {
-                        expression: array_is_not_empty,
-                        then_statement: ast::Statement::from(for_stmt).into(),
-                        else_statement: None,
-                    }));
-                }
-                package::Type::Repeated(_) => unreachable!(),
-                package::Type::Map(_, _) => unreachable!(),
-                package::Type::Enum(_) => {
-                    encode_func.push_statement(
-                        encode_basic_repeated_type_field(
-                            &field_value,
-                            &package::Type::Int32,
-                            field.tag,
-                            &writer_var,
-                        )
-                        .into(),
-                    );
-                }
-                basic => {
-                    assert!(basic.is_basic());
-
-                    encode_func.push_statement(
-                        encode_basic_repeated_type_field(
-                            &field_value,
-                            basic,
-                            field.tag,
-                            &writer_var,
-                        )
-                        .into(),
-                    )
-                }
-            },
-            package::Type::Map(kt, vt) => encode_func.push_statement(
-                encode_map_field(
-                    &root,
-                    &message_scope,
-                    &mut file,
-                    &message_parameter_id,
-                    &writer_var,
-                    &js_name_id,
+            package::Type::Repeated(_) => unreachable!(),
+            package::Type::Map(_, _) => unreachable!(),
+            package::Type::Enum(_) => {
+                let enum_writer = enum_writer(root, message_scope, file, &field.field_type);
+                encode_basic_repeated_type_field(
+                    root,
+                    file,
                     &field_value,
+                    &package::Type::Int32,
+                    enum_writer.as_ref(),
                     field.tag,
-                    kt,
-                    vt,
-                )?
-                .into(),
-            ),
-            t => {
-                assert!(t.is_basic());
-
-                encode_func.push_statement(
-                    encode_basic_type_field(
-                        &field_value,
-                        &message_parameter_id,
-                        &js_name_id,
-                        &writer_var,
-                        t,
-                        field.tag,
-                    )
-                    .into(),
-                );
+                    field.packed,
+                    writer_var,
+                )
             }
-        }
-    }
+            basic => {
+                assert!(basic.is_basic());
 
-    encode_func.push_statement(
-        ast::Expression::from(writer_var)
-            .into_return_statement()
-            .into(),
-    );
+                encode_basic_repeated_type_field(
+                    root,
+                    file,
+                    &field_value,
+                    basic,
+                    None,
+                    field.tag,
+                    field.packed,
+                    writer_var,
+                )
+            }
+        },
+        package::Type::Map(kt, vt) => encode_map_field(
+            root,
+            message_scope,
+            file,
+            owner_id,
+            writer_var,
+            js_name_id,
+            &field_value,
+            field.tag,
+            kt,
+            vt,
+        )?,
+        t => {
+            assert!(t.is_basic());
 
-    file.push_statement(encode_func.into());
+            encode_basic_type_field(
+                root,
+                file,
+                &field_value,
+                owner_id,
+                js_name_id,
+                writer_var,
+                t,
+                field.tag,
+            )
+        }
+    };
+    Ok(statement)
+}
 
-    message_folder.push_file(file);
+#[cfg(test)]
+mod test {
+    use crate::proto::compiler::ts::{codegen_test::compile, compile_options::CompileOptions};
 
-    Ok(())
+    #[test]
+    fn frames_groups_with_start_and_end_tags() {
+        let files = compile(
+            r#"
+            syntax = "proto2";
+            message Hello {
+                optional group Result = 1 {
+                    optional int32 a = 2;
+                }
+                repeated group Item = 3 {
+                    optional int32 b = 4;
+                }
+            }
+        "#,
+            CompileOptions::default(),
+        )
+        .unwrap();
+        // Start group is wire type 3, end group is wire type 4
+        let encode = &files["test/Hello/encode.ts"];
+        assert!(encode.contains("e2(message.result, w.uint32(11)).uint32(12)"));
+        assert!(encode.contains("w.uint32(27)).uint32(28)"));
+        let decode = &files["test/Hello/decode.ts"];
+        assert!(decode.contains("message.result = d2(r, undefined, 12)"));
+        assert!(decode.contains("(r, undefined, 28))"));
+        let nested_decode = &files["test/Hello/Result/decode.ts"];
+        assert!(nested_decode.contains("if (tag === endTag)"));
+    }
 }
@@ -1,6 +1,7 @@
 use std::rc::Rc;
 
 use super::ast::{self, MethodChain};
+use super::enum_style::EnumConversion;
 use super::has_property::has_property;
 use crate::proto::package;
 
@@ -9,6 +10,7 @@ pub(super) fn encode_enum_field(
     writer_var: &Rc<ast::Identifier>,
     js_name_id: &Rc<ast::Identifier>,
     field_value: Rc<ast::Expression>,
+    enum_writer: Option<&EnumConversion>,
     field_tag: i64,
 ) -> ast::Statement {
     let wire_type = package::Type::Int32.get_basic_wire_type();
@@ -26,6 +28,10 @@ pub(super) fn encode_enum_field(
         )
         .into();
 
+    let written_value = match enum_writer {
+        Some(enum_writer) => enum_writer.apply(Rc::clone(&field_value)),
+        None => Rc::clone(&field_value),
+    };
     let writer_var_expr: Rc<ast::Expression> = Rc::new(Rc::clone(&writer_var).into());
     let encode_field_stmt = ast::Statement::Expression(
         writer_var_expr
@@ -36,7 +42,7 @@ pub(super) fn encode_enum_field(
                         field_prefix as f64,
                     ))],
                 ),
-                ("int32", vec![Rc::clone(&field_value)]),
+                ("int32", vec![written_value]),
             ])
             .into(),
     );
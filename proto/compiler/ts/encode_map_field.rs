@@ -10,7 +10,9 @@ use crate::proto::{
 use super::{
     ast::{self, MethodCall, MethodChain, StatementList},
     encode_message_expr::encode_message_expr,
+    enum_style::enum_writer,
     has_property::has_property,
+    long_type::write_basic_value,
 };
 
 pub(super) fn encode_map_field(
@@ -78,11 +80,12 @@ pub(super) fn encode_map_field(
     let writer_var_expr: Rc<ast::Expression> =
         Rc::new(ast::Expression::Identifier(Rc::clone(writer_var)));
 
+    // Keys of 64-bit integers are decimal strings
     let encode_key_expr = Rc::new(encode_key(
         Rc::clone(&writer_var_expr),
         field_tag,
         key_type,
-        key_expr,
+        write_basic_value(root, encode_file, key_type, key_expr),
     ));
 
     match value_type {
@@ -113,11 +116,17 @@ pub(super) fn encode_map_field(
             for_stmt.push_statement(encode_value.into());
         }
         package::Type::Enum(_) => {
+            let value_expr = match enum_writer(root, parent_message_scope, encode_file, value_type)
+            {
+                Some(enum_writer) => enum_writer.apply(value_expr),
+                None => value_expr,
+            };
             let key_value_expr =
                 encode_basic_key_value(&package::Type::Int32, encode_key_expr, value_expr);
             for_stmt.push_statement(key_value_expr.into());
         }
         basic => {
+            let value_expr = write_basic_value(root, encode_file, basic, value_expr);
             for_stmt
                 .push_statement(encode_basic_key_value(basic, encode_key_expr, value_expr).into());
         }
@@ -0,0 +1,20 @@
+use std::rc::Rc;
+
+use super::ast;
+
+/// Adds module private helper function unless the file already has it
+pub(super) fn ensure_function(
+    file: &mut ast::File,
+    name: &str,
+    create: impl FnOnce(&mut ast::File) -> ast::FunctionDeclaration,
+) -> Rc<ast::Expression> {
+    let exists = file.ast.statements.iter().any(|statement| {
+        matches!(statement, ast::Statement::FunctionDeclaration(f) if f.name.text.as_ref() == name)
+    });
+    if !exists {
+        let function = create(file);
+        assert_eq!(function.name.text.as_ref(), name);
+        file.ast.statements.push(function.into());
+    }
+    Rc::new(ast::Expression::from(name))
+}
@@ -1,25 +1,253 @@
-use crate::proto::proto_scope::{ProtoScope};
+use std::rc::Rc;
 
-use super::ast::{self, Folder};
+use crate::proto::proto_scope::{enum_scope::EnumScope, root_scope::RootScope, ProtoScope};
 
-pub(super) fn insert_enum_declaration(res: &mut Folder, enum_scope: &ProtoScope) {
+use super::{
+    ast::{self, Folder},
+    ast_builders::{object_expr, string_expr},
+    compile_options::EnumStyle,
+    enum_helpers::enum_helper_declarations,
+    enum_style::{enum_names_name, enum_values_name},
+    js_doc_comment::js_doc_comment,
+    to_js_string::to_js_string,
+};
+
+pub(super) fn insert_enum_declaration(root: &RootScope, res: &mut Folder, enum_scope: &ProtoScope) {
     let mut file = ast::File::new(enum_scope.name());
     let enum_decl = match enum_scope {
         ProtoScope::Enum(e) => e,
         _ => unreachable!(),
     };
-    let enum_declaration = super::ast::EnumDeclaration {
+    file.ast
+        .statements
+        .extend(enum_declarations(root, enum_decl));
+    file.ast
+        .statements
+        .extend(enum_helper_declarations(root, enum_decl));
+    res.entries.push(file.into());
+}
+
+/// Declarations of the enum file in the configured style
+fn enum_declarations(root: &RootScope, enum_scope: &EnumScope) -> Vec<ast::Statement> {
+    let name = &enum_scope.name;
+    let comment = js_doc_comment(&[&enum_scope.comments]);
+    let enum_declaration = |modifiers| {
+        ast::EnumDeclaration {
+            modifiers,
+            name: Rc::clone(name).into(),
+            members: enum_scope
+                .entries
+                .iter()
+                .map(|entry| ast::EnumMember {
+                    name: entry.name.clone().into(),
+                    value: Some(entry.value.into()),
+                    comment: js_doc_comment(&[&entry.comments]),
+                })
+                .collect(),
+            comment: comment.clone(),
+        }
+        .into()
+    };
+    match root.options.enum_style {
+        EnumStyle::Enum => vec![enum_declaration(vec![ast::Modifier::Export])],
+        EnumStyle::ConstEnum => vec![
+            enum_declaration(vec![ast::Modifier::Export, ast::Modifier::Const]),
+            names_declaration(root, enum_scope),
+            values_declaration(root, enum_scope),
+        ],
+        EnumStyle::StringUnion => {
+            let names = enum_scope
+                .entries
+                .iter()
+                .map(|entry| ast::Type::StringLiteral(Rc::clone(&entry.name)))
+                .collect::<Vec<_>>();
+            vec![
+                ast::TypeAliasDeclaration {
+                    modifiers: vec![ast::Modifier::Export],
+                    name: Rc::clone(name).into(),
+                    alias_type: ast::UnionType::from(names).into(),
+                    comment,
+                }
+                .into(),
+                names_declaration(root, enum_scope),
+                values_declaration(root, enum_scope),
+            ]
+        }
+        EnumStyle::AsConst => {
+            let values = enum_scope
+                .entries
+                .iter()
+                .map(|entry| (entry.name.as_ref(), Rc::new((entry.value as f64).into())))
+                .collect();
+            let object_type = || ast::Type::Query(Rc::new(Rc::clone(name).into()));
+            vec![
+                ast::ConstDeclaration {
+                    modifiers: vec![ast::Modifier::Export],
+                    name: Rc::clone(name).into(),
+                    const_type: None,
+                    initializer: ast::Expression::AsConst(object_expr(values).into()),
+                    comment: comment.clone(),
+                }
+                .into(),
+                ast::TypeAliasDeclaration {
+                    modifiers: vec![ast::Modifier::Export],
+                    name: Rc::clone(name).into(),
+                    alias_type: ast::Type::IndexedAccess(
+                        object_type().into(),
+                        ast::Type::KeyOf(object_type().into()).into(),
+                    ),
+                    comment,
+                }
+                .into(),
+                names_declaration(root, enum_scope),
+            ]
+        }
+    }
+}
+
+/// `export const ColorNames: Record<number, string> = { 0: "RED" }`,
+/// aliases are named by the first declared entry
+fn names_declaration(root: &RootScope, enum_scope: &EnumScope) -> ast::Statement {
+    let mut keys: Vec<(String, Rc<ast::Expression>)> = Vec::new();
+    let mut numbers = Vec::new();
+    for entry in &enum_scope.entries {
+        if numbers.contains(&entry.value) {
+            continue;
+        }
+        numbers.push(entry.value);
+        // Negative numbers are not valid property names
+        let key = if entry.value < 0 {
+            to_js_string(&entry.value.to_string())
+        } else {
+            entry.value.to_string()
+        };
+        keys.push((key, string_expr(&entry.name)));
+    }
+    ast::ConstDeclaration {
+        modifiers: vec![ast::Modifier::Export],
+        name: enum_names_name(root, &enum_scope.name).into(),
+        const_type: Some(ast::Type::Record(
+            ast::Type::Number.into(),
+            ast::Type::String.into(),
+        )),
+        initializer: object_expr(
+            keys.iter()
+                .map(|(key, name)| (key.as_str(), Rc::clone(name)))
+                .collect(),
+        ),
+        comment: None,
+    }
+    .into()
+}
+
+/// `export const ColorNumbers: Record<string, number> = { RED: 0 }`
+fn values_declaration(root: &RootScope, enum_scope: &EnumScope) -> ast::Statement {
+    ast::ConstDeclaration {
         modifiers: vec![ast::Modifier::Export],
-        name: enum_scope.name().into(),
-        members: enum_decl
-            .entries
-            .iter()
-            .map(|entry| super::ast::EnumMember {
-                name: entry.name.clone().into(),
-                value: Some(entry.value.into()),
-            })
-            .collect(),
+        name: enum_values_name(root, &enum_scope.name).into(),
+        const_type: Some(ast::Type::Record(
+            ast::Type::String.into(),
+            ast::Type::Number.into(),
+        )),
+        initializer: object_expr(
+            enum_scope
+                .entries
+                .iter()
+                .map(|entry| (entry.name.as_ref(), Rc::new((entry.value as f64).into())))
+                .collect(),
+        ),
+        comment: None,
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, HashSet};
+
+    use crate::proto::compiler::ts::{
+        codegen_test::compile,
+        compile_options::{CompileOptions, EnumStyle},
     };
-    file.ast.statements.push(enum_declaration.into());
-    res.entries.push(file.into());
+
+    fn compile_color(enum_style: EnumStyle) -> BTreeMap<String, String> {
+        compile(
+            r#"
+            syntax = "proto3";
+            enum color { RED = 0; GREEN = 1; }
+            message Hello { color c = 1; }
+        "#,
+            CompileOptions {
+                enum_style,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    }
+
+    /// Asserts that values exported by the enum file have distinct names
+    fn assert_unique_exports(content: &str) {
+        let mut names = HashSet::new();
+        for line in content.lines() {
+            let Some(declaration) = line.strip_prefix("export ") else {
+                continue;
+            };
+            let mut words = declaration
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .filter(|word| !matches!(*word, "" | "const" | "enum" | "type" | "function"));
+            let name = words.next().unwrap();
+            // `as const` objects declare the type of the same name
+            if !declaration.starts_with("type ") {
+                assert!(names.insert(name.to_string()), "{} is exported twice", name);
+            }
+        }
+    }
+
+    #[test]
+    fn declares_numeric_enums() {
+        let files = compile_color(EnumStyle::Enum);
+        let color = &files["test/color.ts"];
+        assert!(color.starts_with("export enum color {\n  RED = 0,\n  GREEN = 1,\n}\n"));
+        assert!(!color.contains("colorNames"));
+        assert!(!color.contains("colorNumbers"));
+        assert_unique_exports(color);
+        assert!(files["test/Hello/fromJSON.ts"].contains("enumFromJSON(color, value, options)"));
+    }
+
+    #[test]
+    fn declares_const_enums() {
+        let files = compile_color(EnumStyle::ConstEnum);
+        let color = &files["test/color.ts"];
+        assert!(color.starts_with("export const enum color {\n"));
+        assert!(color.contains("export const colorNames: Record<number, string> = {\n"));
+        assert!(color.contains("export const colorNumbers: Record<string, number> = {\n"));
+        assert!(color.contains("export const colorValues: color[] = [color.RED, color.GREEN]\n"));
+        assert_unique_exports(color);
+    }
+
+    #[test]
+    fn declares_string_unions() {
+        let files = compile_color(EnumStyle::StringUnion);
+        let color = &files["test/color.ts"];
+        assert!(color.starts_with("export type color = \"RED\" | \"GREEN\"\n"));
+        assert!(color.contains("export const colorNumbers: Record<string, number> = {\n"));
+        assert!(color.contains("export const colorValues: color[] = [\"RED\", \"GREEN\"]\n"));
+        assert_unique_exports(color);
+        let from_json = &files["test/Hello/fromJSON.ts"];
+        assert!(from_json.contains("const number = enumFromJSON(colorNumbers, value, options)\n"));
+        assert!(from_json.contains("message.c = colorFromNumber(number)\n"));
+        assert!(files["test/Hello/toJSON.ts"]
+            .contains("enumToJSON(colorNames, colorToNumber(message.c))"));
+    }
+
+    #[test]
+    fn declares_objects_as_const() {
+        let files = compile_color(EnumStyle::AsConst);
+        let color = &files["test/color.ts"];
+        assert!(color.starts_with("export const color = {\n  RED: 0,\n  GREEN: 1,\n} as const\n"));
+        assert!(color.contains("export type color = (typeof color)[keyof typeof color]\n"));
+        assert!(color.contains("export const colorNames: Record<number, string> = {\n"));
+        assert!(!color.contains("colorNumbers"));
+        assert_unique_exports(color);
+    }
 }
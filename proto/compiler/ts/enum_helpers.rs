@@ -0,0 +1,328 @@
+use std::{ops::Deref, rc::Rc};
+
+use crate::proto::{
+    package::EnumEntry,
+    proto_scope::{enum_scope::EnumScope, root_scope::RootScope, ProtoScope},
+};
+
+use super::{
+    ast::{self, ElementAccess, MethodCall, Prop, StatementList},
+    ast_builders::{id_expr, is_type_of, number_expr, object_expr, return_statement, string_expr},
+    compile_options::EnumStyle,
+    constants::UNRECOGNIZED_PROPERTY,
+    enum_style::enum_values_name,
+};
+
+/// `colorFromNumber`, which converts numbers read from the wire into enum values
+pub(super) fn from_number_name(enum_name: &str) -> Rc<str> {
+    format!("{}FromNumber", helper_prefix(enum_name)).into()
+}
+
+/// `colorToNumber`, which converts enum values into numbers written to the wire
+pub(super) fn to_number_name(enum_name: &str) -> Rc<str> {
+    format!("{}ToNumber", helper_prefix(enum_name)).into()
+}
+
+/// `UnrecognizedColor`, which holds numbers unknown to open enums
+pub(super) fn unrecognized_type_name(enum_name: &str) -> Rc<str> {
+    format!("Unrecognized{}", enum_name).into()
+}
+
+/// Whether unknown values of the enum are kept on decoding
+pub(super) fn is_open_enum(root: &RootScope, enum_id: usize) -> bool {
+    root.get_declaration(enum_id)
+        .map(|scope| matches!(scope.deref(), ProtoScope::Enum(e) if !e.closed))
+        .unwrap_or(false)
+}
+
+fn helper_prefix(enum_name: &str) -> String {
+    let mut chars = enum_name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Helpers exported by the enum file next to its declaration:
+/// `colorValues`, `isValidColor`, `colorFromName`, `colorToName`,
+/// `colorFromNumber` and `colorToNumber`, plus `UnrecognizedColor` of open enums
+pub(super) fn enum_helper_declarations(
+    root: &RootScope,
+    enum_scope: &EnumScope,
+) -> Vec<ast::Statement> {
+    let name = &enum_scope.name;
+    let prefix = helper_prefix(name);
+    let enum_type = ast::Type::from_id(name);
+    let string_union = root.options.enum_style == EnumStyle::StringUnion;
+    // Names of string unions are distinct values even for aliases
+    let distinct_entries = distinct_entries(enum_scope);
+    let value_entries = if string_union {
+        enum_scope.entries.iter().collect()
+    } else {
+        distinct_entries.clone()
+    };
+    let entry_value = |entry: &EnumEntry| -> ast::Expression {
+        if string_union {
+            ast::Expression::StringLiteral(Rc::clone(&entry.name).into())
+        } else {
+            id_expr(name).prop(&entry.name)
+        }
+    };
+    let unrecognized_type = ast::Type::from_id(&unrecognized_type_name(name));
+    let (decoded_type, unknown_number) = if enum_scope.closed {
+        (
+            enum_type.or(&ast::Type::Undefined),
+            ast::Expression::Undefined,
+        )
+    } else {
+        (
+            enum_type.or(&unrecognized_type),
+            object_expr(vec![(UNRECOGNIZED_PROPERTY, id_expr("value"))]),
+        )
+    };
+
+    let mut res = Vec::new();
+    if !enum_scope.closed {
+        res.push(
+            ast::TypeAliasDeclaration {
+                modifiers: vec![ast::Modifier::Export],
+                name: unrecognized_type_name(name).into(),
+                alias_type: ast::Type::ObjectLiteral(vec![(
+                    UNRECOGNIZED_PROPERTY.into(),
+                    ast::Type::Number,
+                )]),
+                comment: None,
+            }
+            .into(),
+        );
+    }
+
+    let values_name = format!("{}Values", prefix);
+    res.push(
+        ast::ConstDeclaration {
+            modifiers: vec![ast::Modifier::Export],
+            name: values_name.as_str().into(),
+            const_type: Some(ast::Type::array(enum_type.clone())),
+            initializer: ast::Expression::ArrayLiteralExpression(
+                value_entries
+                    .iter()
+                    .map(|entry| Rc::new(entry_value(entry)))
+                    .collect(),
+            ),
+            comment: None,
+        }
+        .into(),
+    );
+
+    let mut is_valid = ast::FunctionDeclaration::new_exported(&format!("isValid{}", name));
+    is_valid.add_param(ast::Parameter::new("value", ast::Type::Any));
+    is_valid.returns(ast::Type::Predicate(
+        Rc::new("value".into()),
+        enum_type.clone().into(),
+    ));
+    is_valid.push_statement(return_statement(
+        id_expr(&values_name).method_call("includes", vec![id_expr("value")]),
+    ));
+    res.push(is_valid.into());
+
+    res.push(
+        switch_function(
+            &format!("{}FromName", prefix),
+            ("name", ast::Type::String),
+            enum_type.or(&ast::Type::Undefined),
+            enum_scope
+                .entries
+                .iter()
+                .map(|entry| (string_expr(&entry.name), entry_value(entry)))
+                .collect(),
+            ast::Expression::Undefined,
+        )
+        .into(),
+    );
+
+    res.push(
+        switch_function(
+            &format!("{}ToName", prefix),
+            ("value", enum_type.clone()),
+            ast::Type::String.or(&ast::Type::Undefined),
+            value_entries
+                .iter()
+                .map(|entry| {
+                    (
+                        entry_value(entry).into(),
+                        ast::Expression::StringLiteral(Rc::clone(&entry.name).into()),
+                    )
+                })
+                .collect(),
+            ast::Expression::Undefined,
+        )
+        .into(),
+    );
+
+    res.push(
+        switch_function(
+            &from_number_name(name),
+            ("value", ast::Type::Number),
+            decoded_type.clone(),
+            distinct_entries
+                .iter()
+                .map(|entry| (number_expr(entry.value as f64), entry_value(entry)))
+                .collect(),
+            unknown_number,
+        )
+        .into(),
+    );
+
+    let value_expr = id_expr("value");
+    let known_number = || -> ast::Expression {
+        if string_union {
+            id_expr(&enum_values_name(root, name)).element(id_expr("value"))
+        } else {
+            ast::Expression::from("value")
+        }
+    };
+    let mut to_number = ast::FunctionDeclaration::new_exported(&to_number_name(name));
+    if enum_scope.closed {
+        to_number.add_param(ast::Parameter::new("value", enum_type));
+        to_number.returns(ast::Type::Number);
+        to_number.push_statement(return_statement(known_number()));
+    } else {
+        to_number.add_param(ast::Parameter::new(
+            "value",
+            enum_type.or(&unrecognized_type),
+        ));
+        to_number.returns(ast::Type::Number);
+        to_number.push_statement(return_statement(ast::Expression::conditional(
+            is_type_of(&value_expr, "object").into(),
+            value_expr.prop(UNRECOGNIZED_PROPERTY).into(),
+            known_number().into(),
+        )));
+    }
+    res.push(to_number.into());
+    res
+}
+
+/// Entries of the enum with the first name of each number
+fn distinct_entries(enum_scope: &EnumScope) -> Vec<&EnumEntry> {
+    let mut res: Vec<&EnumEntry> = Vec::new();
+    for entry in &enum_scope.entries {
+        if res.iter().all(|known| known.value != entry.value) {
+            res.push(entry);
+        }
+    }
+    res
+}
+
+/// Function returning the value of the matching case, e.g.
+/// `function colorFromName(name: string) { switch (name) { case "RED": return Color.RED } }`
+fn switch_function(
+    name: &str,
+    (param_name, param_type): (&str, ast::Type),
+    return_type: ast::Type,
+    cases: Vec<(Rc<ast::Expression>, ast::Expression)>,
+    fallback: ast::Expression,
+) -> ast::FunctionDeclaration {
+    let mut function = ast::FunctionDeclaration::new_exported(name);
+    function.add_param(ast::Parameter::new(param_name, param_type));
+    function.returns(return_type);
+    let mut switch =
+        ast::SwitchStatement::new(id_expr(param_name), vec![return_statement(fallback)].into());
+    for (case_expr, result) in cases {
+        let mut case = ast::CaseClause::new(case_expr);
+        case.push_statement(return_statement(result));
+        switch.add_case(case);
+    }
+    function.push_statement(switch.into());
+    function
+}
+
+#[cfg(test)]
+mod test {
+    use crate::proto::compiler::ts::{
+        codegen_test::compile,
+        compile_options::{CompileOptions, EnumStyle},
+    };
+
+    const PROTO3: &str = r#"
+        syntax = "proto3";
+        enum Color { option allow_alias = true; RED = 0; GREEN = 1; LIME = 1; }
+        message Hello { Color c = 1; repeated Color all = 2; }
+    "#;
+
+    #[test]
+    fn declares_enum_helpers() {
+        let files = compile(PROTO3, CompileOptions::default()).unwrap();
+        let color = &files["test/Color.ts"];
+        assert!(color.contains("export const colorValues: Color[] = [Color.RED, Color.GREEN]\n"));
+        assert!(color.contains(
+            "export function isValidColor(value: any): value is Color {\n  return colorValues.includes(value)\n}\n"
+        ));
+        assert!(
+            color.contains("export function colorFromName(name: string): Color | undefined {\n")
+        );
+        // Aliases are parsed from names, but numbers are named after the first value
+        assert!(color.contains("    case \"LIME\": {\n      return Color.LIME\n    }\n"));
+        assert!(color.contains("export function colorToName(value: Color): string | undefined {\n"));
+        assert!(!color.contains("return \"LIME\""));
+    }
+
+    #[test]
+    fn converts_names_of_string_unions() {
+        let files = compile(
+            PROTO3,
+            CompileOptions {
+                enum_style: EnumStyle::StringUnion,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let color = &files["test/Color.ts"];
+        assert!(
+            color.contains("export const colorValues: Color[] = [\"RED\", \"GREEN\", \"LIME\"]\n")
+        );
+        assert!(color.contains("    case 1: {\n      return \"GREEN\"\n    }\n"));
+        assert!(color.contains(
+            "return typeof value === \"object\" ? value.UNRECOGNIZED : ColorNumbers[value]\n"
+        ));
+    }
+
+    #[test]
+    fn keeps_unrecognized_values_of_open_enums() {
+        let files = compile(PROTO3, CompileOptions::default()).unwrap();
+        let color = &files["test/Color.ts"];
+        assert!(color.contains("export type UnrecognizedColor = { UNRECOGNIZED: number }\n"));
+        assert!(color.contains(
+            "export function colorFromNumber(value: number): Color | UnrecognizedColor {\n"
+        ));
+        assert!(
+            color.contains("    default:\n      return {\n        UNRECOGNIZED: value,\n      }\n")
+        );
+        assert!(color.contains("return typeof value === \"object\" ? value.UNRECOGNIZED : value\n"));
+        assert!(files["test/Hello/types.ts"].contains("c: Color | UnrecognizedColor\n"));
+        let decode = &files["test/Hello/decode.ts"];
+        assert!(decode.contains("message.c = colorFromNumber(r.int32())\n"));
+        assert!(decode.contains("message.all.push(colorFromNumber(r.int32()))\n"));
+        assert!(files["test/Hello/encode.ts"].contains("int32(colorToNumber(message.c))"));
+    }
+
+    #[test]
+    fn skips_unknown_values_of_closed_enums() {
+        let files = compile(
+            &PROTO3
+                .replace("proto3", "proto2")
+                .replace("Color c", "optional Color c"),
+            CompileOptions::default(),
+        )
+        .unwrap();
+        let color = &files["test/Color.ts"];
+        assert!(!color.contains("UnrecognizedColor"));
+        assert!(
+            color.contains("export function colorFromNumber(value: number): Color | undefined {\n")
+        );
+        let decode = &files["test/Hello/decode.ts"];
+        assert!(decode.contains(
+            "const value = r.int32()\n        if ([0, 1, 1].includes(value))\n          message.c = value\n"
+        ));
+        assert!(!decode.contains("colorFromNumber"));
+    }
+}
@@ -0,0 +1,201 @@
+use std::{ops::Deref, rc::Rc};
+
+use crate::proto::{
+    package,
+    proto_scope::{root_scope::RootScope, ProtoScope},
+};
+
+use super::{
+    ast::{self, Call},
+    ast_builders::id_expr,
+    compile_options::EnumStyle,
+    ensure_import::ensure_import,
+    enum_helpers::{from_number_name, is_open_enum, to_number_name},
+    get_relative_import::get_relative_import,
+    ts_path::{TsPath, TsPathComponent},
+};
+
+/// Conversion between numbers of the wire format and enum values of messages,
+/// e.g. `colorFromNumber(value)` of string union or open enums
+pub(super) struct EnumConversion {
+    function: Rc<ast::Expression>,
+}
+
+impl EnumConversion {
+    pub fn apply(&self, value: Rc<ast::Expression>) -> Rc<ast::Expression> {
+        self.function.call(vec![value]).into()
+    }
+}
+
+/// Name of the object mapping numbers of the enum to names, which the enum file exports
+pub(super) fn enum_names_name(root: &RootScope, enum_name: &str) -> Rc<str> {
+    match root.options.enum_style {
+        // Numeric enums map numbers back to names themselves
+        EnumStyle::Enum => enum_name.into(),
+        _ => format!("{}Names", enum_name).into(),
+    }
+}
+
+/// Name of the object mapping names of the enum to numbers, which the enum file exports
+pub(super) fn enum_values_name(root: &RootScope, enum_name: &str) -> Rc<str> {
+    match root.options.enum_style {
+        EnumStyle::Enum | EnumStyle::AsConst => enum_name.into(),
+        EnumStyle::ConstEnum | EnumStyle::StringUnion => format!("{}Numbers", enum_name).into(),
+    }
+}
+
+/// Imports the object mapping numbers of the enum to names into the file of the message
+pub(super) fn import_enum_names(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    enum_id: usize,
+) -> Rc<ast::Expression> {
+    let enum_name = root.get_declaration_name(enum_id).unwrap();
+    let names_name = enum_names_name(root, &enum_name);
+    import_enum_export(
+        root,
+        message_scope,
+        file,
+        enum_id,
+        TsPathComponent::Enum(names_name),
+    )
+}
+
+/// Imports the object mapping names of the enum to numbers into the file of the message
+pub(super) fn import_enum_values(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    enum_id: usize,
+) -> Rc<ast::Expression> {
+    let enum_name = root.get_declaration_name(enum_id).unwrap();
+    let values_name = enum_values_name(root, &enum_name);
+    import_enum_export(
+        root,
+        message_scope,
+        file,
+        enum_id,
+        TsPathComponent::Enum(values_name),
+    )
+}
+
+fn import_enum_export(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    enum_id: usize,
+    export: TsPathComponent,
+) -> Rc<ast::Expression> {
+    let name: String = (&export).into();
+    let export_path = {
+        let mut res = TsPath::from(root.get_declaration_path(enum_id).unwrap());
+        res.push(export);
+        res
+    };
+    let current_file_path = {
+        let mut res = TsPath::from(
+            root.get_declaration_path(message_scope.id().unwrap())
+                .unwrap(),
+        );
+        res.push_file(&file.name);
+        res
+    };
+    if let Some(import_declaration) = get_relative_import(&current_file_path, &export_path) {
+        ensure_import(file, import_declaration);
+    }
+    id_expr(&name)
+}
+
+/// Whether enum values in messages are names instead of numbers
+pub(super) fn is_string_enum(root: &RootScope) -> bool {
+    root.options.enum_style == EnumStyle::StringUnion
+}
+
+/// Enum of the field, its elements or map values
+fn field_enum_id(field_type: &package::Type) -> Option<usize> {
+    match field_type {
+        package::Type::Enum(enum_id) => Some(*enum_id),
+        package::Type::Repeated(element_type) => field_enum_id(element_type),
+        package::Type::Map(_, value_type) => field_enum_id(value_type),
+        _ => None,
+    }
+}
+
+/// Converts numbers read by `Reader` into enum values of the field,
+/// `None` when enum values are the numbers themselves
+pub(super) fn enum_reader(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    field_type: &package::Type,
+) -> Option<EnumConversion> {
+    let enum_id = converted_enum_id(root, field_type)?;
+    let enum_name = root.get_declaration_name(enum_id).unwrap();
+    let function = import_enum_export(
+        root,
+        message_scope,
+        file,
+        enum_id,
+        TsPathComponent::Function(from_number_name(&enum_name)),
+    );
+    Some(EnumConversion { function })
+}
+
+/// Converts enum values of the field into numbers passed to `Writer`,
+/// `None` when enum values are the numbers themselves
+pub(super) fn enum_writer(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    field_type: &package::Type,
+) -> Option<EnumConversion> {
+    let enum_id = converted_enum_id(root, field_type)?;
+    let enum_name = root.get_declaration_name(enum_id).unwrap();
+    let function = import_enum_export(
+        root,
+        message_scope,
+        file,
+        enum_id,
+        TsPathComponent::Function(to_number_name(&enum_name)),
+    );
+    Some(EnumConversion { function })
+}
+
+/// Enum of the field, which values are not plain numbers:
+/// names of string unions or unrecognized numbers of open enums
+fn converted_enum_id(root: &RootScope, field_type: &package::Type) -> Option<usize> {
+    field_enum_id(field_type).filter(|enum_id| is_string_enum(root) || is_open_enum(root, *enum_id))
+}
+
+/// Literal of the enum value with the number, e.g. `"RED"` of string unions
+pub(super) fn enum_literal(root: &RootScope, enum_id: usize, number: i64) -> ast::Expression {
+    let name = root
+        .get_declaration(enum_id)
+        .and_then(|scope| match scope.deref() {
+            ProtoScope::Enum(enum_scope) => enum_scope
+                .entries
+                .iter()
+                .find(|entry| entry.value == number)
+                .map(|entry| Rc::clone(&entry.name)),
+            _ => None,
+        });
+    match name {
+        Some(name) if is_string_enum(root) => ast::Expression::StringLiteral(name.into()),
+        _ => (number as f64).into(),
+    }
+}
+
+/// Default value of enum fields, which is the first declared value
+pub(super) fn enum_default_expression(root: &RootScope, enum_id: usize) -> ast::Expression {
+    let first_value = root
+        .get_declaration(enum_id)
+        .and_then(|scope| match scope.deref() {
+            ProtoScope::Enum(enum_scope) => enum_scope.entries.first().map(|entry| entry.value),
+            _ => None,
+        });
+    match first_value {
+        Some(value) if is_string_enum(root) => enum_literal(root, enum_id, value),
+        _ => 0f64.into(),
+    }
+}
@@ -0,0 +1,252 @@
+use std::rc::Rc;
+
+use crate::proto::{
+    error::ProtoError,
+    package::{self, Extension},
+    proto_scope::root_scope::RootScope,
+    protopath::ProtoPath,
+};
+
+use super::{
+    ast::{self, ElementAccess, ExpressionChain, Folder, StatementList, Type},
+    ast_builders::{object_expr, string_expr},
+    constants::EXTENSIONS_PROPERTY,
+    ensure_import::ensure_import,
+    get_relative_import::get_relative_import,
+    message_name_to_encode_type_name::message_name_to_encode_type_name,
+    ts_path::{TsPath, TsPathComponent},
+    types_compiler::{import_decode_result_type, import_encoding_input_type},
+};
+
+const EXTENSIONS_FOLDER_NAME: &str = "extensions";
+
+/// Inserts `extensions/<field>.ts` accessor module for every extension
+/// declared in the scope with `scope_path`
+pub(super) fn insert_extension_accessors(
+    root: &RootScope,
+    scope_folder: &mut Folder,
+    scope_path: ProtoPath,
+    extensions: &[Rc<Extension>],
+) -> Result<(), ProtoError> {
+    if extensions.is_empty() {
+        return Ok(());
+    }
+    let mut extensions_folder = Folder::new(EXTENSIONS_FOLDER_NAME.into());
+    for extension in extensions {
+        let file_path = {
+            let mut res = TsPath::from(scope_path.clone());
+            res.push(TsPathComponent::Folder(EXTENSIONS_FOLDER_NAME.into()));
+            res.push_file(&extension.field.name);
+            res
+        };
+        let file = compile_extension_accessors(root, &scope_path, &file_path, extension)?;
+        extensions_folder.push_file(file);
+    }
+    scope_folder.push_folder(extensions_folder);
+    Ok(())
+}
+
+/// `getExt`/`setExt` accessors of extension `ext` and its descriptor `extExtension`
+fn compile_extension_accessors(
+    root: &RootScope,
+    scope_path: &ProtoPath,
+    file_path: &TsPath,
+    extension: &Extension,
+) -> Result<ast::File, ProtoError> {
+    let field = &extension.field;
+    let mut file = ast::File::new(Rc::clone(&field.name));
+    let camel_name = field.canonical_json_name();
+    let pascal_name = {
+        let mut chars = camel_name.chars();
+        let first = chars.next().map(|c| c.to_uppercase().to_string());
+        first.unwrap_or_default() + chars.as_str()
+    };
+
+    let extendee_name = root.get_declaration_name(extension.extendee).unwrap();
+    let extendee_encode_name = message_name_to_encode_type_name(&extendee_name);
+    for imported_name in [extendee_name.to_string(), extendee_encode_name.clone()] {
+        let imported_path = {
+            let mut res = TsPath::from(root.get_declaration_path(extension.extendee).unwrap());
+            res.push_file("types");
+            res.push(TsPathComponent::Interface(imported_name.into()));
+            res
+        };
+        if let Some(import_declaration) = get_relative_import(file_path, &imported_path) {
+            ensure_import(&mut file, import_declaration);
+        }
+    }
+
+    let message_id: Rc<ast::Identifier> = ast::Identifier::new("message").into();
+    let value_id: Rc<ast::Identifier> = ast::Identifier::new("value").into();
+    let message_extensions_expr: Rc<ast::Expression> =
+        Rc::new(ast::Expression::from(Rc::clone(&message_id)).into_prop(EXTENSIONS_PROPERTY));
+    let extension_value_expr = || message_extensions_expr.element(Rc::new(field.tag.into()));
+
+    let decode_result_type =
+        import_decode_result_type(root, file_path, &mut file, &field.field_type)?;
+    let mut get_extension = ast::FunctionDeclaration::new_exported(&format!("get{}", pascal_name));
+    get_extension.add_param(ast::Parameter::new(
+        &message_id,
+        Type::from_id(&extendee_name),
+    ));
+    get_extension.returns(decode_result_type.or(&Type::Undefined));
+    get_extension.push_statement(extension_value_expr().into_return_statement());
+    file.push_statement(get_extension.into());
+
+    let encode_input_type =
+        import_encoding_input_type(root, file_path, &mut file, &field.field_type)?;
+    let mut set_extension = ast::FunctionDeclaration::new_exported(&format!("set{}", pascal_name));
+    set_extension.add_param(ast::Parameter::new(
+        &message_id,
+        Type::from_id(&extendee_encode_name),
+    ));
+    set_extension.add_param(ast::Parameter::new(
+        &value_id,
+        encode_input_type.or(&Type::Null),
+    ));
+    set_extension.returns(Type::Void);
+    set_extension.push_statement(ast::Statement::IfStatement(ast::IfStatement {
+        expression: message_extensions_expr.not().into(),
+        then_statement: ast::Statement::Expression(
+            ast::BinaryOperator::Assign
+                .apply(
+                    Rc::clone(&message_extensions_expr),
+                    ast::Expression::ObjectLiteralExpression(vec![]).into(),
+                )
+                .into(),
+        )
+        .into(),
+        else_statement: None,
+    }));
+    set_extension.push_statement(ast::Statement::Expression(
+        ast::BinaryOperator::Assign
+            .apply(
+                extension_value_expr().into(),
+                Rc::new(Rc::clone(&value_id).into()),
+            )
+            .into(),
+    ));
+    file.push_statement(set_extension.into());
+
+    let full_name = match scope_path.full_name() {
+        scope_name if scope_name.is_empty() => field.name.to_string(),
+        scope_name => format!("{}.{}", scope_name, field.name),
+    };
+    let extendee_full_name = root
+        .get_declaration_path(extension.extendee)
+        .unwrap()
+        .full_name();
+    let repeated = match &field.field_type {
+        package::Type::Repeated(_) => ast::Expression::True,
+        _ => ast::Expression::False,
+    };
+    file.push_statement(
+        ast::ConstDeclaration {
+            modifiers: vec![ast::Modifier::Export],
+            name: format!("{}Extension", camel_name).as_str().into(),
+            const_type: None,
+            initializer: object_expr(vec![
+                ("name", string_expr(&full_name)),
+                ("extendee", string_expr(&extendee_full_name)),
+                ("fieldNumber", Rc::new((field.tag as f64).into())),
+                ("type", string_expr(&type_name(root, &field.field_type))),
+                ("repeated", repeated.into()),
+            ]),
+            comment: None,
+        }
+        .into(),
+    );
+
+    Ok(file)
+}
+
+/// Name of the field type in extension descriptors, e.g. `int32` or `pkg.Hello`
+fn type_name(root: &RootScope, field_type: &package::Type) -> String {
+    match field_type {
+        package::Type::Enum(id) | package::Type::Message(id) => {
+            root.get_declaration_path(*id).unwrap().full_name()
+        }
+        package::Type::Repeated(element_type) => type_name(root, element_type),
+        field_type => field_type.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::proto::compiler::ts::{codegen_test::compile, compile_options::CompileOptions};
+
+    #[test]
+    fn encodes_and_decodes_extensions() {
+        let files = compile(
+            r#"
+            syntax = "proto2";
+            message Hello {
+                extensions 100 to 200;
+            }
+            extend Hello {
+                optional int32 ext = 100;
+                repeated string extra_names = 101;
+            }
+        "#,
+            CompileOptions::default(),
+        )
+        .unwrap();
+        let encode = &files["test/Hello/encode.ts"];
+        assert!(encode.contains("w.uint32(800).int32(ext[100])"));
+        assert!(encode.contains("w.uint32(810).string(ext[101][i])"));
+        let decode = &files["test/Hello/decode.ts"];
+        assert!(decode.contains("$extensions: {},"));
+        assert!(decode.contains("case 100: {\n        message.$extensions[100] = r.int32()"));
+        assert!(decode.contains("message.$extensions[101].push(r.string())"));
+        let accessors = &files["test/extensions/ext.ts"];
+        assert!(accessors.contains(
+            "export function getExt(message: Hello): number | undefined {\n  \
+             return message.$extensions[100]\n}"
+        ));
+        assert!(accessors.contains(
+            "export function setExt(message: HelloEncodeInput, value: number | null): void {\n"
+        ));
+        assert!(accessors.contains("message.$extensions[100] = value"));
+        let accessors = &files["test/extensions/extra_names.ts"];
+        assert!(accessors
+            .contains("export function getExtraNames(message: Hello): (string[]) | undefined {\n"));
+        assert!(accessors.contains("export function setExtraNames(message: HelloEncodeInput"));
+    }
+
+    #[test]
+    fn describes_extensions() {
+        let files = compile(
+            r#"
+            syntax = "proto2";
+            package my.pkg;
+            message Hello {
+                extensions 100 to 200;
+                message Inner {
+                    extend Hello { repeated Hello back = 101; }
+                }
+            }
+            extend Hello { optional int32 extra_info = 100; }
+        "#,
+            CompileOptions::default(),
+        )
+        .unwrap();
+        assert!(files["my/pkg/test/extensions/extra_info.ts"].contains(
+            "export const extraInfoExtension = {\n  \
+             name: \"my.pkg.extra_info\",\n  \
+             extendee: \"my.pkg.Hello\",\n  \
+             fieldNumber: 100,\n  \
+             type: \"int32\",\n  \
+             repeated: false,\n}\n"
+        ));
+        assert!(
+            files["my/pkg/test/Hello/Inner/extensions/back.ts"].contains(
+                "export const backExtension = {\n  \
+             name: \"my.pkg.Hello.Inner.back\",\n  \
+             extendee: \"my.pkg.Hello\",\n  \
+             fieldNumber: 101,\n  \
+             type: \"my.pkg.Hello\",\n  \
+             repeated: true,\n}\n"
+            )
+        );
+    }
+}
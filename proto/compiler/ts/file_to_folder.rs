@@ -2,7 +2,9 @@ use std::ops::Deref;
 
 use super::{
     ast::Folder, decode_compiler::compile_decode, encode_compiler::compile_encode,
-    enum_compiler::insert_enum_declaration, file_name_to_folder_name::file_name_to_folder_name,
+    enum_compiler::insert_enum_declaration, extension_compiler::insert_extension_accessors,
+    file_name_to_folder_name::file_name_to_folder_name, from_json_compiler::compile_from_json,
+    to_json_compiler::compile_to_json,
     types_compiler::insert_message_types,
 };
 use crate::proto::{
@@ -21,12 +23,19 @@ pub(super) fn file_to_folder(
             ProtoScope::Root(_) => unreachable!(),
             ProtoScope::Package(_) => unreachable!(),
             ProtoScope::File(_) => unreachable!(),
-            e @ ProtoScope::Enum(_) => insert_enum_declaration(&mut res, e),
+            e @ ProtoScope::Enum(_) => insert_enum_declaration(root, &mut res, e),
             m @ ProtoScope::Message(_) => {
                 insert_message_declaration(&root, &mut res, m)?;
             }
+            ProtoScope::Service(_) => {}
         };
     }
+    if let ProtoScope::File(file) = file_scope {
+        if !file.extensions.is_empty() {
+            let file_path = root.get_scope_path(file_scope).unwrap();
+            insert_extension_accessors(root, &mut res, file_path, &file.extensions)?;
+        }
+    }
     Ok(res)
 }
 
@@ -37,16 +46,26 @@ fn insert_message_declaration(
 ) -> Result<(), ProtoError> {
     let message_name = message_scope.name();
     let mut message_folder = Folder::new(message_name);
-    if !message_scope
-        .get_message_declaration()
-        .map(|d| d.entries.is_empty())
-        .unwrap_or(false)
+    let message_declaration = message_scope.get_message_declaration().unwrap();
+    if !message_declaration.entries.is_empty()
+        || root.extensions.contains_key(&message_declaration.id)
     {
         insert_message_types(&root, &mut message_folder, &message_scope)?;
         compile_encode(&root, &mut message_folder, &message_scope)?;
         compile_decode(&root, &mut message_folder, &message_scope)?;
+        compile_to_json(&root, &mut message_folder, &message_scope)?;
+        compile_from_json(&root, &mut message_folder, &message_scope)?;
     }
     insert_children(&root, &mut message_folder, &message_scope)?;
+    if !message_declaration.extensions.is_empty() {
+        let message_path = root.get_declaration_path(message_declaration.id).unwrap();
+        insert_extension_accessors(
+            root,
+            &mut message_folder,
+            message_path,
+            &message_declaration.extensions,
+        )?;
+    }
     message_parent_folder.entries.push(message_folder.into());
 
     Ok(())
@@ -67,10 +86,11 @@ fn insert_children(
             ProtoScope::Root(_) => unreachable!(),
             ProtoScope::Package(_) => unreachable!(),
             ProtoScope::File(_) => unreachable!(),
-            e @ ProtoScope::Enum(_) => insert_enum_declaration(message_folder, e),
+            e @ ProtoScope::Enum(_) => insert_enum_declaration(root, message_folder, e),
             m @ ProtoScope::Message(_) => {
                 insert_message_declaration(&root, message_folder, m)?;
             }
+            ProtoScope::Service(_) => unreachable!(),
         }
     }
     Ok(())
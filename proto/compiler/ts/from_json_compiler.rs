@@ -0,0 +1,1169 @@
+use std::rc::Rc;
+
+use crate::proto::{
+    error::ProtoError,
+    package,
+    proto_scope::{root_scope::RootScope, ProtoScope},
+};
+
+use super::{
+    ast::{
+        self, BinaryOperator, Call, ElementAccess, ExpressionChain, MethodCall, Prop, StatementList,
+    },
+    ast_builders::{
+        assign, binary, call_global, declare_const, declare_typed, for_each, id_expr, if_block,
+        import_util, is_array, is_type_of, key_element, number_expr, object_expr, object_keys,
+        return_statement, string_expr, throw_error,
+    },
+    compile_options::LongType,
+    constants::FROM_JSON_FUNCTION_NAME,
+    decode_compiler::get_default_message_value,
+    ensure_import::ensure_import,
+    enum_style::{enum_literal, enum_reader, import_enum_values},
+    json_helpers::{
+        google_protobuf_name, import_json_function, is_empty_message, is_not_json_object,
+        option_expr, options_interface, SPECIAL_JSON_TYPES,
+    },
+    json_runtime::import_runtime_function,
+    one_of_union::{one_of_case_value, union_one_of_group},
+};
+
+const OPTIONS_INTERFACE_NAME: &str = "FromJSONOptions";
+
+/// Generates `fromJSON`, which parses proto3 JSON value into the message
+/// in the same form as `decode` returns
+pub(super) fn compile_from_json(
+    root: &RootScope,
+    message_folder: &mut ast::Folder,
+    message_scope: &ProtoScope,
+) -> Result<(), ProtoError> {
+    let mut file = ast::File::new(FROM_JSON_FUNCTION_NAME.into());
+
+    let message_type_id: Rc<ast::Identifier> = ast::Identifier::from(message_scope.name()).into();
+    ensure_import(
+        &mut file,
+        ast::ImportDeclaration::import(
+            vec![ast::ImportSpecifier::new(Rc::clone(&message_type_id))],
+            "./types".into(),
+        ),
+    );
+    file.push_statement(options_interface(OPTIONS_INTERFACE_NAME, true).into());
+
+    let mut from_json_func = ast::FunctionDeclaration::new_exported(FROM_JSON_FUNCTION_NAME);
+    from_json_func.add_param(ast::Parameter::new("json", ast::Type::Any));
+    from_json_func.add_param(ast::Parameter::new_optional(
+        "options",
+        ast::Type::from_id(OPTIONS_INTERFACE_NAME),
+    ));
+    from_json_func.returns(ast::Type::reference(message_type_id));
+
+    let statements = match google_protobuf_name(root, message_scope.id().unwrap()).as_deref() {
+        Some(name) if SPECIAL_JSON_TYPES.contains(&name) => {
+            well_known_from_json(root, message_scope, &mut file, name)?
+        }
+        _ => message_from_json(root, message_scope, &mut file)?,
+    };
+    for statement in statements {
+        from_json_func.push_statement(statement);
+    }
+
+    file.push_statement(from_json_func.into());
+    message_folder.push_file(file);
+    Ok(())
+}
+
+fn message_from_json(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+) -> Result<Vec<ast::Statement>, ProtoError> {
+    let message_declaration = message_scope.get_message_declaration().unwrap();
+    let full_name = full_name(root, message_scope);
+    let json_expr = id_expr("json");
+    let message_expr = id_expr("message");
+    let key_expr = id_expr("key");
+    let value_expr = id_expr("value");
+
+    let fields = message_declaration.get_fields();
+    if fields.iter().any(|f| {
+        matches!(
+            f.field_type,
+            package::Type::Repeated(_) | package::Type::Map(_, _)
+        )
+    }) {
+        // Defaults of repeated and map fields are shared empty values of `util`
+        import_util(file);
+    }
+
+    // Unknown keys are the last branch of the key matching chain
+    let ignore_unknown_fields = option_expr("ignoreUnknownFields").into_parentheses();
+    let unknown_field = binary(
+        BinaryOperator::Plus,
+        binary(
+            BinaryOperator::Plus,
+            string_expr("Unknown field "),
+            Rc::clone(&key_expr),
+        ),
+        string_expr(&format!(" of {}", full_name)),
+    );
+    let mut key_chain = ast::Statement::from(if_block(
+        ignore_unknown_fields.not(),
+        vec![throw_error(unknown_field)],
+    ));
+    let mut field_branches = Vec::new();
+    for field in fields.iter() {
+        let one_of = union_one_of_group(root, message_declaration, field);
+        let store = |value: Rc<ast::Expression>| match one_of {
+            Some(one_of) => assign(
+                message_expr.prop(&one_of.name),
+                one_of_case_value(&field.json_name(), value),
+            ),
+            None => assign(message_expr.prop(&field.json_name()), value),
+        };
+        let statements = field_from_json(root, message_scope, file, field, store, &value_expr)?;
+        // Parsers accept both JSON and original names of fields
+        let json_name = field.canonical_json_name();
+        let mut is_field_key = binary(
+            BinaryOperator::StrictEqual,
+            Rc::clone(&key_expr),
+            string_expr(&json_name),
+        );
+        if json_name != field.name {
+            is_field_key = binary(
+                BinaryOperator::LogicalOr,
+                is_field_key,
+                binary(
+                    BinaryOperator::StrictEqual,
+                    Rc::clone(&key_expr),
+                    string_expr(&field.name),
+                ),
+            );
+        }
+        field_branches.push(if_block(is_field_key, statements));
+    }
+    for branch in field_branches.into_iter().rev() {
+        key_chain = ast::IfStatement {
+            else_statement: Some(key_chain.into()),
+            ..branch
+        }
+        .into();
+    }
+
+    Ok(vec![
+        if_block(
+            is_not_json_object(&json_expr),
+            vec![throw_error(string_expr(&format!(
+                "Expected JSON object for {}",
+                full_name
+            )))],
+        )
+        .into(),
+        declare_typed(
+            "message",
+            ast::Type::Any,
+            get_default_message_value(root, file, message_scope)?,
+        ),
+        declare_const("keys", object_keys(&json_expr)),
+        for_each(
+            "i",
+            &id_expr("keys"),
+            vec![
+                declare_const("key", id_expr("keys").element(id_expr("i"))),
+                declare_const("value", json_expr.element(Rc::clone(&key_expr))),
+                key_chain,
+            ],
+        ),
+        return_statement("message".into()),
+    ])
+}
+
+/// Statements parsing `value` of the field, which is saved by `store`
+fn field_from_json(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    field: &package::Field,
+    store: impl Fn(Rc<ast::Expression>) -> ast::Statement,
+    value_expr: &Rc<ast::Expression>,
+) -> Result<Vec<ast::Statement>, ProtoError> {
+    let statements = match &field.field_type {
+        package::Type::Repeated(element_type) => {
+            let push_element = store_from_json_value(
+                root,
+                message_scope,
+                file,
+                element_type,
+                value_expr.element(id_expr("j")).into(),
+                |element| id_expr("arr").method_call("push", vec![element]).into(),
+            )?;
+            vec![
+                declare_typed(
+                    "arr",
+                    ast::Type::array(ast::Type::Any),
+                    ast::Expression::ArrayLiteralExpression(vec![]),
+                ),
+                for_each("j", value_expr, push_element),
+                store(id_expr("arr")),
+            ]
+        }
+        package::Type::Map(_, value_type) => {
+            let assign_value = store_from_json_value(
+                root,
+                message_scope,
+                file,
+                value_type,
+                key_element(value_expr, "mapKeys", "j").into(),
+                |value| assign(key_element(&id_expr("obj"), "mapKeys", "j"), value),
+            )?;
+            vec![
+                declare_typed(
+                    "obj",
+                    ast::Type::Any,
+                    ast::Expression::ObjectLiteralExpression(vec![]),
+                ),
+                declare_const("mapKeys", object_keys(value_expr)),
+                for_each("j", &id_expr("mapKeys"), assign_value),
+                store(id_expr("obj")),
+            ]
+        }
+        field_type => {
+            let statements = store_from_json_value(
+                root,
+                message_scope,
+                file,
+                field_type,
+                Rc::clone(value_expr),
+                store,
+            )?;
+            if let package::Type::Message(m_id) = field_type {
+                if google_protobuf_name(root, *m_id).as_deref() == Some("Value") {
+                    // Null is a valid `google.protobuf.Value`
+                    return Ok(statements);
+                }
+            }
+            statements
+        }
+    };
+    // Null means default value of the field
+    Ok(vec![if_block(
+        binary(
+            BinaryOperator::StrictNotEqual,
+            Rc::clone(value_expr),
+            ast::Expression::Null,
+        ),
+        statements,
+    )
+    .into()])
+}
+
+/// Statements parsing single `value` of the field type, which is saved by `store`.
+/// Unknown names of enums are ignored with `ignoreUnknownFields`, so they are not saved
+fn store_from_json_value(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    field_type: &package::Type,
+    value: Rc<ast::Expression>,
+    store: impl FnOnce(Rc<ast::Expression>) -> ast::Statement,
+) -> Result<Vec<ast::Statement>, ProtoError> {
+    let enum_id = match field_type {
+        package::Type::Enum(enum_id) => *enum_id,
+        _ => {
+            let value = from_json_value(root, message_scope, file, field_type, value)?;
+            return Ok(vec![store(value)]);
+        }
+    };
+    let values_expr = import_enum_values(root, message_scope, file, enum_id);
+    let number = import_runtime_function(root, message_scope, file, "enumFromJSON").call(vec![
+        values_expr,
+        value,
+        id_expr("options"),
+    ]);
+    let number_expr = id_expr("number");
+    // String union enums keep names of parsed numbers
+    let enum_value = match enum_reader(root, message_scope, file, field_type) {
+        Some(enum_reader) => enum_reader.apply(Rc::clone(&number_expr)),
+        None => Rc::clone(&number_expr),
+    };
+    Ok(vec![
+        declare_const("number", number),
+        if_block(
+            binary(
+                BinaryOperator::StrictNotEqual,
+                number_expr,
+                ast::Expression::Undefined,
+            ),
+            vec![store(enum_value)],
+        )
+        .into(),
+    ])
+}
+
+/// Parses single value of the field type, i.e. neither repeated nor map
+fn from_json_value(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    field_type: &package::Type,
+    value: Rc<ast::Expression>,
+) -> Result<Rc<ast::Expression>, ProtoError> {
+    use package::Type;
+    let expr = match field_type {
+        Type::Message(m_id) if is_empty_message(root, *m_id) => {
+            ast::Expression::ObjectLiteralExpression(vec![])
+        }
+        Type::Message(m_id) => import_json_function(
+            root,
+            message_scope,
+            file,
+            FROM_JSON_FUNCTION_NAME,
+            "f",
+            *m_id,
+        )
+        .call(vec![value, id_expr("options")]),
+        Type::Double
+        | Type::Float
+        | Type::Int32
+        | Type::Uint32
+        | Type::Sint32
+        | Type::Fixed32
+        | Type::Sfixed32 => call_global("Number", vec![value]),
+        Type::Int64 | Type::Sint64 | Type::Sfixed64 => {
+            long_from_json(root, message_scope, file, value, false)
+        }
+        Type::Uint64 | Type::Fixed64 => long_from_json(root, message_scope, file, value, true),
+        Type::Bytes => {
+            import_runtime_function(root, message_scope, file, "bytesFromJSON").call(vec![value])
+        }
+        // Enums are parsed by `store_from_json_value`, which skips unknown names
+        Type::Repeated(_) | Type::Map(_, _) | Type::Enum(_) => unreachable!(),
+        Type::Bool | Type::String => return Ok(value),
+    };
+    Ok(expr.into())
+}
+
+fn long_from_json(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    value: Rc<ast::Expression>,
+    unsigned: bool,
+) -> ast::Expression {
+    let unsigned_expr = if unsigned {
+        ast::Expression::True
+    } else {
+        ast::Expression::False
+    };
+    import_runtime_function(root, message_scope, file, "longFromJSON")
+        .call(vec![value, unsigned_expr.into()])
+}
+
+fn full_name(root: &RootScope, message_scope: &ProtoScope) -> String {
+    root.get_declaration_path(message_scope.id().unwrap())
+        .unwrap()
+        .full_name()
+}
+
+/// Bodies of well-known types with special JSON forms
+fn well_known_from_json(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    name: &str,
+) -> Result<Vec<ast::Statement>, ProtoError> {
+    let message_declaration = message_scope.get_message_declaration().unwrap();
+    let fields = message_declaration.get_fields();
+    let full_name = full_name(root, message_scope);
+    let json_expr = id_expr("json");
+    let is_not_string = binary(
+        BinaryOperator::StrictNotEqual,
+        json_expr.type_of(),
+        string_expr("string"),
+    );
+    let invalid_value = binary(
+        BinaryOperator::Plus,
+        string_expr(&format!("Invalid {} ", full_name)),
+        Rc::clone(&json_expr),
+    );
+    let statements = match name {
+        "Any" => {
+            let type_url_expr = id_expr("typeUrl");
+            let entry_expr = id_expr("entry");
+            let fields_expr = id_expr("fields");
+            let registry_entry = binary(
+                BinaryOperator::LogicalAnd,
+                option_expr("typeRegistry"),
+                id_expr("options")
+                    .prop("typeRegistry")
+                    .into_element(Rc::clone(&type_url_expr)),
+            );
+            let type_not_found = binary(
+                BinaryOperator::Plus,
+                binary(
+                    BinaryOperator::Plus,
+                    string_expr("Type "),
+                    Rc::clone(&type_url_expr),
+                ),
+                string_expr(" is not found in typeRegistry of options"),
+            );
+            let special_types = SPECIAL_JSON_TYPES
+                .iter()
+                .map(|name| string_expr(&format!("google.protobuf.{}", name)))
+                .collect::<Vec<_>>();
+            let type_name = type_url_expr.method_call(
+                "substring",
+                vec![binary(
+                    BinaryOperator::Plus,
+                    type_url_expr.method_call("lastIndexOf", vec![string_expr("/")]),
+                    number_expr(1f64),
+                )
+                .into()],
+            );
+            // Special JSON forms are nested under `value`, other messages are merged with `@type`
+            let is_not_special = binary(
+                BinaryOperator::StrictEqual,
+                id_expr("specialTypes").method_call("indexOf", vec![id_expr("typeName")]),
+                number_expr(-1f64),
+            );
+            let is_field = binary(
+                BinaryOperator::StrictNotEqual,
+                id_expr("keys").element(id_expr("i")),
+                string_expr("@type"),
+            );
+            let packed_value = Rc::new(entry_expr.method_call(
+                "encode",
+                vec![entry_expr
+                    .method_call("fromJSON", vec![Rc::clone(&fields_expr), id_expr("options")])
+                    .into()],
+            ))
+            .method_call("finish", vec![]);
+            vec![
+                if_block(
+                    is_not_json_object(&json_expr),
+                    vec![throw_error(string_expr(&format!(
+                        "Expected JSON object for {}",
+                        full_name
+                    )))],
+                )
+                .into(),
+                declare_const("typeUrl", json_expr.element(string_expr("@type"))),
+                declare_const("entry", registry_entry),
+                if_block(entry_expr.not(), vec![throw_error(type_not_found)]).into(),
+                declare_const(
+                    "specialTypes",
+                    ast::Expression::ArrayLiteralExpression(special_types),
+                ),
+                declare_const("typeName", type_name),
+                ast::VariableDeclarationList::declare_typed_let(
+                    Rc::new("fields".into()),
+                    ast::Type::Any.into(),
+                    json_expr.prop("value"),
+                )
+                .into(),
+                if_block(
+                    is_not_special,
+                    vec![
+                        assign(
+                            Rc::clone(&fields_expr),
+                            ast::Expression::ObjectLiteralExpression(vec![]),
+                        ),
+                        declare_const("keys", object_keys(&json_expr)),
+                        for_each(
+                            "i",
+                            &id_expr("keys"),
+                            vec![if_block(
+                                is_field,
+                                vec![assign(
+                                    key_element(&fields_expr, "keys", "i"),
+                                    key_element(&json_expr, "keys", "i"),
+                                )],
+                            )
+                            .into()],
+                        ),
+                    ],
+                )
+                .into(),
+                return_statement(object_expr(vec![
+                    ("type_url", type_url_expr),
+                    ("value", packed_value.into()),
+                ])),
+            ]
+        }
+        "Timestamp" => {
+            let dot_expr = id_expr("dot");
+            let end_expr = id_expr("end");
+            let millis_expr = id_expr("millis");
+            let is_digit = binary(
+                BinaryOperator::StrictNotEqual,
+                string_expr("0123456789").method_call(
+                    "indexOf",
+                    vec![json_expr
+                        .method_call("charAt", vec![Rc::clone(&end_expr)])
+                        .into()],
+                ),
+                number_expr(-1f64),
+            );
+            let mut skip_digits = ast::WhileStatement::new(
+                binary(
+                    BinaryOperator::LogicalAnd,
+                    binary(
+                        BinaryOperator::LessThan,
+                        Rc::clone(&end_expr),
+                        json_expr.prop("length"),
+                    ),
+                    is_digit,
+                )
+                .into(),
+            );
+            skip_digits.push_statement(assign(
+                Rc::clone(&end_expr),
+                binary(
+                    BinaryOperator::Plus,
+                    Rc::clone(&end_expr),
+                    number_expr(1f64),
+                ),
+            ));
+            let fraction = json_expr.method_call(
+                "substring",
+                vec![
+                    binary(
+                        BinaryOperator::Plus,
+                        Rc::clone(&dot_expr),
+                        number_expr(1f64),
+                    )
+                    .into(),
+                    Rc::clone(&end_expr),
+                ],
+            );
+            // Fraction of second is kept in nanos, because `Date` has only milliseconds
+            let without_fraction = binary(
+                BinaryOperator::Plus,
+                json_expr.method_call("substring", vec![number_expr(0f64), Rc::clone(&dot_expr)]),
+                json_expr.method_call("substring", vec![Rc::clone(&end_expr)]),
+            );
+            vec![
+                if_block(
+                    is_not_string,
+                    vec![throw_error(string_expr(&format!(
+                        "Expected RFC 3339 string for {}",
+                        full_name
+                    )))],
+                )
+                .into(),
+                ast::VariableDeclarationList::declare_let(
+                    Rc::new("text".into()),
+                    ast::Expression::from("json"),
+                )
+                .into(),
+                ast::VariableDeclarationList::declare_let(Rc::new("nanos".into()), 0f64.into())
+                    .into(),
+                declare_const(
+                    "dot",
+                    json_expr.method_call("indexOf", vec![string_expr(".")]),
+                ),
+                if_block(
+                    binary(
+                        BinaryOperator::StrictNotEqual,
+                        Rc::clone(&dot_expr),
+                        number_expr(-1f64),
+                    ),
+                    vec![
+                        ast::VariableDeclarationList::declare_let(
+                            Rc::new("end".into()),
+                            binary(
+                                BinaryOperator::Plus,
+                                Rc::clone(&dot_expr),
+                                number_expr(1f64),
+                            ),
+                        )
+                        .into(),
+                        skip_digits.into(),
+                        assign(id_expr("nanos"), nanos_from_fraction(fraction)),
+                        assign(id_expr("text"), without_fraction),
+                    ],
+                )
+                .into(),
+                declare_const(
+                    "millis",
+                    id_expr("Date").method_call("parse", vec![id_expr("text")]),
+                ),
+                if_block(
+                    call_global("isNaN", vec![Rc::clone(&millis_expr)]),
+                    vec![throw_error(invalid_value)],
+                )
+                .into(),
+                return_statement(object_expr(vec![
+                    (
+                        "seconds",
+                        long_from_json(
+                            root,
+                            message_scope,
+                            file,
+                            binary(BinaryOperator::Divide, millis_expr, number_expr(1000f64))
+                                .into(),
+                            false,
+                        )
+                        .into(),
+                    ),
+                    ("nanos", id_expr("nanos")),
+                ])),
+            ]
+        }
+        "Duration" => {
+            let is_not_duration = binary(
+                BinaryOperator::LogicalOr,
+                is_not_string,
+                binary(
+                    BinaryOperator::StrictNotEqual,
+                    json_expr.method_call(
+                        "charAt",
+                        vec![binary(
+                            BinaryOperator::Minus,
+                            json_expr.prop("length"),
+                            number_expr(1f64),
+                        )
+                        .into()],
+                    ),
+                    string_expr("s"),
+                ),
+            );
+            let parts_expr = id_expr("parts");
+            let seconds_expr = id_expr("seconds");
+            let nanos_expr = id_expr("nanos");
+            let is_negative = binary(
+                BinaryOperator::StrictEqual,
+                json_expr.method_call("charAt", vec![number_expr(0f64)]),
+                string_expr("-"),
+            );
+            let parts = Rc::new(json_expr.method_call(
+                "substring",
+                vec![
+                    number_expr(0f64),
+                    binary(
+                        BinaryOperator::Minus,
+                        json_expr.prop("length"),
+                        number_expr(1f64),
+                    )
+                    .into(),
+                ],
+            ))
+            .method_call("split", vec![string_expr(".")]);
+            // Nanos have the same sign as seconds, including "-0.5s"
+            let nanos = ast::Expression::conditional(
+                binary(
+                    BinaryOperator::StrictEqual,
+                    parts_expr.prop("length"),
+                    number_expr(2f64),
+                )
+                .into(),
+                binary(
+                    BinaryOperator::Multiply,
+                    id_expr("sign"),
+                    nanos_from_fraction(parts_expr.element(number_expr(1f64))),
+                )
+                .into(),
+                number_expr(0f64),
+            );
+            vec![
+                if_block(
+                    is_not_duration,
+                    vec![throw_error(string_expr(&format!(
+                        "Expected duration string for {}",
+                        full_name
+                    )))],
+                )
+                .into(),
+                declare_const(
+                    "sign",
+                    ast::Expression::conditional(
+                        is_negative.into(),
+                        number_expr(-1f64),
+                        number_expr(1f64),
+                    ),
+                ),
+                declare_const("parts", parts),
+                declare_const(
+                    "seconds",
+                    call_global("Number", vec![parts_expr.element(number_expr(0f64)).into()]),
+                ),
+                declare_const("nanos", nanos),
+                if_block(
+                    binary(
+                        BinaryOperator::LogicalOr,
+                        call_global("isNaN", vec![Rc::clone(&seconds_expr)]),
+                        call_global("isNaN", vec![Rc::clone(&nanos_expr)]),
+                    ),
+                    vec![throw_error(invalid_value)],
+                )
+                .into(),
+                return_statement(object_expr(vec![
+                    (
+                        "seconds",
+                        long_from_json(root, message_scope, file, seconds_expr, false).into(),
+                    ),
+                    ("nanos", nanos_expr),
+                ])),
+            ]
+        }
+        "FieldMask" => {
+            let path_from_json = import_runtime_function(root, message_scope, file, "pathFromJSON");
+            let paths_expr = id_expr("paths");
+            vec![
+                if_block(
+                    is_not_string,
+                    vec![throw_error(string_expr(&format!(
+                        "Expected string for {}",
+                        full_name
+                    )))],
+                )
+                .into(),
+                declare_typed(
+                    "paths",
+                    ast::Type::array(ast::Type::String),
+                    ast::Expression::conditional(
+                        binary(
+                            BinaryOperator::StrictEqual,
+                            Rc::clone(&json_expr),
+                            string_expr(""),
+                        )
+                        .into(),
+                        Rc::new(ast::Expression::ArrayLiteralExpression(vec![])),
+                        json_expr
+                            .method_call("split", vec![string_expr(",")])
+                            .into(),
+                    ),
+                ),
+                for_each(
+                    "i",
+                    &paths_expr,
+                    vec![assign(
+                        paths_expr.element(id_expr("i")),
+                        path_from_json.call(vec![paths_expr.element(id_expr("i")).into()]),
+                    )],
+                ),
+                return_statement(object_expr(vec![("paths", paths_expr)])),
+            ]
+        }
+        "Struct" => {
+            let value_type = match &fields[0].field_type {
+                package::Type::Map(_, value_type) => value_type,
+                _ => unreachable!(),
+            };
+            let fields_expr = id_expr("fields");
+            let value = from_json_value(
+                root,
+                message_scope,
+                file,
+                value_type,
+                key_element(&json_expr, "keys", "i").into(),
+            )?;
+            vec![
+                if_block(
+                    is_not_json_object(&json_expr),
+                    vec![throw_error(string_expr(&format!(
+                        "Expected JSON object for {}",
+                        full_name
+                    )))],
+                )
+                .into(),
+                declare_typed(
+                    "fields",
+                    ast::Type::Any,
+                    ast::Expression::ObjectLiteralExpression(vec![]),
+                ),
+                declare_const("keys", object_keys(&json_expr)),
+                for_each(
+                    "i",
+                    &id_expr("keys"),
+                    vec![assign(key_element(&fields_expr, "keys", "i"), value)],
+                ),
+                return_statement(object_expr(vec![("fields", fields_expr)])),
+            ]
+        }
+        "ListValue" => {
+            let element_type = match &fields[0].field_type {
+                package::Type::Repeated(element_type) => element_type,
+                _ => unreachable!(),
+            };
+            let values_expr = id_expr("values");
+            let value = from_json_value(
+                root,
+                message_scope,
+                file,
+                element_type,
+                json_expr.element(id_expr("i")).into(),
+            )?;
+            vec![
+                if_block(
+                    is_array(&json_expr).not(),
+                    vec![throw_error(string_expr(&format!(
+                        "Expected JSON array for {}",
+                        full_name
+                    )))],
+                )
+                .into(),
+                declare_typed(
+                    "values",
+                    ast::Type::array(ast::Type::Any),
+                    ast::Expression::ArrayLiteralExpression(vec![]),
+                ),
+                for_each(
+                    "i",
+                    &json_expr,
+                    vec![values_expr.method_call("push", vec![value]).into()],
+                ),
+                return_statement(object_expr(vec![("values", values_expr)])),
+            ]
+        }
+        "Value" => {
+            // Kind of the value is chosen by type of JSON value
+            let mut statements = Vec::new();
+            for field in fields {
+                let (is_kind, value) = match &field.field_type {
+                    package::Type::Enum(enum_id) => (
+                        binary(
+                            BinaryOperator::StrictEqual,
+                            Rc::clone(&json_expr),
+                            ast::Expression::Null,
+                        ),
+                        enum_literal(root, *enum_id, 0).into(),
+                    ),
+                    package::Type::Double => {
+                        (is_type_of(&json_expr, "number"), Rc::clone(&json_expr))
+                    }
+                    package::Type::String => {
+                        (is_type_of(&json_expr, "string"), Rc::clone(&json_expr))
+                    }
+                    package::Type::Bool => {
+                        (is_type_of(&json_expr, "boolean"), Rc::clone(&json_expr))
+                    }
+                    field_type @ package::Type::Message(m_id) => {
+                        let value = from_json_value(
+                            root,
+                            message_scope,
+                            file,
+                            field_type,
+                            Rc::clone(&json_expr),
+                        )?;
+                        let is_list = is_array(&json_expr);
+                        if google_protobuf_name(root, *m_id).as_deref() == Some("ListValue") {
+                            (is_list, value)
+                        } else {
+                            let is_object = binary(
+                                BinaryOperator::LogicalAnd,
+                                is_type_of(&json_expr, "object"),
+                                is_list.not(),
+                            );
+                            (is_object, value)
+                        }
+                    }
+                    _ => unreachable!(),
+                };
+                let message = match union_one_of_group(root, message_declaration, field) {
+                    Some(one_of) => object_expr(vec![(
+                        &one_of.name,
+                        one_of_case_value(&field.json_name(), value).into(),
+                    )]),
+                    None => object_expr(vec![(&field.json_name(), value)]),
+                };
+                statements.push(if_block(is_kind, vec![return_statement(message)]).into());
+            }
+            statements.push(throw_error(string_expr(&format!(
+                "Unsupported JSON value for {}",
+                full_name
+            ))));
+            statements
+        }
+        // Wrappers are parsed from their wrapped values
+        _ => {
+            let value = from_json_value(
+                root,
+                message_scope,
+                file,
+                &fields[0].field_type,
+                Rc::clone(&json_expr),
+            )?;
+            vec![return_statement(object_expr(vec![("value", value)]))]
+        }
+    };
+    Ok(statements)
+}
+
+/// Nanos from digits after the point: `Number(({digits} + "000000000").substring(0, 9))`
+fn nanos_from_fraction(digits: ast::Expression) -> ast::Expression {
+    let padded = binary(BinaryOperator::Plus, digits, string_expr("000000000"));
+    call_global(
+        "Number",
+        vec![Rc::new(padded)
+            .method_call("substring", vec![number_expr(0f64), number_expr(9f64)])
+            .into()],
+    )
+}
+
+/// Adds helpers of `fromJSON` files to the JSON runtime file
+pub(super) fn push_from_json_helpers(file: &mut ast::File, long_type: LongType) {
+    file.push_statement(options_interface(OPTIONS_INTERFACE_NAME, true).into());
+    file.push_statement(enum_from_json_function().into());
+    let long_from_json = long_from_json_function(file, long_type);
+    file.push_statement(long_from_json.into());
+    let bytes_from_json = bytes_from_json_function(file);
+    file.push_statement(bytes_from_json.into());
+    file.push_statement(path_from_json_function().into());
+}
+
+/// `enumFromJSON(values, value, options)` accepts both names and numbers of enum values,
+/// unknown names are `undefined` with `ignoreUnknownFields`
+fn enum_from_json_function() -> ast::FunctionDeclaration {
+    let mut function = ast::FunctionDeclaration::new_exported("enumFromJSON");
+    function.add_param(ast::Parameter::new("values", ast::Type::Any));
+    function.add_param(ast::Parameter::new("value", ast::Type::Any));
+    function.add_param(ast::Parameter::new_optional(
+        "options",
+        ast::Type::from_id(OPTIONS_INTERFACE_NAME),
+    ));
+    function.returns(ast::Type::Number.or(&ast::Type::Undefined));
+    let value_expr = id_expr("value");
+    let number = id_expr("values").element(Rc::clone(&value_expr));
+    function.push_statement(
+        if_block(
+            is_type_of(&value_expr, "number"),
+            vec![return_statement("value".into())],
+        )
+        .into(),
+    );
+    function.push_statement(
+        if_block(
+            is_type_of(
+                &Rc::new(id_expr("values").element(Rc::clone(&value_expr))),
+                "number",
+            ),
+            vec![return_statement(number)],
+        )
+        .into(),
+    );
+    // Unknown names are ignored like unknown fields
+    function.push_statement(
+        if_block(
+            option_expr("ignoreUnknownFields"),
+            vec![return_statement(ast::Expression::Undefined)],
+        )
+        .into(),
+    );
+    function.push_statement(throw_error(binary(
+        BinaryOperator::Plus,
+        string_expr("Unknown enum value "),
+        value_expr,
+    )));
+    function
+}
+
+/// `longFromJSON(value, unsigned)` parses numbers and strings into the configured
+/// representation of 64-bit integers, `Long` falls back to numbers when it is not available,
+/// like `Reader` does
+fn long_from_json_function(file: &mut ast::File, long_type: LongType) -> ast::FunctionDeclaration {
+    let mut function = ast::FunctionDeclaration::new_exported("longFromJSON");
+    function.add_param(ast::Parameter::new("value", ast::Type::Any));
+    function.add_param(ast::Parameter::new("unsigned", ast::Type::Boolean));
+    function.returns(ast::Type::Any);
+    let value = match long_type {
+        LongType::Long => {
+            let long_expr: Rc<ast::Expression> = import_util(file).prop("Long").into();
+            ast::Expression::conditional(
+                Rc::clone(&long_expr),
+                long_expr
+                    .method_call(
+                        "fromString",
+                        vec![
+                            call_global("String", vec![id_expr("value")]).into(),
+                            id_expr("unsigned"),
+                        ],
+                    )
+                    .into(),
+                call_global("Number", vec![id_expr("value")]).into(),
+            )
+        }
+        LongType::BigInt => call_global("BigInt", vec![id_expr("value")]),
+        LongType::String => call_global(
+            "String",
+            vec![call_global("BigInt", vec![id_expr("value")]).into()],
+        ),
+        LongType::Number => call_global("Number", vec![id_expr("value")]),
+    };
+    function.push_statement(return_statement(value));
+    function
+}
+
+/// `bytesFromJSON(value)` decodes both standard and URL-safe base64
+fn bytes_from_json_function(file: &mut ast::File) -> ast::FunctionDeclaration {
+    let util_expr = import_util(file);
+    let mut function = ast::FunctionDeclaration::new_exported("bytesFromJSON");
+    function.add_param(ast::Parameter::new("value", ast::Type::String));
+    function.returns(ast::Type::from_id("Uint8Array"));
+    let base64_expr = id_expr("base64");
+    let util_base64: Rc<ast::Expression> = util_expr.prop("base64").into();
+    let standard = id_expr("value")
+        .method_call("split", vec![string_expr("-")])
+        .into_method_call("join", vec![string_expr("+")])
+        .into_method_call("split", vec![string_expr("_")])
+        .into_method_call("join", vec![string_expr("/")]);
+    function.push_statement(declare_const("base64", standard));
+    function.push_statement(declare_const(
+        "buffer",
+        util_expr.method_call(
+            "newBuffer",
+            vec![util_base64
+                .method_call("length", vec![Rc::clone(&base64_expr)])
+                .into()],
+        ),
+    ));
+    function.push_statement(
+        util_base64
+            .method_call(
+                "decode",
+                vec![base64_expr, id_expr("buffer"), number_expr(0f64)],
+            )
+            .into(),
+    );
+    function.push_statement(return_statement("buffer".into()));
+    function
+}
+
+/// `pathFromJSON(path)` converts lowerCamelCase field path of `FieldMask` into snake_case
+fn path_from_json_function() -> ast::FunctionDeclaration {
+    let mut function = ast::FunctionDeclaration::new_exported("pathFromJSON");
+    function.add_param(ast::Parameter::new("path", ast::Type::String));
+    function.returns(ast::Type::String);
+    let res_expr = id_expr("res");
+    let c_expr = id_expr("c");
+    let lower_expr = id_expr("lower");
+    function.push_statement(
+        ast::VariableDeclarationList::declare_let(
+            Rc::new("res".into()),
+            ast::Expression::StringLiteral("".into()),
+        )
+        .into(),
+    );
+    function.push_statement(for_each(
+        "i",
+        &id_expr("path"),
+        vec![
+            declare_const(
+                "c",
+                id_expr("path").method_call("charAt", vec![id_expr("i")]),
+            ),
+            declare_const("lower", c_expr.method_call("toLowerCase", vec![])),
+            if_block(
+                binary(
+                    BinaryOperator::StrictNotEqual,
+                    Rc::clone(&c_expr),
+                    Rc::clone(&lower_expr),
+                ),
+                vec![assign(
+                    Rc::clone(&res_expr),
+                    binary(BinaryOperator::Plus, Rc::clone(&res_expr), string_expr("_")),
+                )],
+            )
+            .into(),
+            assign(
+                Rc::clone(&res_expr),
+                binary(BinaryOperator::Plus, Rc::clone(&res_expr), lower_expr),
+            ),
+        ],
+    ));
+    function.push_statement(return_statement("res".into()));
+    function
+}
+
+#[cfg(test)]
+mod test {
+    use crate::proto::compiler::ts::{
+        codegen_test::{compile, compile_files},
+        compile_options::CompileOptions,
+    };
+
+    #[test]
+    fn parses_json_and_original_field_names() {
+        let files = compile(
+            r#"
+            syntax = "proto3";
+            enum Color { RED = 0; GREEN = 1; }
+            message Hello {
+                int64 big_number = 1;
+                fixed64 id = 2;
+                Color favorite_color = 3;
+                string user_name = 4 [json_name = "login"];
+                bytes data = 5;
+            }
+        "#,
+            CompileOptions::default(),
+        )
+        .unwrap();
+        let from_json = &files["test/Hello/fromJSON.ts"];
+        assert!(from_json.contains("if (key === \"bigNumber\" || key === \"big_number\") {\n"));
+        assert!(from_json.contains("message.big_number = longFromJSON(value, false)\n"));
+        assert!(from_json.contains("message.id = longFromJSON(value, true)\n"));
+        assert!(from_json.contains("const number = enumFromJSON(Color, value, options)\n"));
+        assert!(from_json.contains("message.favorite_color = colorFromNumber(number)\n"));
+        assert!(from_json.contains("} else if (key === \"login\" || key === \"user_name\") {\n"));
+        assert!(from_json.contains("message.data = bytesFromJSON(value)\n"));
+        assert!(from_json.contains("throw new Error(\"Unknown field \" + key + \" of Hello\")\n"));
+    }
+
+    #[test]
+    fn skips_unknown_enum_names() {
+        let files = compile(
+            r#"
+            syntax = "proto3";
+            enum Color { RED = 0; GREEN = 1; }
+            message Hello {
+                Color c = 1;
+                repeated Color all = 2;
+                map<string, Color> by = 3;
+            }
+        "#,
+            CompileOptions::default(),
+        )
+        .unwrap();
+        let from_json = &files["test/Hello/fromJSON.ts"];
+        assert!(from_json.contains(
+            "const number = enumFromJSON(Color, value, options)\n        \
+             if (number !== undefined) {\n          message.c = colorFromNumber(number)\n"
+        ));
+        assert!(from_json.contains(
+            "const number = enumFromJSON(Color, value[j], options)\n          \
+             if (number !== undefined) {\n            arr.push(colorFromNumber(number))\n"
+        ));
+        assert!(from_json.contains(
+            "if (number !== undefined) {\n            obj[mapKeys[j]] = colorFromNumber(number)\n"
+        ));
+        let runtime = &files["jsonRuntime.ts"];
+        assert!(runtime.contains(
+            "if (options && options.ignoreUnknownFields) {\n    return undefined\n  }\n"
+        ));
+        assert!(!runtime.contains("return 0\n"));
+    }
+
+    #[test]
+    fn parses_well_known_types() {
+        let files = compile_files(
+            &[(
+                "timestamp.proto",
+                r#"
+                syntax = "proto3";
+                package google.protobuf;
+                message Timestamp { int64 seconds = 1; int32 nanos = 2; }
+                message Int64Value { int64 value = 1; }
+            "#,
+            )],
+            CompileOptions::default(),
+        )
+        .unwrap();
+        let timestamp = &files["google/protobuf/timestamp/Timestamp/fromJSON.ts"];
+        assert!(timestamp.contains(
+            "throw new Error(\"Expected RFC 3339 string for google.protobuf.Timestamp\")\n"
+        ));
+        assert!(timestamp.contains("seconds: longFromJSON(millis / 1000, false),\n"));
+        let wrapper = &files["google/protobuf/timestamp/Int64Value/fromJSON.ts"];
+        assert!(wrapper.contains("value: longFromJSON(json, false),\n"));
+    }
+}
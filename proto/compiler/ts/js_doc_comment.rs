@@ -0,0 +1,21 @@
+use crate::proto::package::Comments;
+
+use super::ast;
+
+/// Joins leading and trailing comments of declarations into a single doc comment,
+/// detached comments are not documentation of the declaration and are skipped
+pub(super) fn js_doc_comment(comments: &[&Comments]) -> Option<ast::JsDocComment> {
+    let paragraphs = comments
+        .iter()
+        .flat_map(|comments| [&comments.leading, &comments.trailing])
+        .flatten()
+        .map(|text| text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>();
+    if paragraphs.is_empty() {
+        return None;
+    }
+    Some(ast::JsDocComment {
+        text: paragraphs.join("\n\n").into(),
+    })
+}
@@ -0,0 +1,139 @@
+use std::rc::Rc;
+
+use crate::proto::proto_scope::{root_scope::RootScope, ProtoScope};
+
+use super::{
+    ast::{self, BinaryOperator, ExpressionChain, Prop},
+    ast_builders::{binary, id_expr, is_array, string_expr},
+    ensure_import::ensure_import,
+    get_relative_import::get_relative_import_string,
+    ts_path::TsPath,
+};
+
+/// Well-known types, which are converted to JSON values other than objects
+/// and are nested under `value` key when packed into `google.protobuf.Any`
+pub(super) const SPECIAL_JSON_TYPES: [&str; 16] = [
+    "Any",
+    "Duration",
+    "FieldMask",
+    "Struct",
+    "Value",
+    "ListValue",
+    "Timestamp",
+    "DoubleValue",
+    "FloatValue",
+    "Int64Value",
+    "UInt64Value",
+    "Int32Value",
+    "UInt32Value",
+    "BoolValue",
+    "StringValue",
+    "BytesValue",
+];
+
+/// Name of the declaration if it is one of `google.protobuf` types
+pub(super) fn google_protobuf_name(root: &RootScope, declaration_id: usize) -> Option<String> {
+    let full_name = root.get_declaration_path(declaration_id)?.full_name();
+    full_name
+        .strip_prefix("google.protobuf.")
+        .filter(|name| !name.contains('.'))
+        .map(String::from)
+}
+
+/// Messages without fields and extensions, e.g. `google.protobuf.Empty`, have no generated
+/// files, so they are converted in place
+pub(super) fn is_empty_message(root: &RootScope, message_id: usize) -> bool {
+    root.get_declaration(message_id)
+        .and_then(|scope| {
+            scope
+                .get_message_declaration()
+                .map(|m| m.entries.is_empty() && !root.extensions.contains_key(&message_id))
+        })
+        .unwrap_or(false)
+}
+
+/// Imports `toJSON` or `fromJSON` of another message as `{prefix}{id}`
+pub(super) fn import_json_function(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    function_name: &str,
+    prefix: &str,
+    m_id: usize,
+) -> Rc<ast::Expression> {
+    let function_path = {
+        let mut ts_path = TsPath::from(root.get_declaration_path(m_id).unwrap());
+        ts_path.push_file(function_name);
+        ts_path.push_function(function_name);
+        ts_path
+    };
+    let current_file_path = {
+        let mut ts_path = TsPath::from(
+            root.get_declaration_path(message_scope.id().unwrap())
+                .unwrap(),
+        );
+        ts_path.push_file(function_name);
+        ts_path
+    };
+    match get_relative_import_string(&current_file_path, &function_path) {
+        Some(import_string) => {
+            let imported_name = Rc::new(ast::Identifier::from(format!("{}{}", prefix, m_id)));
+            let import_stmt = ast::ImportDeclaration::import(
+                vec![ast::ImportSpecifier {
+                    name: Rc::clone(&imported_name),
+                    property_name: Some(Rc::new(function_name.into())),
+                }],
+                import_string.into(),
+            );
+            ensure_import(file, import_stmt);
+            Rc::new(ast::Expression::from(imported_name))
+        }
+        None => id_expr(function_name),
+    }
+}
+
+/// `export interface {name} { ... }` with options of conversion functions
+pub(super) fn options_interface(name: &str, parsing: bool) -> ast::InterfaceDeclaration {
+    let mut interface = ast::InterfaceDeclaration::new_exported(name.into());
+    if parsing {
+        interface.members.push(
+            ast::PropertySignature::new_optional("ignoreUnknownFields".into(), ast::Type::Boolean)
+                .into(),
+        );
+    }
+    // Functions of messages packed into `google.protobuf.Any` by type URL
+    interface.members.push(
+        ast::PropertySignature::new_optional(
+            "typeRegistry".into(),
+            ast::Type::Record(ast::Type::String.into(), ast::Type::Any.into()),
+        )
+        .into(),
+    );
+    interface
+}
+
+/// Whether value is not a JSON object, i.e. a primitive, null or an array
+pub(super) fn is_not_json_object(value: &Rc<ast::Expression>) -> ast::Expression {
+    let not_object = binary(
+        BinaryOperator::StrictNotEqual,
+        value.type_of(),
+        string_expr("object"),
+    );
+    let is_null = binary(
+        BinaryOperator::StrictEqual,
+        Rc::clone(value),
+        ast::Expression::Null,
+    );
+    let not_object_or_null = binary(BinaryOperator::LogicalOr, not_object, is_null);
+    binary(
+        BinaryOperator::LogicalOr,
+        not_object_or_null,
+        is_array(value),
+    )
+}
+
+/// `options && options.{name}`
+pub(super) fn option_expr(name: &str) -> ast::Expression {
+    let options = id_expr("options");
+    options.and(options.prop(name).into())
+}
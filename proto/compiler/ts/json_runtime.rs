@@ -0,0 +1,127 @@
+use std::rc::Rc;
+
+use crate::proto::proto_scope::{root_scope::RootScope, ProtoScope};
+
+use super::{
+    ast::{self, Folder, FolderEntry},
+    ast_builders::id_expr,
+    constants::{JSON_RUNTIME_FILE_NAME, TO_JSON_FUNCTION_NAME},
+    ensure_import::ensure_import,
+    from_json_compiler::push_from_json_helpers,
+    get_relative_import::get_relative_import_string,
+    to_json_compiler::push_to_json_helpers,
+    ts_path::TsPath,
+};
+
+/// Imports helper of `toJSON` or `fromJSON` files from the runtime file
+/// at the root of the output folder
+pub(super) fn import_runtime_function(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    name: &str,
+) -> Rc<ast::Expression> {
+    let function_path = {
+        let mut ts_path = TsPath::default();
+        ts_path.push_file(JSON_RUNTIME_FILE_NAME);
+        ts_path.push_function(name);
+        ts_path
+    };
+    let current_file_path = {
+        let mut ts_path = TsPath::from(
+            root.get_declaration_path(message_scope.id().unwrap())
+                .unwrap(),
+        );
+        ts_path.push_file(&file.name);
+        ts_path
+    };
+    let import_string = get_relative_import_string(&current_file_path, &function_path).unwrap();
+    ensure_import(
+        file,
+        ast::ImportDeclaration::import(
+            vec![ast::ImportSpecifier::new(Rc::new(name.into()))],
+            import_string.into(),
+        ),
+    );
+    id_expr(name)
+}
+
+/// Adds the runtime file with helpers of all `toJSON` and `fromJSON` files,
+/// unless nothing is generated for messages
+pub(super) fn insert_json_runtime(root: &RootScope, folder: &mut Folder) {
+    if !has_file(folder, TO_JSON_FUNCTION_NAME) {
+        return;
+    }
+    let mut file = ast::File::new(JSON_RUNTIME_FILE_NAME.into());
+    push_to_json_helpers(&mut file, root.options.long_type);
+    push_from_json_helpers(&mut file, root.options.long_type);
+    folder.push_file(file);
+}
+
+fn has_file(folder: &Folder, name: &str) -> bool {
+    folder.entries.iter().any(|entry| match entry {
+        FolderEntry::File(file) => file.name.as_ref() == name,
+        FolderEntry::Folder(folder) => has_file(folder, name),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::proto::compiler::ts::{
+        codegen_test::compile,
+        compile_options::{CompileOptions, LongType},
+    };
+
+    const PROTO: &str = r#"
+        syntax = "proto3";
+        message Hello { int64 id = 1; bytes data = 2; }
+        message World { int64 id = 1; }
+    "#;
+
+    #[test]
+    fn emits_json_helpers_once() {
+        let files = compile(PROTO, CompileOptions::default()).unwrap();
+        let runtime = &files["jsonRuntime.ts"];
+        assert!(runtime.contains("export function enumToJSON(names: any, value: number): any {\n"));
+        assert!(runtime.contains(
+            "return util.Long ? util.Long.fromString(String(value), unsigned) : Number(value)\n"
+        ));
+        for (path, content) in &files {
+            assert!(!content.contains("function longFromJSON") || path == "jsonRuntime.ts");
+        }
+        assert!(files["test/World/fromJSON.ts"]
+            .contains("import { longFromJSON } from \"../../jsonRuntime\"\n"));
+    }
+
+    #[test]
+    fn parses_longs_of_configured_type() {
+        let files = compile(
+            PROTO,
+            CompileOptions {
+                long_type: LongType::String,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(files["jsonRuntime.ts"].contains("return String(BigInt(value))\n"));
+        let files = compile(
+            PROTO,
+            CompileOptions {
+                long_type: LongType::BigInt,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(files["jsonRuntime.ts"].contains("return BigInt(value)\n"));
+    }
+
+    #[test]
+    fn skips_runtime_without_messages() {
+        let files = compile(
+            "syntax = \"proto3\"; enum Color { RED = 0; }",
+            CompileOptions::default(),
+        )
+        .unwrap();
+        assert!(!files.contains_key("jsonRuntime.ts"));
+    }
+}
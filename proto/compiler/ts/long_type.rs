@@ -0,0 +1,521 @@
+use std::rc::Rc;
+
+use crate::proto::{package, proto_scope::root_scope::RootScope};
+
+use super::{
+    ast::{
+        self, BinaryOperator, Call, ElementAccess, ExpressionChain, MethodCall, Prop, StatementList,
+    },
+    ast_builders::{
+        assign, binary, call_global, declare_const, for_each, id_expr, import_util, number_expr,
+        object_expr, return_statement, string_expr,
+    },
+    compile_options::LongType,
+    constants::PROTOBUF_MODULE,
+    ensure_function::ensure_function,
+    ensure_import::ensure_import,
+    enum_style::enum_default_expression,
+};
+
+/// Type of 64-bit integer fields, encode input of `Long` accepts numbers as well
+pub(super) fn import_long_type(
+    root: &RootScope,
+    types_file: &mut ast::File,
+    input: bool,
+) -> ast::Type {
+    match root.options.long_type {
+        LongType::Long => {
+            let util_id: Rc<ast::Identifier> = Rc::new("util".into());
+            let util_import = ast::ImportDeclaration::import(
+                vec![ast::ImportSpecifier::new(Rc::clone(&util_id))],
+                PROTOBUF_MODULE.into(),
+            );
+            ensure_import(types_file, util_import);
+            let long_type =
+                ast::Type::TypeReference(vec![util_id, Rc::new(ast::Identifier::new("Long"))]);
+            if input {
+                long_type.or(&ast::Type::Number)
+            } else {
+                long_type
+            }
+        }
+        LongType::BigInt => ast::Type::from_id("bigint"),
+        LongType::String => ast::Type::String,
+        LongType::Number => ast::Type::Number,
+    }
+}
+
+/// Initial value of the field before decoding
+pub(super) fn default_value_expression(
+    root: &RootScope,
+    field_type: &package::Type,
+) -> ast::Expression {
+    match (field_type, field_type.long_wire_type()) {
+        (package::Type::Enum(enum_id), _) => enum_default_expression(root, *enum_id),
+        (_, Some(_)) => long_literal(root, 0),
+        (_, None) => field_type.default_expression(),
+    }
+}
+
+/// Literal of 64-bit integer field value
+pub(super) fn long_literal(root: &RootScope, value: i128) -> ast::Expression {
+    match root.options.long_type {
+        LongType::Long | LongType::Number => (value as f64).into(),
+        // Strings keep precision of values above 2^53
+        LongType::BigInt if value.unsigned_abs() > 1 << 53 => {
+            call_global("BigInt", vec![string_expr(&value.to_string())])
+        }
+        LongType::BigInt => call_global("BigInt", vec![number_expr(value as f64)]),
+        LongType::String => ast::Expression::StringLiteral(value.to_string().into()),
+    }
+}
+
+/// Literal of `[default = ...]` of 64-bit integer field. `Long` values above 2^53
+/// are parsed from strings when `Long` is available, so they keep precision
+pub(super) fn long_default_literal(
+    root: &RootScope,
+    file: &mut ast::File,
+    field_type: &package::Type,
+    value: i128,
+) -> ast::Expression {
+    if root.options.long_type != LongType::Long || value.unsigned_abs() <= 1 << 53 {
+        return long_literal(root, value);
+    }
+    let long_expr: Rc<ast::Expression> = import_util(file).prop("Long").into();
+    let unsigned = match field_type {
+        package::Type::Uint64 | package::Type::Fixed64 => ast::Expression::True,
+        _ => ast::Expression::False,
+    };
+    ast::Expression::conditional(
+        Rc::clone(&long_expr),
+        long_expr
+            .method_call(
+                "fromString",
+                vec![string_expr(&value.to_string()), unsigned.into()],
+            )
+            .into(),
+        long_literal(root, value).into(),
+    )
+}
+
+/// Reads value of basic type, e.g. `r.int64()`, converting 64-bit integers
+/// into the configured representation
+pub(super) fn read_basic_value(
+    root: &RootScope,
+    file: &mut ast::File,
+    reader_expr: &Rc<ast::Expression>,
+    basic: &package::Type,
+) -> ast::Expression {
+    let read = || reader_expr.method_call(&basic.to_string(), vec![]);
+    if basic.long_wire_type().is_none() {
+        return read();
+    }
+    let to_bigint = |file: &mut ast::File| {
+        // `Reader` produces `Long` only when it is available, so bits are read directly
+        let read_bits = match basic {
+            package::Type::Fixed64 | package::Type::Sfixed64 => {
+                ensure_function(file, "readLongFixed", read_long_fixed_function)
+            }
+            _ => ensure_function(file, "readLongVarint", read_long_varint_function),
+        }
+        .call(vec![Rc::clone(reader_expr)]);
+        match basic {
+            package::Type::Int64 | package::Type::Sfixed64 => {
+                id_expr("BigInt").method_call("asIntN", vec![number_expr(64f64), read_bits.into()])
+            }
+            package::Type::Uint64 => {
+                id_expr("BigInt").method_call("asUintN", vec![number_expr(64f64), read_bits.into()])
+            }
+            package::Type::Sint64 => ensure_function(file, "zigZagDecode", zig_zag_decode_function)
+                .call(vec![read_bits.into()]),
+            _ => read_bits,
+        }
+    };
+    match root.options.long_type {
+        LongType::Long => read(),
+        LongType::BigInt => to_bigint(file),
+        LongType::String => call_global("String", vec![to_bigint(file).into()]),
+        LongType::Number => {
+            ensure_function(file, "longToNumber", long_to_number_function).call(vec![read().into()])
+        }
+    }
+}
+
+/// Value passed to the writer method of basic type, e.g. `w.int64(value)`
+pub(super) fn write_basic_value(
+    root: &RootScope,
+    file: &mut ast::File,
+    basic: &package::Type,
+    value: Rc<ast::Expression>,
+) -> Rc<ast::Expression> {
+    if basic.long_wire_type().is_none() {
+        return value;
+    }
+    match root.options.long_type {
+        // `Writer` accepts `Long`, numbers and strings
+        LongType::Long | LongType::Number => value,
+        LongType::BigInt | LongType::String => {
+            ensure_function(file, "longBits", long_bits_function)
+                .call(vec![value])
+                .into()
+        }
+    }
+}
+
+/// Whether map keys read by `read_basic_value` are `Long`, which are converted into hashes
+pub(super) fn is_long_object(root: &RootScope) -> bool {
+    root.options.long_type == LongType::Long
+}
+
+/// `readLongVarint(r)` reads varint as unsigned `bigint`
+fn read_long_varint_function(_file: &mut ast::File) -> ast::FunctionDeclaration {
+    let mut function = ast::FunctionDeclaration::new("readLongVarint");
+    function.add_param(ast::Parameter::new("r", ast::Type::from_id("Reader")));
+    function.returns(ast::Type::from_id("bigint"));
+    let reader_expr = id_expr("r");
+    let res_expr = id_expr("res");
+    let factor_expr = id_expr("factor");
+    let bytes_expr = id_expr("bytes");
+    function.push_statement(declare_const("start", reader_expr.prop("pos")));
+    // Skipping checks bounds of the varint
+    function.push_statement(reader_expr.method_call("skip", vec![]).into());
+    function.push_statement(declare_const(
+        "bytes",
+        reader_expr.prop("buf").into_method_call(
+            "subarray",
+            vec![id_expr("start"), reader_expr.prop("pos").into()],
+        ),
+    ));
+    function.push_statement(
+        ast::VariableDeclarationList::declare_let(
+            Rc::new("res".into()),
+            call_global("BigInt", vec![number_expr(0f64)]),
+        )
+        .into(),
+    );
+    function.push_statement(
+        ast::VariableDeclarationList::declare_let(
+            Rc::new("factor".into()),
+            call_global("BigInt", vec![number_expr(1f64)]),
+        )
+        .into(),
+    );
+    let byte_value = call_global(
+        "BigInt",
+        vec![binary(
+            BinaryOperator::BinaryAnd,
+            bytes_expr.element(id_expr("i")),
+            number_expr(127f64),
+        )
+        .into()],
+    );
+    function.push_statement(for_each(
+        "i",
+        &bytes_expr,
+        vec![
+            assign(
+                Rc::clone(&res_expr),
+                binary(
+                    BinaryOperator::Plus,
+                    Rc::clone(&res_expr),
+                    binary(
+                        BinaryOperator::Multiply,
+                        byte_value,
+                        Rc::clone(&factor_expr),
+                    ),
+                ),
+            ),
+            assign(
+                Rc::clone(&factor_expr),
+                binary(
+                    BinaryOperator::Multiply,
+                    Rc::clone(&factor_expr),
+                    call_global("BigInt", vec![number_expr(128f64)]),
+                ),
+            ),
+        ],
+    ));
+    function.push_statement(return_statement("res".into()));
+    function
+}
+
+/// `readLongFixed(r)` reads fixed 64 bits as unsigned `bigint`
+fn read_long_fixed_function(_file: &mut ast::File) -> ast::FunctionDeclaration {
+    let mut function = ast::FunctionDeclaration::new("readLongFixed");
+    function.add_param(ast::Parameter::new("r", ast::Type::from_id("Reader")));
+    function.returns(ast::Type::from_id("bigint"));
+    let reader_expr = id_expr("r");
+    function.push_statement(declare_const(
+        "low",
+        reader_expr.method_call("fixed32", vec![]),
+    ));
+    let high = call_global(
+        "BigInt",
+        vec![reader_expr.method_call("fixed32", vec![]).into()],
+    );
+    function.push_statement(return_statement(binary(
+        BinaryOperator::Plus,
+        binary(
+            BinaryOperator::Multiply,
+            high,
+            call_global("BigInt", vec![number_expr(4294967296f64)]),
+        ),
+        call_global("BigInt", vec![id_expr("low")]),
+    )));
+    function
+}
+
+/// `zigZagDecode(value)` decodes `sint64` from unsigned `bigint`
+fn zig_zag_decode_function(_file: &mut ast::File) -> ast::FunctionDeclaration {
+    let mut function = ast::FunctionDeclaration::new("zigZagDecode");
+    function.add_param(ast::Parameter::new("value", ast::Type::from_id("bigint")));
+    function.returns(ast::Type::from_id("bigint"));
+    let bits_expr = id_expr("bits");
+    let half_expr = id_expr("half");
+    function.push_statement(declare_const(
+        "bits",
+        id_expr("BigInt").method_call("asUintN", vec![number_expr(64f64), id_expr("value")]),
+    ));
+    function.push_statement(declare_const(
+        "half",
+        binary(
+            BinaryOperator::Divide,
+            Rc::clone(&bits_expr),
+            call_global("BigInt", vec![number_expr(2f64)]),
+        ),
+    ));
+    // Odd values are negative: 1 is -1, 3 is -2 and so on
+    let is_odd = binary(
+        BinaryOperator::StrictEqual,
+        binary(
+            BinaryOperator::BinaryAnd,
+            bits_expr,
+            call_global("BigInt", vec![number_expr(1f64)]),
+        )
+        .into_parentheses(),
+        call_global("BigInt", vec![number_expr(1f64)]),
+    );
+    function.push_statement(return_statement(ast::Expression::conditional(
+        is_odd.into(),
+        binary(
+            BinaryOperator::Minus,
+            call_global("BigInt", vec![number_expr(-1f64)]),
+            Rc::clone(&half_expr),
+        )
+        .into(),
+        half_expr,
+    )));
+    function
+}
+
+/// `longToNumber(value)` converts `Long` returned by `Reader` into number
+fn long_to_number_function(_file: &mut ast::File) -> ast::FunctionDeclaration {
+    let mut function = ast::FunctionDeclaration::new("longToNumber");
+    function.add_param(ast::Parameter::new("value", ast::Type::Any));
+    function.returns(ast::Type::Number);
+    let value_expr = id_expr("value");
+    function.push_statement(return_statement(ast::Expression::conditional(
+        binary(
+            BinaryOperator::StrictEqual,
+            value_expr.type_of(),
+            string_expr("number"),
+        )
+        .into(),
+        Rc::clone(&value_expr),
+        value_expr.method_call("toNumber", vec![]).into(),
+    )));
+    function
+}
+
+/// `longBits(value)` converts `bigint` or decimal string into low and high bits,
+/// which `Writer` accepts as `Long` without precision loss
+fn long_bits_function(_file: &mut ast::File) -> ast::FunctionDeclaration {
+    let mut function = ast::FunctionDeclaration::new("longBits");
+    function.add_param(ast::Parameter::new(
+        "value",
+        ast::Type::from_id("bigint").or(&ast::Type::String),
+    ));
+    function.returns(ast::Type::Any);
+    let bits_expr = id_expr("bits");
+    function.push_statement(declare_const(
+        "bits",
+        id_expr("BigInt").method_call(
+            "asUintN",
+            vec![
+                number_expr(64f64),
+                call_global("BigInt", vec![id_expr("value")]).into(),
+            ],
+        ),
+    ));
+    let low = call_global(
+        "Number",
+        vec![binary(
+            BinaryOperator::BinaryAnd,
+            Rc::clone(&bits_expr),
+            call_global("BigInt", vec![number_expr(4294967295f64)]),
+        )
+        .into()],
+    );
+    let high = call_global(
+        "Number",
+        vec![binary(
+            BinaryOperator::Divide,
+            bits_expr,
+            call_global("BigInt", vec![number_expr(4294967296f64)]),
+        )
+        .into()],
+    );
+    function.push_statement(return_statement(object_expr(vec![
+        ("low", low.into()),
+        ("high", high.into()),
+    ])));
+    function
+}
+
+#[cfg(test)]
+mod test {
+    use crate::proto::compiler::ts::{
+        codegen_test::compile,
+        compile_options::{CompileOptions, LongType},
+    };
+
+    const PROTO: &str = r#"
+        syntax = "proto2";
+        message Hello {
+            optional int64 a = 1 [default = -5];
+            optional uint64 b = 2 [default = 18446744073709551615];
+            optional sint64 c = 3;
+            optional fixed64 d = 4;
+            optional sfixed64 e = 5;
+            map<int64, string> m = 6;
+        }
+    "#;
+
+    /// Generated `types.ts`, `decode.ts` and `encode.ts` of `Hello`
+    fn compile_hello(long_type: LongType) -> [String; 3] {
+        let mut files = compile(
+            PROTO,
+            CompileOptions {
+                long_type,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        ["types", "decode", "encode"]
+            .map(|name| files.remove(&format!("test/Hello/{}.ts", name)).unwrap())
+    }
+
+    fn assert_contains_all(file: &str, parts: &[&str]) {
+        for part in parts {
+            assert!(file.contains(part), "{} is not found in\n{}", part, file);
+        }
+    }
+
+    #[test]
+    fn keeps_protobufjs_long() {
+        let [types, decode, encode] = compile_hello(LongType::Long);
+        assert_contains_all(
+            &types,
+            &[
+                "a?: util.Long | number | null",
+                "a: util.Long",
+                "m: Record<string, string>",
+            ],
+        );
+        assert_contains_all(
+            &decode,
+            &[
+                "a: -5,",
+                "b: util.Long\n      ? util.Long.fromString(\"18446744073709551615\", true)\n      \
+                 : 18446744073709552000,",
+                "message.b = r.uint64()",
+                "message.c = r.sint64()",
+                "message.d = r.fixed64()",
+                "message.e = r.sfixed64()",
+                "k = typeof k === \"object\" ? util.longToHash(k) : k",
+            ],
+        );
+        assert_contains_all(
+            &encode,
+            &[
+                "w.uint32(8).int64(message.a)",
+                "w.uint32(33).fixed64(message.d)",
+                "w.uint32(50).fork().uint32(8).int64(k)",
+            ],
+        );
+    }
+
+    #[test]
+    fn converts_longs_to_numbers() {
+        let [types, decode, encode] = compile_hello(LongType::Number);
+        assert_contains_all(&types, &["a?: number | null", "e?: number"]);
+        assert_contains_all(
+            &decode,
+            &[
+                "b: 18446744073709552000,",
+                "message.a = longToNumber(r.int64())",
+                "message.c = longToNumber(r.sint64())",
+                "message.e = longToNumber(r.sfixed64())",
+                "k = longToNumber(r.int64())",
+            ],
+        );
+        assert!(!decode.contains("longToHash"));
+        assert_contains_all(
+            &encode,
+            &["w.uint32(24).sint64(message.c)", "uint32(8).int64(k)"],
+        );
+    }
+
+    #[test]
+    fn reads_longs_as_bigints() {
+        let [types, decode, encode] = compile_hello(LongType::BigInt);
+        assert_contains_all(&types, &["a?: bigint | null", "d?: bigint"]);
+        assert_contains_all(
+            &decode,
+            &[
+                "a: BigInt(-5),",
+                "b: BigInt(\"18446744073709551615\"),",
+                "message.a = BigInt.asIntN(64, readLongVarint(r))",
+                "message.b = BigInt.asUintN(64, readLongVarint(r))",
+                "message.c = zigZagDecode(readLongVarint(r))",
+                "message.d = readLongFixed(r)",
+                "message.e = BigInt.asIntN(64, readLongFixed(r))",
+                "let k: any = BigInt(0)",
+            ],
+        );
+        assert_contains_all(
+            &encode,
+            &[
+                "w.uint32(16).uint64(longBits(message.b))",
+                "w.uint32(41).sfixed64(longBits(message.e))",
+                "uint32(8).int64(longBits(k))",
+            ],
+        );
+    }
+
+    #[test]
+    fn reads_longs_as_strings() {
+        let [types, decode, encode] = compile_hello(LongType::String);
+        assert_contains_all(&types, &["a?: string | null", "c?: string"]);
+        assert_contains_all(
+            &decode,
+            &[
+                "a: \"-5\",",
+                "b: \"18446744073709551615\",",
+                "message.a = String(BigInt.asIntN(64, readLongVarint(r)))",
+                "message.c = String(zigZagDecode(readLongVarint(r)))",
+                "message.d = String(readLongFixed(r))",
+                "let k: any = \"0\"",
+                "k = String(BigInt.asIntN(64, readLongVarint(r)))",
+            ],
+        );
+        assert_contains_all(
+            &encode,
+            &[
+                "w.uint32(8).int64(longBits(message.a))",
+                "w.uint32(24).sint64(longBits(message.c))",
+                "uint32(8).int64(longBits(k))",
+            ],
+        );
+    }
+}
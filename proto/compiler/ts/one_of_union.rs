@@ -0,0 +1,90 @@
+use std::rc::Rc;
+
+use crate::proto::{
+    package::{Field, OneOfGroup},
+    proto_scope::{message::MessageScope, root_scope::RootScope},
+};
+
+use super::{
+    ast::{self, BinaryOperator, Prop},
+    ast_builders::{binary, is_not_null, object_expr, string_expr},
+    constants::ONE_OF_CASE_PROPERTY,
+};
+
+/// `oneof` group of the field when groups are generated as discriminated unions
+pub(super) fn union_one_of_group<'a>(
+    root: &RootScope,
+    message_declaration: &'a MessageScope,
+    field: &Field,
+) -> Option<&'a OneOfGroup> {
+    if !root.options.oneof_unions {
+        return None;
+    }
+    message_declaration.get_one_of_group(field)
+}
+
+/// Value of the group with the option set: `{ $case: "{name}", {name}: value }`
+pub(super) fn one_of_case_value(name: &str, value: Rc<ast::Expression>) -> ast::Expression {
+    object_expr(vec![
+        (ONE_OF_CASE_PROPERTY, string_expr(name)),
+        (name, value),
+    ])
+}
+
+/// Check whether the option is set in the group and value of the option:
+/// `group != null && group.$case === "{name}"` and `group.{name}`
+pub(super) fn one_of_option_value(
+    group: &Rc<ast::Expression>,
+    name: &str,
+) -> (ast::Expression, Rc<ast::Expression>) {
+    let is_set = binary(
+        BinaryOperator::LogicalAnd,
+        is_not_null(Rc::clone(group)),
+        binary(
+            BinaryOperator::StrictEqual,
+            group.prop(ONE_OF_CASE_PROPERTY),
+            string_expr(name),
+        ),
+    );
+    (is_set, group.prop(name).into())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::proto::compiler::ts::{codegen_test::compile, compile_options::CompileOptions};
+
+    #[test]
+    fn narrows_oneof_unions_by_case() {
+        let files = compile(
+            r#"
+            syntax = "proto3";
+            message Hello {
+                oneof result {
+                    string ok = 1;
+                    int32 error = 2;
+                }
+            }
+        "#,
+            CompileOptions {
+                oneof_unions: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let types = &files["test/Hello/types.ts"];
+        assert!(types.contains(
+            "result?: { $case: \"ok\"; ok: string } | { $case: \"error\"; error: number }\n"
+        ));
+        let decode = &files["test/Hello/decode.ts"];
+        assert!(decode.contains(
+            "message.result = {\n          $case: \"error\",\n          error: r.int32(),\n        }"
+        ));
+        let encode = &files["test/Hello/encode.ts"];
+        assert!(encode.contains("const oneOf = message.result"));
+        assert!(encode.contains(
+            "} else if (oneOf.$case === \"error\") {\n      \
+             if (oneOf.error != null && Object.hasOwnProperty.call(oneOf, \"error\")) {\n        \
+             w.uint32(16).int32(oneOf.error)"
+        ));
+    }
+}
@@ -19,9 +19,9 @@ impl From<&ImportDeclaration> for String {
             imports.push(format!("{{ {} }}", pairs.join(", ")).into());
         }
         format!(
-            "import {} from \"{}\"",
+            "import {} from {}",
             imports.join(", "),
-            import_declaration.string_literal.text
+            to_js_string(&import_declaration.string_literal.text)
         )
     }
 }
@@ -56,10 +56,15 @@ impl From<&EnumDeclaration> for String {
             modifiers,
             name,
             members,
+            comment,
         } = enum_declaration;
+        if let Some(comment) = comment {
+            push_comment(&mut res, comment, "");
+        }
         for modifier in modifiers {
             match modifier {
                 Modifier::Export => res.push_str("export "),
+                Modifier::Const => res.push_str("const "),
             }
         }
         res.push_str("enum ");
@@ -70,15 +75,16 @@ impl From<&EnumDeclaration> for String {
         }
         res.push_str(" {\n");
         for member in members {
+            if let Some(comment) = &member.comment {
+                push_comment(&mut res, comment, "  ");
+            }
             res.push_str("  ");
             res.push_str(&member.name.text);
             if let Some(value) = &member.value {
                 res.push_str(" = ");
                 match value {
                     EnumValue::String(string_literal) => {
-                        res.push_str("\"");
-                        res.push_str(&string_literal.text);
-                        res.push_str("\"");
+                        res.push_str(&to_js_string(&string_literal.text))
                     }
                     EnumValue::Number(numeric_literal) => res.push_str(&numeric_literal.text),
                 }
@@ -103,16 +109,20 @@ mod test_enum_declaration {
                 EnumMember {
                     name: "A".into(),
                     value: Some(EnumValue::String("A".into())),
+                    comment: None,
                 },
                 EnumMember {
                     name: "B".into(),
                     value: None,
+                    comment: None,
                 },
                 EnumMember {
                     name: "C".into(),
                     value: Some(1.into()),
+                    comment: None,
                 },
             ],
+            comment: None,
         };
         let rendered: String = (&decl).into();
         assert_eq!(
@@ -130,6 +140,8 @@ impl From<&Type> for String {
             Type::Number => "number".into(),
             Type::String => "string".into(),
             Type::Null => "null".into(),
+            Type::Undefined => "undefined".into(),
+            Type::Void => "void".into(),
             Type::Never => "never".into(),
             Type::UnionType(UnionType { types }) => {
                 let type_str: Vec<String> = types
@@ -160,6 +172,24 @@ impl From<&Type> for String {
                 .map(|id| id.text.to_string())
                 .collect::<Vec<_>>()
                 .join("."),
+            Type::StringLiteral(str) => to_js_string(str),
+            Type::ObjectLiteral(properties) => {
+                let properties_str: Vec<String> = properties
+                    .iter()
+                    .map(|(name, t)| format!("{}: {}", name, t))
+                    .collect();
+                format!("{{ {} }}", properties_str.join("; "))
+            }
+            Type::Query(id) => format!("typeof {}", id.text),
+            Type::KeyOf(t) => format!("keyof {}", t),
+            Type::Predicate(parameter, t) => format!("{} is {}", parameter.text, t),
+            Type::IndexedAccess(object_type, index_type) => {
+                if object_type.requires_wrap_for_nesting() {
+                    format!("({})[{}]", object_type, index_type)
+                } else {
+                    format!("{}[{}]", object_type, index_type)
+                }
+            }
         }
     }
 }
@@ -192,6 +222,26 @@ mod test_type {
         let rendered: String = (&type_).into();
         assert_eq!(rendered, "boolean[]");
     }
+    #[test]
+    fn it_renders_union_of_object_literals() {
+        let type_ = Type::UnionType(UnionType {
+            types: vec![
+                Type::ObjectLiteral(vec![
+                    ("$case".into(), Type::StringLiteral("ok".into())),
+                    ("ok".into(), Type::from_id("Ok")),
+                ]),
+                Type::ObjectLiteral(vec![
+                    ("$case".into(), Type::StringLiteral("error".into())),
+                    ("error".into(), Type::String),
+                ]),
+            ],
+        });
+        let rendered: String = (&type_).into();
+        assert_eq!(
+            rendered,
+            "{ $case: \"ok\"; ok: Ok } | { $case: \"error\"; error: string }"
+        );
+    }
 }
 
 impl std::fmt::Display for Type {
@@ -201,6 +251,85 @@ impl std::fmt::Display for Type {
     }
 }
 
+impl From<&TypeAliasDeclaration> for String {
+    fn from(type_alias_declaration: &TypeAliasDeclaration) -> Self {
+        let mut res = String::new();
+        let TypeAliasDeclaration {
+            modifiers,
+            name,
+            alias_type,
+            comment,
+        } = type_alias_declaration;
+        if let Some(comment) = comment {
+            push_comment(&mut res, comment, "");
+        }
+        for modifier in modifiers {
+            match modifier {
+                Modifier::Export => res.push_str("export "),
+                Modifier::Const => res.push_str("const "),
+            }
+        }
+        res.push_str("type ");
+        res.push_str(&name.text);
+        res.push_str(" = ");
+        res.push_str(&String::from(alias_type));
+        res
+    }
+}
+
+impl From<&ConstDeclaration> for String {
+    fn from(const_declaration: &ConstDeclaration) -> Self {
+        let mut res = String::new();
+        let ConstDeclaration {
+            modifiers,
+            name,
+            const_type,
+            initializer,
+            comment,
+        } = const_declaration;
+        if let Some(comment) = comment {
+            push_comment(&mut res, comment, "");
+        }
+        for modifier in modifiers {
+            match modifier {
+                Modifier::Export => res.push_str("export "),
+                Modifier::Const => {}
+            }
+        }
+        res.push_str("const ");
+        res.push_str(&name.text);
+        if let Some(const_type) = const_type {
+            res.push_str(": ");
+            res.push_str(&String::from(const_type));
+        }
+        res.push_str(" = ");
+        res.push_str(&String::from(initializer));
+        res
+    }
+}
+
+#[cfg(test)]
+mod test_type_alias_declaration {
+    use super::*;
+    #[test]
+    fn it_renders_as_const_object_type() {
+        let decl = TypeAliasDeclaration {
+            modifiers: vec![Modifier::Export],
+            name: "Color".into(),
+            alias_type: Type::IndexedAccess(
+                Type::Query(Rc::new("Color".into())).into(),
+                Type::KeyOf(Type::Query(Rc::new("Color".into())).into()).into(),
+            ),
+            comment: None,
+        };
+        let rendered: String = (&decl).into();
+        assert_eq!(
+            rendered,
+            "export type Color = (typeof Color)[keyof typeof Color]"
+        );
+    }
+}
+
 impl From<&InterfaceDeclaration> for String {
     fn from(interface_declaration: &InterfaceDeclaration) -> Self {
         let mut res = String::new();
@@ -208,10 +337,15 @@ impl From<&InterfaceDeclaration> for String {
             modifiers,
             name,
             members,
+            comment,
         } = interface_declaration;
+        if let Some(comment) = comment {
+            push_comment(&mut res, comment, "");
+        }
         for modifier in modifiers {
             match modifier {
                 Modifier::Export => res.push_str("export "),
+                Modifier::Const => res.push_str("const "),
             }
         }
         res.push_str("interface ");
@@ -224,6 +358,9 @@ impl From<&InterfaceDeclaration> for String {
         for member in members {
             match member {
                 InterfaceMember::PropertySignature(prop) => {
+                    if let Some(comment) = &prop.comment {
+                        push_comment(&mut res, comment, "  ");
+                    }
                     res.push_str("  ");
                     res.push_str(&prop.name.text);
                     if prop.optional {
@@ -255,6 +392,7 @@ mod test_interface_declaration {
                 PropertySignature::new_optional("B".into(), Type::Number).into(),
                 PropertySignature::new("C".into(), Type::String).into(),
             ],
+            comment: None,
         };
         let rendered: String = (&decl).into();
         assert_eq!(
@@ -265,6 +403,59 @@ mod test_interface_declaration {
     }
 }
 
+impl From<&JsDocComment> for String {
+    fn from(comment: &JsDocComment) -> Self {
+        let text = comment.text.replace("*/", "*\\/");
+        let lines = text.lines().collect::<Vec<_>>();
+        if let [line] = lines[..] {
+            return format!("/** {} */", line);
+        }
+        let mut res = String::from("/**\n");
+        for line in lines {
+            res.push_str(" *");
+            if !line.is_empty() {
+                res.push(' ');
+                res.push_str(line);
+            }
+            res.push('\n');
+        }
+        res.push_str(" */");
+        res
+    }
+}
+
+/// Pushes comment lines with given indentation followed by a line break
+fn push_comment(res: &mut String, comment: &JsDocComment, indent: &str) {
+    let comment_str: String = comment.into();
+    for line in comment_str.lines() {
+        res.push_str(indent);
+        res.push_str(line);
+        res.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod test_js_doc_comment {
+    use super::*;
+    #[test]
+    fn it_works() {
+        let mut prop = PropertySignature::new("A".into(), Type::Boolean);
+        prop.comment = Some(JsDocComment {
+            text: "First line\n\nEnds with */".into(),
+        });
+        let mut decl = InterfaceDeclaration::new_exported("MyInterface".into());
+        decl.comment = Some(JsDocComment {
+            text: "Single line".into(),
+        });
+        decl.members.push(prop.into());
+        let rendered: String = (&decl).into();
+        assert_eq!(
+            rendered,
+            "/** Single line */\nexport interface MyInterface {\n  /**\n   * First line\n   *\n   * Ends with *\\/\n   */\n  A: boolean\n}"
+        );
+    }
+}
+
 impl From<&FunctionDeclaration> for String {
     fn from(f: &FunctionDeclaration) -> Self {
         let mut res = String::new();
@@ -280,6 +471,7 @@ impl From<&FunctionDeclaration> for String {
         for modifier in modifiers {
             match modifier {
                 Modifier::Export => res.push_str("export "),
+                Modifier::Const => res.push_str("const "),
             }
         }
         res.push_str("function ");
@@ -352,7 +544,8 @@ impl From<&BinaryExpression> for String {
         let left_str: String = left.deref().into();
         let right_str: String = right.deref().into();
         assert!(!left_str.contains('\n'));
-        assert!(!right_str.contains('\n'));
+        // Only assigned values, e.g. object literals, may span multiple lines
+        assert!(*operator == BinaryOperator::Assign || !right_str.contains('\n'));
 
         res.push_str(&left_str);
         res.push(' ');
@@ -444,13 +637,22 @@ impl From<&Expression> for String {
                 format!("({})", expr_str)
             }
             Expression::ArrayLiteralExpression(exprs) => {
-                match exprs[..] {
-                    [] => "[]".into(),
-                    _ => unreachable!()
-                }
+                let elements = exprs
+                    .iter()
+                    .map(|expr| String::from(expr.deref()))
+                    .collect::<Vec<_>>();
+                format!("[{}]", elements.join(", "))
             },
             Expression::ObjectLiteralExpression(props) => object_literal_to_string(props),
-            Expression::NewExpression(_) => todo!(),
+            Expression::NewExpression(new_expr) => {
+                let expr_str: String = new_expr.expression.deref().into();
+                let args = new_expr
+                    .arguments
+                    .iter()
+                    .map(|arg| String::from(arg.deref()))
+                    .collect::<Vec<_>>();
+                format!("new {}({})", expr_str, args.join(", "))
+            }
             Expression::NumericLiteral(f64) => f64.to_string(),
             Expression::StringLiteral(str) => to_js_string(str),
             Expression::ElementAccessExpression(element_access_expr) => {
@@ -465,6 +667,10 @@ impl From<&Expression> for String {
                 res.push_str(&inner_str);
                 res
             },
+            Expression::AsConst(expr) => {
+                let expr_str: String = expr.deref().into();
+                format!("{} as const", expr_str)
+            }
             
         }
     }
@@ -486,7 +692,9 @@ fn object_literal_to_string(props: &[Rc<ObjectLiteralMember>]) -> String {
                         res.push(':');
                         res.push(' ');
                         let value_str: String = value.deref().into();
-                        res.push_str(&value_str);
+                        // String literals are escaped to a single line, so every
+                        // line break belongs to the code of a nested value
+                        res.push_str(&value_str.replace('\n', "\n  "));
                         res.push(',');
                         res.push('\n');
                     }
@@ -677,6 +885,8 @@ impl From<&Statement> for String {
         match statement {
             Statement::ImportDeclaration(import_declaration) => (import_declaration.deref()).into(),
             Statement::EnumDeclaration(enum_declaration) => (enum_declaration.deref()).into(),
+            Statement::TypeAliasDeclaration(type_alias) => type_alias.deref().into(),
+            Statement::ConstDeclaration(const_declaration) => const_declaration.deref().into(),
             Statement::InterfaceDeclaration(interface_declaration) => {
                 (interface_declaration.deref()).into()
             }
@@ -698,6 +908,10 @@ impl From<&Statement> for String {
             Statement::While(whl) => whl.deref().into(),
             Statement::Break => "break;".into(),
             Statement::Switch(s) => s.deref().into(),
+            Statement::Throw(expression) => {
+                let expr_str: String = expression.into();
+                format!("throw {}", expr_str)
+            }
         }
     }
 }
@@ -710,7 +924,12 @@ impl From<&File> for String {
             // Addition of vertical space between declarations
             match (statement, last_statement) {
                 (_, None) => {}
-                (Statement::EnumDeclaration(_), _) => res.push_str("\n"),
+                (
+                    Statement::EnumDeclaration(_)
+                    | Statement::TypeAliasDeclaration(_)
+                    | Statement::ConstDeclaration(_),
+                    _,
+                ) => res.push_str("\n"),
                 (Statement::InterfaceDeclaration(_), _) => res.push_str("\n"),
                 (Statement::ImportDeclaration(_), Some(Statement::ImportDeclaration(_))) => {}
                 (Statement::ImportDeclaration(_), _) => res.push_str("\n"),
@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use super::{ast::*, file_to_folder::file_to_folder};
+use super::{ast::*, file_to_folder::file_to_folder, json_runtime::insert_json_runtime};
 use crate::proto::{
     error::ProtoError,
     proto_scope::{root_scope::RootScope, traits::ChildrenScopes, ProtoScope},
@@ -12,11 +12,16 @@ fn scope_to_folder(root: &RootScope, scope: &ProtoScope) -> Result<Folder, Proto
         let child_folder: Folder = match child.deref() {
             ProtoScope::Root(_) => unreachable!(),
             p @ ProtoScope::Package(_) => scope_to_folder(root, p)?,
+            ProtoScope::File(f) if !f.generated => continue,
             f @ ProtoScope::File(_) => file_to_folder(root, f)?,
             ProtoScope::Enum(_) => unreachable!(),
             ProtoScope::Message(_) => unreachable!(),
+            ProtoScope::Service(_) => unreachable!(),
         };
-        folder.push_folder(child_folder);
+        // Packages containing only imported files are skipped
+        if !child_folder.entries.is_empty() {
+            folder.push_folder(child_folder);
+        }
     }
     Ok(folder)
 }
@@ -30,11 +35,16 @@ pub(crate) fn root_scope_to_folder(
         let child_folder = match child.deref() {
             ProtoScope::Root(_) => unreachable!(),
             package_child @ ProtoScope::Package(_) => scope_to_folder(root, package_child)?,
+            ProtoScope::File(f) if !f.generated => continue,
             file_scope @ ProtoScope::File(_) => file_to_folder(root, file_scope)?,
             ProtoScope::Enum(_) => todo!(),
             ProtoScope::Message(_) => todo!(),
+            ProtoScope::Service(_) => todo!(),
         };
-        folder.push_folder(child_folder);
+        if !child_folder.entries.is_empty() {
+            folder.push_folder(child_folder);
+        }
     }
+    insert_json_runtime(root, &mut folder);
     Ok(folder)
 }
@@ -1,3 +1,5 @@
+/// Quotes text as a JS string literal. Line terminators and other control
+/// characters are escaped, so the literal stays on a single line
 pub(super) fn to_js_string(text: &str) -> String {
     let mut res = String::new();
     res.push('"');
@@ -5,10 +7,29 @@ pub(super) fn to_js_string(text: &str) -> String {
         match char {
             '\"' => res.push_str(r#"\""#),
             '\\' => res.push_str(r"\\"),
+            '\n' => res.push_str(r"\n"),
+            '\r' => res.push_str(r"\r"),
+            '\t' => res.push_str(r"\t"),
+            '\u{2028}' | '\u{2029}' => res.push_str(&format!("\\u{:04x}", char as u32)),
+            _ if char.is_control() => res.push_str(&format!("\\x{:02x}", char as u32)),
             _ => res.push(char),
         }
-
     }
     res.push('"');
     res
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use super::to_js_string;
+
+    #[test]
+    fn escapes_quotes_and_control_characters() {
+        assert_eq!(to_js_string(r#"say "hi\""#), r#""say \"hi\\\"""#);
+        assert_eq!(
+            to_js_string("a\nb\r\tc\0d\u{7f}"),
+            r#""a\nb\r\tc\x00d\x7f""#
+        );
+        assert_eq!(to_js_string("\u{2028}\u{2029}é"), r#""\u2028\u2029é""#);
+    }
+}
@@ -0,0 +1,953 @@
+use std::rc::Rc;
+
+use crate::proto::{
+    error::ProtoError,
+    package,
+    proto_scope::{root_scope::RootScope, ProtoScope},
+};
+
+use super::{
+    ast::{
+        self, BinaryOperator, Call, ElementAccess, ExpressionChain, MethodCall, Prop, StatementList,
+    },
+    ast_builders::{
+        assign, binary, call_global, declare_const, declare_typed, for_each, id_expr, if_block,
+        if_else_block, import_util, is_not_null, key_element, new_expr, number_expr, object_keys,
+        return_statement, string_expr, throw_error,
+    },
+    compile_options::LongType,
+    constants::TO_JSON_FUNCTION_NAME,
+    ensure_import::ensure_import,
+    enum_style::{enum_literal, enum_writer, import_enum_names, is_string_enum},
+    json_helpers::{
+        google_protobuf_name, import_json_function, is_empty_message, option_expr,
+        options_interface, SPECIAL_JSON_TYPES,
+    },
+    json_runtime::import_runtime_function,
+    long_type::is_long_object,
+    message_name_to_encode_type_name::message_name_to_encode_type_name,
+    one_of_union::{one_of_option_value, union_one_of_group},
+};
+
+const OPTIONS_INTERFACE_NAME: &str = "ToJSONOptions";
+
+/// Generates `toJSON`, which converts encode input of the message into proto3 JSON value
+pub(super) fn compile_to_json(
+    root: &RootScope,
+    message_folder: &mut ast::Folder,
+    message_scope: &ProtoScope,
+) -> Result<(), ProtoError> {
+    let mut file = ast::File::new(TO_JSON_FUNCTION_NAME.into());
+
+    let message_input_type_id: Rc<ast::Identifier> = ast::Identifier::new(
+        &message_name_to_encode_type_name(message_scope.name().as_ref()),
+    )
+    .into();
+    ensure_import(
+        &mut file,
+        ast::ImportDeclaration::import(
+            vec![ast::ImportSpecifier::new(Rc::clone(&message_input_type_id))],
+            "./types".into(),
+        ),
+    );
+    file.push_statement(options_interface(OPTIONS_INTERFACE_NAME, false).into());
+
+    let mut to_json_func = ast::FunctionDeclaration::new_exported(TO_JSON_FUNCTION_NAME);
+    to_json_func.add_param(ast::Parameter::new(
+        "message",
+        ast::Type::reference(message_input_type_id),
+    ));
+    to_json_func.add_param(ast::Parameter::new_optional(
+        "options",
+        ast::Type::from_id(OPTIONS_INTERFACE_NAME),
+    ));
+    to_json_func.returns(ast::Type::Any);
+
+    let statements = match google_protobuf_name(root, message_scope.id().unwrap()).as_deref() {
+        Some(name) if SPECIAL_JSON_TYPES.contains(&name) => {
+            well_known_to_json(root, message_scope, &mut file, name)?
+        }
+        _ => message_to_json(root, message_scope, &mut file)?,
+    };
+    for statement in statements {
+        to_json_func.push_statement(statement);
+    }
+
+    file.push_statement(to_json_func.into());
+    message_folder.push_file(file);
+    Ok(())
+}
+
+fn message_to_json(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+) -> Result<Vec<ast::Statement>, ProtoError> {
+    let message_declaration = message_scope.get_message_declaration().unwrap();
+    let message_expr = id_expr("message");
+    let json_expr = id_expr("json");
+    let mut statements = vec![declare_typed(
+        "json",
+        ast::Type::Any,
+        ast::Expression::ObjectLiteralExpression(vec![]),
+    )];
+    // Extensions have no JSON names in the message, so they are not converted
+    for field in message_declaration.get_fields() {
+        let one_of_option = union_one_of_group(root, message_declaration, field).map(|one_of| {
+            one_of_option_value(&message_expr.prop(&one_of.name).into(), &field.json_name())
+        });
+        let field_value: Rc<ast::Expression> = match &one_of_option {
+            Some((_, option_value)) => Rc::clone(option_value),
+            None => message_expr.prop(&field.json_name()).into(),
+        };
+        let json_field = json_expr.prop(&field.canonical_json_name());
+        let statement = match &field.field_type {
+            package::Type::Repeated(element_type) => {
+                let element = to_json_value(
+                    root,
+                    message_scope,
+                    file,
+                    element_type,
+                    field_value.element(id_expr("i")).into(),
+                )?;
+                let is_not_empty = binary(
+                    BinaryOperator::LogicalAnd,
+                    is_not_null(Rc::clone(&field_value)),
+                    field_value.prop("length"),
+                );
+                if_block(
+                    is_not_empty,
+                    vec![
+                        declare_typed(
+                            "arr",
+                            ast::Type::array(ast::Type::Any),
+                            ast::Expression::ArrayLiteralExpression(vec![]),
+                        ),
+                        for_each(
+                            "i",
+                            &field_value,
+                            vec![id_expr("arr").method_call("push", vec![element]).into()],
+                        ),
+                        assign(json_field, id_expr("arr")),
+                    ],
+                )
+            }
+            package::Type::Map(key_type, value_type) => {
+                let key: Rc<ast::Expression> = id_expr("keys").element(id_expr("i")).into();
+                let json_key = match key_type.long_wire_type() {
+                    // Decoding keeps `Long` keys as hashes like protobufjs does
+                    Some(_) if is_long_object(root) => {
+                        let unsigned = if matches!(
+                            key_type.as_ref(),
+                            package::Type::Uint64 | package::Type::Fixed64
+                        ) {
+                            ast::Expression::True
+                        } else {
+                            ast::Expression::False
+                        };
+                        import_runtime_function(root, message_scope, file, "longKeyToJSON")
+                            .call(vec![key, unsigned.into()])
+                            .into()
+                    }
+                    _ => key,
+                };
+                let value = to_json_value(
+                    root,
+                    message_scope,
+                    file,
+                    value_type,
+                    key_element(&field_value, "keys", "i").into(),
+                )?;
+                let is_not_empty = binary(
+                    BinaryOperator::LogicalAnd,
+                    is_not_null(Rc::clone(&field_value)),
+                    Rc::new(object_keys(&field_value)).prop("length"),
+                );
+                if_block(
+                    is_not_empty,
+                    vec![
+                        declare_typed(
+                            "obj",
+                            ast::Type::Any,
+                            ast::Expression::ObjectLiteralExpression(vec![]),
+                        ),
+                        declare_const("keys", object_keys(&field_value)),
+                        for_each(
+                            "i",
+                            &id_expr("keys"),
+                            vec![assign(id_expr("obj").element(json_key), value)],
+                        ),
+                        assign(json_field, id_expr("obj")),
+                    ],
+                )
+            }
+            field_type => {
+                let is_set = if let Some((is_option_set, _)) = one_of_option {
+                    is_option_set
+                } else if field.label.is_none() && !message_declaration.is_one_of_field(field) {
+                    // Fields without presence are omitted when they have default values
+                    is_not_default(root, field_type, &field_value)
+                } else {
+                    is_not_null(Rc::clone(&field_value))
+                };
+                let value = to_json_value(
+                    root,
+                    message_scope,
+                    file,
+                    field_type,
+                    Rc::clone(&field_value),
+                )?;
+                if_block(is_set, vec![assign(json_field, value)])
+            }
+        };
+        statements.push(statement.into());
+    }
+    statements.push(return_statement("json".into()));
+    Ok(statements)
+}
+
+fn is_not_default(
+    root: &RootScope,
+    field_type: &package::Type,
+    value: &Rc<ast::Expression>,
+) -> ast::Expression {
+    use package::Type;
+    let not_default: Rc<ast::Expression> = match field_type {
+        Type::Enum(enum_id) if is_string_enum(root) => binary(
+            BinaryOperator::StrictNotEqual,
+            Rc::clone(value),
+            enum_literal(root, *enum_id, 0),
+        )
+        .into(),
+        Type::Message(_) => return is_not_null(Rc::clone(value)),
+        Type::Bool => Rc::clone(value),
+        Type::String => binary(
+            BinaryOperator::StrictNotEqual,
+            Rc::clone(value),
+            string_expr(""),
+        )
+        .into(),
+        Type::Bytes => value.prop("length").into(),
+        _ => binary(
+            BinaryOperator::WeakNotEqual,
+            Rc::clone(value),
+            number_expr(0f64),
+        )
+        .into(),
+    };
+    binary(
+        BinaryOperator::LogicalAnd,
+        is_not_null(Rc::clone(value)),
+        not_default,
+    )
+}
+
+/// Converts single value of the field type, i.e. neither repeated nor map
+fn to_json_value(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    field_type: &package::Type,
+    value: Rc<ast::Expression>,
+) -> Result<Rc<ast::Expression>, ProtoError> {
+    use package::Type;
+    let expr = match field_type {
+        Type::Enum(enum_id)
+            if google_protobuf_name(root, *enum_id).as_deref() == Some("NullValue") =>
+        {
+            ast::Expression::Null
+        }
+        Type::Enum(enum_id) => {
+            let names_expr = import_enum_names(root, message_scope, file, *enum_id);
+            // Names of string unions and unrecognized values are converted back into numbers
+            let number = match enum_writer(root, message_scope, file, field_type) {
+                Some(enum_writer) => enum_writer.apply(value),
+                None => value,
+            };
+            import_runtime_function(root, message_scope, file, "enumToJSON")
+                .call(vec![names_expr, number])
+        }
+        Type::Message(m_id) if is_empty_message(root, *m_id) => {
+            ast::Expression::ObjectLiteralExpression(vec![])
+        }
+        Type::Message(m_id) => {
+            import_json_function(root, message_scope, file, TO_JSON_FUNCTION_NAME, "j", *m_id)
+                .call(vec![value, id_expr("options")])
+        }
+        Type::Double | Type::Float => {
+            import_runtime_function(root, message_scope, file, "floatToJSON").call(vec![value])
+        }
+        Type::Int64 | Type::Uint64 | Type::Sint64 | Type::Fixed64 | Type::Sfixed64 => {
+            call_global("String", vec![value])
+        }
+        Type::Bytes => Rc::new(import_util(file).prop("base64")).method_call(
+            "encode",
+            vec![
+                Rc::clone(&value),
+                number_expr(0f64),
+                value.prop("length").into(),
+            ],
+        ),
+        Type::Repeated(_) | Type::Map(_, _) => unreachable!(),
+        Type::Bool
+        | Type::String
+        | Type::Int32
+        | Type::Uint32
+        | Type::Sint32
+        | Type::Fixed32
+        | Type::Sfixed32 => return Ok(value),
+    };
+    Ok(expr.into())
+}
+
+/// Bodies of well-known types with special JSON forms
+fn well_known_to_json(
+    root: &RootScope,
+    message_scope: &ProtoScope,
+    file: &mut ast::File,
+    name: &str,
+) -> Result<Vec<ast::Statement>, ProtoError> {
+    let message_declaration = message_scope.get_message_declaration().unwrap();
+    let fields = message_declaration.get_fields();
+    let message_expr = id_expr("message");
+    let json_expr = id_expr("json");
+    let statements = match name {
+        "Any" => {
+            let type_url_expr = id_expr("typeUrl");
+            let entry_expr = id_expr("entry");
+            let registry_entry = binary(
+                BinaryOperator::LogicalAnd,
+                option_expr("typeRegistry"),
+                id_expr("options")
+                    .prop("typeRegistry")
+                    .into_element(Rc::clone(&type_url_expr)),
+            );
+            let packed_bytes = binary(
+                BinaryOperator::LogicalOr,
+                message_expr.prop("value"),
+                new_expr("Uint8Array", vec![number_expr(0f64)]),
+            );
+            let value = entry_expr.method_call(
+                "toJSON",
+                vec![
+                    entry_expr
+                        .method_call("decode", vec![packed_bytes.into()])
+                        .into(),
+                    id_expr("options"),
+                ],
+            );
+            let type_name = type_url_expr.method_call(
+                "substring",
+                vec![binary(
+                    BinaryOperator::Plus,
+                    type_url_expr.method_call("lastIndexOf", vec![string_expr("/")]),
+                    number_expr(1f64),
+                )
+                .into()],
+            );
+            let special_types = SPECIAL_JSON_TYPES
+                .iter()
+                .map(|name| string_expr(&format!("google.protobuf.{}", name)))
+                .collect::<Vec<_>>();
+            // Special JSON forms can't be merged with `@type`, so they are nested under `value`
+            let is_special = binary(
+                BinaryOperator::StrictNotEqual,
+                id_expr("specialTypes").method_call("indexOf", vec![id_expr("typeName")]),
+                number_expr(-1f64),
+            );
+            let type_not_found = binary(
+                BinaryOperator::Plus,
+                binary(
+                    BinaryOperator::Plus,
+                    string_expr("Type "),
+                    Rc::clone(&type_url_expr),
+                ),
+                string_expr(" is not found in typeRegistry of options"),
+            );
+            vec![
+                declare_const(
+                    "typeUrl",
+                    binary(
+                        BinaryOperator::LogicalOr,
+                        message_expr.prop("type_url"),
+                        string_expr(""),
+                    ),
+                ),
+                declare_const("entry", registry_entry),
+                if_block(entry_expr.not(), vec![throw_error(type_not_found)]).into(),
+                declare_const("value", value),
+                declare_typed(
+                    "json",
+                    ast::Type::Any,
+                    ast::Expression::ObjectLiteralExpression(vec![]),
+                ),
+                assign(json_expr.element(string_expr("@type")), type_url_expr),
+                declare_const(
+                    "specialTypes",
+                    ast::Expression::ArrayLiteralExpression(special_types),
+                ),
+                declare_const("typeName", type_name),
+                if_else_block(
+                    is_special,
+                    vec![assign(json_expr.prop("value"), id_expr("value"))],
+                    vec![id_expr("Object")
+                        .method_call("assign", vec![Rc::clone(&json_expr), id_expr("value")])
+                        .into()],
+                )
+                .into(),
+                return_statement("json".into()),
+            ]
+        }
+        "Timestamp" | "Duration" => {
+            let seconds_expr = id_expr("seconds");
+            let nanos_expr = id_expr("nanos");
+            let nanos_to_json = import_runtime_function(root, message_scope, file, "nanosToJSON");
+            let mut statements = vec![
+                declare_const(
+                    "seconds",
+                    call_global(
+                        "Number",
+                        vec![binary(
+                            BinaryOperator::LogicalOr,
+                            message_expr.prop("seconds"),
+                            number_expr(0f64),
+                        )
+                        .into()],
+                    ),
+                ),
+                declare_const(
+                    "nanos",
+                    binary(
+                        BinaryOperator::LogicalOr,
+                        message_expr.prop("nanos"),
+                        number_expr(0f64),
+                    ),
+                ),
+            ];
+            if name == "Timestamp" {
+                // Date and time are taken from ISO string, and fraction of second from nanos
+                let date = new_expr(
+                    "Date",
+                    vec![binary(
+                        BinaryOperator::Multiply,
+                        Rc::clone(&seconds_expr),
+                        number_expr(1000f64),
+                    )
+                    .into()],
+                );
+                let date_time = Rc::new(Rc::new(date).method_call("toISOString", vec![]))
+                    .method_call("substring", vec![number_expr(0f64), number_expr(19f64)]);
+                statements.push(return_statement(binary(
+                    BinaryOperator::Plus,
+                    binary(
+                        BinaryOperator::Plus,
+                        date_time,
+                        nanos_to_json.call(vec![nanos_expr]),
+                    ),
+                    string_expr("Z"),
+                )));
+            } else {
+                let is_negative = binary(
+                    BinaryOperator::LogicalOr,
+                    binary(
+                        BinaryOperator::LessThan,
+                        Rc::clone(&seconds_expr),
+                        number_expr(0f64),
+                    ),
+                    binary(
+                        BinaryOperator::LessThan,
+                        Rc::clone(&nanos_expr),
+                        number_expr(0f64),
+                    ),
+                );
+                let abs = |value: Rc<ast::Expression>| {
+                    Rc::new(id_expr("Math").method_call("abs", vec![value]))
+                };
+                let seconds_str = call_global("String", vec![abs(seconds_expr)]);
+                let nanos_str = nanos_to_json.call(vec![abs(nanos_expr)]);
+                statements.push(declare_const(
+                    "sign",
+                    ast::Expression::conditional(
+                        is_negative.into(),
+                        string_expr("-"),
+                        string_expr(""),
+                    ),
+                ));
+                statements.push(return_statement(binary(
+                    BinaryOperator::Plus,
+                    binary(
+                        BinaryOperator::Plus,
+                        binary(BinaryOperator::Plus, id_expr("sign"), seconds_str),
+                        nanos_str,
+                    ),
+                    string_expr("s"),
+                )));
+            }
+            statements
+        }
+        "FieldMask" => {
+            let path_to_json = import_runtime_function(root, message_scope, file, "pathToJSON");
+            let paths_expr = id_expr("paths");
+            vec![
+                declare_const(
+                    "paths",
+                    binary(
+                        BinaryOperator::LogicalOr,
+                        message_expr.prop("paths"),
+                        ast::Expression::ArrayLiteralExpression(vec![]),
+                    ),
+                ),
+                declare_typed(
+                    "json",
+                    ast::Type::array(ast::Type::String),
+                    ast::Expression::ArrayLiteralExpression(vec![]),
+                ),
+                for_each(
+                    "i",
+                    &paths_expr,
+                    vec![json_expr
+                        .method_call(
+                            "push",
+                            vec![path_to_json
+                                .call(vec![paths_expr.element(id_expr("i")).into()])
+                                .into()],
+                        )
+                        .into()],
+                ),
+                return_statement(json_expr.method_call("join", vec![string_expr(",")])),
+            ]
+        }
+        "Struct" => {
+            let value_type = match &fields[0].field_type {
+                package::Type::Map(_, value_type) => value_type,
+                _ => unreachable!(),
+            };
+            let fields_expr = id_expr("fields");
+            let value = to_json_value(
+                root,
+                message_scope,
+                file,
+                value_type,
+                key_element(&fields_expr, "keys", "i").into(),
+            )?;
+            vec![
+                declare_const(
+                    "fields",
+                    binary(
+                        BinaryOperator::LogicalOr,
+                        message_expr.prop("fields"),
+                        ast::Expression::ObjectLiteralExpression(vec![]),
+                    ),
+                ),
+                declare_typed(
+                    "json",
+                    ast::Type::Any,
+                    ast::Expression::ObjectLiteralExpression(vec![]),
+                ),
+                declare_const("keys", object_keys(&fields_expr)),
+                for_each(
+                    "i",
+                    &id_expr("keys"),
+                    vec![assign(key_element(&json_expr, "keys", "i"), value)],
+                ),
+                return_statement("json".into()),
+            ]
+        }
+        "ListValue" => {
+            let element_type = match &fields[0].field_type {
+                package::Type::Repeated(element_type) => element_type,
+                _ => unreachable!(),
+            };
+            let values_expr = id_expr("values");
+            let value = to_json_value(
+                root,
+                message_scope,
+                file,
+                element_type,
+                values_expr.element(id_expr("i")).into(),
+            )?;
+            vec![
+                declare_const(
+                    "values",
+                    binary(
+                        BinaryOperator::LogicalOr,
+                        message_expr.prop("values"),
+                        ast::Expression::ArrayLiteralExpression(vec![]),
+                    ),
+                ),
+                declare_typed(
+                    "json",
+                    ast::Type::array(ast::Type::Any),
+                    ast::Expression::ArrayLiteralExpression(vec![]),
+                ),
+                for_each(
+                    "i",
+                    &values_expr,
+                    vec![json_expr.method_call("push", vec![value]).into()],
+                ),
+                return_statement("json".into()),
+            ]
+        }
+        "Value" => {
+            // Kind of the value is the only set option of the oneof
+            let mut statements = Vec::new();
+            for field in fields {
+                let json_name = field.json_name();
+                let one_of = union_one_of_group(root, message_declaration, field);
+                let owner: Rc<ast::Expression> = match one_of {
+                    Some(one_of) => message_expr.prop(&one_of.name).into(),
+                    None => Rc::clone(&message_expr),
+                };
+                let (is_set, field_value) = match one_of {
+                    Some(_) => one_of_option_value(&owner, &json_name),
+                    None => {
+                        let field_value: Rc<ast::Expression> = owner.prop(&json_name).into();
+                        (is_not_null(Rc::clone(&field_value)), field_value)
+                    }
+                };
+                let value = match &field.field_type {
+                    package::Type::Enum(_) => ast::Expression::Null,
+                    package::Type::Message(m_id) => import_json_function(
+                        root,
+                        message_scope,
+                        file,
+                        TO_JSON_FUNCTION_NAME,
+                        "j",
+                        *m_id,
+                    )
+                    .call(vec![Rc::clone(&field_value), id_expr("options")]),
+                    // Numbers are kept as is, NaN and infinities are not allowed here
+                    _ => owner.prop(&json_name),
+                };
+                statements.push(if_block(is_set, vec![return_statement(value)]).into());
+            }
+            statements.push(return_statement(ast::Expression::Null));
+            statements
+        }
+        // Wrappers are represented by their wrapped values
+        _ => {
+            let value_type = &fields[0].field_type;
+            let default_value = match value_type {
+                package::Type::Int64 | package::Type::Uint64 => string_expr("0"),
+                package::Type::Bool => ast::Expression::False.into(),
+                package::Type::String | package::Type::Bytes => string_expr(""),
+                _ => number_expr(0f64),
+            };
+            let value_expr = id_expr("value");
+            let value = to_json_value(
+                root,
+                message_scope,
+                file,
+                value_type,
+                Rc::clone(&value_expr),
+            )?;
+            vec![
+                declare_const("value", message_expr.prop("value")),
+                return_statement(ast::Expression::conditional(
+                    is_not_null(value_expr).into(),
+                    value,
+                    default_value,
+                )),
+            ]
+        }
+    };
+    Ok(statements)
+}
+
+/// Adds helpers of `toJSON` files to the JSON runtime file
+pub(super) fn push_to_json_helpers(file: &mut ast::File, long_type: LongType) {
+    file.push_statement(enum_to_json_function().into());
+    file.push_statement(float_to_json_function().into());
+    if long_type == LongType::Long {
+        let long_key_to_json = long_key_to_json_function(file);
+        file.push_statement(long_key_to_json.into());
+    }
+    file.push_statement(nanos_to_json_function().into());
+    file.push_statement(path_to_json_function().into());
+}
+
+/// `enumToJSON(names, value)` returns name of the value or the number itself for unknown values
+fn enum_to_json_function() -> ast::FunctionDeclaration {
+    let mut function = ast::FunctionDeclaration::new_exported("enumToJSON");
+    function.add_param(ast::Parameter::new("names", ast::Type::Any));
+    function.add_param(ast::Parameter::new("value", ast::Type::Number));
+    function.returns(ast::Type::Any);
+    let name_expr = id_expr("name");
+    function.push_statement(declare_const(
+        "name",
+        id_expr("names").element(id_expr("value")),
+    ));
+    function.push_statement(return_statement(ast::Expression::conditional(
+        binary(
+            BinaryOperator::StrictEqual,
+            Rc::clone(&name_expr),
+            ast::Expression::Undefined,
+        )
+        .into(),
+        id_expr("value"),
+        name_expr,
+    )));
+    function
+}
+
+/// `longKeyToJSON(key, unsigned)` converts hashes of `Long` map keys into decimal strings,
+/// other keys are decimal strings already
+fn long_key_to_json_function(file: &mut ast::File) -> ast::FunctionDeclaration {
+    let util_expr = import_util(file);
+    let mut function = ast::FunctionDeclaration::new_exported("longKeyToJSON");
+    function.add_param(ast::Parameter::new("key", ast::Type::String));
+    function.add_param(ast::Parameter::new("unsigned", ast::Type::Boolean));
+    function.returns(ast::Type::String);
+    let key_expr = id_expr("key");
+    let is_decimal = Rc::new(new_expr("RegExp", vec![string_expr("^-?[0-9]+$")]))
+        .method_call("test", vec![Rc::clone(&key_expr)]);
+    let is_hash = binary(
+        BinaryOperator::LogicalAnd,
+        binary(
+            BinaryOperator::StrictEqual,
+            key_expr.prop("length"),
+            number_expr(8f64),
+        ),
+        Rc::new(is_decimal).not(),
+    );
+    let decimal = Rc::new(util_expr.prop("LongBits"))
+        .method_call("fromHash", vec![Rc::clone(&key_expr)])
+        .into_method_call("toLong", vec![id_expr("unsigned")])
+        .into_method_call("toString", vec![]);
+    function.push_statement(return_statement(ast::Expression::conditional(
+        is_hash.into(),
+        decimal.into(),
+        key_expr,
+    )));
+    function
+}
+
+/// `floatToJSON(value)` returns strings for NaN and infinities, which are not JSON numbers
+fn float_to_json_function() -> ast::FunctionDeclaration {
+    let mut function = ast::FunctionDeclaration::new_exported("floatToJSON");
+    function.add_param(ast::Parameter::new("value", ast::Type::Number));
+    function.returns(ast::Type::Any);
+    function.push_statement(return_statement(ast::Expression::conditional(
+        call_global("isFinite", vec![id_expr("value")]).into(),
+        id_expr("value"),
+        call_global("String", vec![id_expr("value")]).into(),
+    )));
+    function
+}
+
+/// `nanosToJSON(nanos)` returns fraction of second with 0, 3, 6 or 9 digits
+fn nanos_to_json_function() -> ast::FunctionDeclaration {
+    let mut function = ast::FunctionDeclaration::new_exported("nanosToJSON");
+    function.add_param(ast::Parameter::new("nanos", ast::Type::Number));
+    function.returns(ast::Type::String);
+    let nanos_expr = id_expr("nanos");
+    let fraction_expr = id_expr("fraction");
+    let fraction_length_minus_3 = || -> Rc<ast::Expression> {
+        binary(
+            BinaryOperator::Minus,
+            fraction_expr.prop("length"),
+            number_expr(3f64),
+        )
+        .into()
+    };
+    function.push_statement(
+        if_block(
+            binary(
+                BinaryOperator::StrictEqual,
+                Rc::clone(&nanos_expr),
+                number_expr(0f64),
+            ),
+            vec![return_statement(ast::Expression::StringLiteral("".into()))],
+        )
+        .into(),
+    );
+    function.push_statement(
+        ast::VariableDeclarationList::declare_let(
+            Rc::new("fraction".into()),
+            Rc::new(call_global(
+                "String",
+                vec![binary(BinaryOperator::Plus, nanos_expr, number_expr(1e9)).into()],
+            ))
+            .method_call("substring", vec![number_expr(1f64)]),
+        )
+        .into(),
+    );
+    let mut trim_zeros = ast::WhileStatement::new(
+        binary(
+            BinaryOperator::StrictEqual,
+            fraction_expr.method_call("substring", vec![fraction_length_minus_3()]),
+            string_expr("000"),
+        )
+        .into(),
+    );
+    trim_zeros.push_statement(assign(
+        Rc::clone(&fraction_expr),
+        fraction_expr.method_call(
+            "substring",
+            vec![number_expr(0f64), fraction_length_minus_3()],
+        ),
+    ));
+    function.push_statement(trim_zeros.into());
+    function.push_statement(return_statement(binary(
+        BinaryOperator::Plus,
+        string_expr("."),
+        fraction_expr,
+    )));
+    function
+}
+
+/// `pathToJSON(path)` converts field path of `FieldMask` into lowerCamelCase
+fn path_to_json_function() -> ast::FunctionDeclaration {
+    let mut function = ast::FunctionDeclaration::new_exported("pathToJSON");
+    function.add_param(ast::Parameter::new("path", ast::Type::String));
+    function.returns(ast::Type::String);
+    let res_expr = id_expr("res");
+    let upper_expr = id_expr("upper");
+    let c_expr = id_expr("c");
+    function.push_statement(
+        ast::VariableDeclarationList::declare_let(
+            Rc::new("res".into()),
+            ast::Expression::StringLiteral("".into()),
+        )
+        .into(),
+    );
+    function.push_statement(
+        ast::VariableDeclarationList::declare_let(Rc::new("upper".into()), ast::Expression::False)
+            .into(),
+    );
+    let next_char = ast::Expression::conditional(
+        Rc::clone(&upper_expr),
+        c_expr.method_call("toUpperCase", vec![]).into(),
+        Rc::clone(&c_expr),
+    )
+    .into_parentheses();
+    function.push_statement(for_each(
+        "i",
+        &id_expr("path"),
+        vec![
+            declare_const(
+                "c",
+                id_expr("path").method_call("charAt", vec![id_expr("i")]),
+            ),
+            if_else_block(
+                binary(
+                    BinaryOperator::StrictEqual,
+                    Rc::clone(&c_expr),
+                    string_expr("_"),
+                ),
+                vec![assign(Rc::clone(&upper_expr), ast::Expression::True)],
+                vec![
+                    assign(
+                        Rc::clone(&res_expr),
+                        binary(BinaryOperator::Plus, Rc::clone(&res_expr), next_char),
+                    ),
+                    assign(upper_expr, ast::Expression::False),
+                ],
+            )
+            .into(),
+        ],
+    ));
+    function.push_statement(return_statement("res".into()));
+    function
+}
+
+#[cfg(test)]
+mod test {
+    use crate::proto::compiler::ts::{
+        codegen_test::{compile, compile_files},
+        compile_options::{CompileOptions, LongType},
+    };
+
+    #[test]
+    fn converts_fields_to_proto3_json() {
+        let files = compile(
+            r#"
+            syntax = "proto3";
+            enum Color { RED = 0; GREEN = 1; }
+            message Hello {
+                int64 big_number = 1;
+                Color favorite_color = 2;
+                string user_name = 3 [json_name = "login"];
+                repeated bytes chunks = 4;
+            }
+        "#,
+            CompileOptions::default(),
+        )
+        .unwrap();
+        let to_json = &files["test/Hello/toJSON.ts"];
+        assert!(to_json.contains("json.bigNumber = String(message.big_number)\n"));
+        assert!(to_json.contains(
+            "json.favoriteColor = enumToJSON(Color, colorToNumber(message.favorite_color))\n"
+        ));
+        assert!(to_json.contains("json.login = message.login\n"));
+        assert!(to_json.contains(
+            "arr.push(util.base64.encode(message.chunks[i], 0, message.chunks[i].length))\n"
+        ));
+        assert!(to_json.contains("import { enumToJSON } from \"../../jsonRuntime\"\n"));
+    }
+
+    #[test]
+    fn converts_hashes_of_long_map_keys() {
+        let proto = r#"
+            syntax = "proto3";
+            message Hello { map<int64, string> m = 1; map<fixed64, int32> u = 2; }
+        "#;
+        let files = compile(proto, CompileOptions::default()).unwrap();
+        let to_json = &files["test/Hello/toJSON.ts"];
+        assert!(to_json.contains("obj[longKeyToJSON(keys[i], false)] = message.m[keys[i]]\n"));
+        assert!(to_json.contains("obj[longKeyToJSON(keys[i], true)] = message.u[keys[i]]\n"));
+        assert!(files["jsonRuntime.ts"]
+            .contains("? util.LongBits.fromHash(key).toLong(unsigned).toString()\n"));
+
+        // Other representations of 64-bit integers are decimal keys already
+        let files = compile(
+            proto,
+            CompileOptions {
+                long_type: LongType::BigInt,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(files["test/Hello/toJSON.ts"].contains("obj[keys[i]] = message.m[keys[i]]\n"));
+        assert!(!files["jsonRuntime.ts"].contains("longKeyToJSON"));
+    }
+
+    #[test]
+    fn converts_well_known_types() {
+        let files = compile_files(
+            &[
+                (
+                    "timestamp.proto",
+                    r#"
+                    syntax = "proto3";
+                    package google.protobuf;
+                    message Timestamp { int64 seconds = 1; int32 nanos = 2; }
+                    message UInt64Value { uint64 value = 1; }
+                "#,
+                ),
+                (
+                    "test.proto",
+                    r#"
+                    syntax = "proto3";
+                    import "timestamp.proto";
+                    message Hello { google.protobuf.Timestamp created_at = 1; }
+                "#,
+                ),
+            ],
+            CompileOptions::default(),
+        )
+        .unwrap();
+        let timestamp = &files["google/protobuf/timestamp/Timestamp/toJSON.ts"];
+        assert!(timestamp.contains(
+            "return new Date(seconds * 1000).toISOString().substring(0, 19) + nanosToJSON(nanos) + \"Z\"\n"
+        ));
+        assert!(timestamp.contains("import { nanosToJSON } from \"../../../../jsonRuntime\"\n"));
+        let wrapper = &files["google/protobuf/timestamp/UInt64Value/toJSON.ts"];
+        assert!(wrapper.contains("return value != null ? String(value) : \"0\"\n"));
+        let to_json = &files["test/Hello/toJSON.ts"];
+        assert!(to_json.contains(
+            "import { toJSON as j1 } from \"../../google/protobuf/timestamp/Timestamp/toJSON\"\n"
+        ));
+        assert!(to_json.contains("json.createdAt = j1(message.created_at, options)\n"));
+    }
+}
@@ -9,9 +9,12 @@ use crate::proto::{
 
 use super::{
     ast::Folder,
-    constants::PROTOBUF_MODULE,
+    constants::{EXTENSIONS_PROPERTY, ONE_OF_CASE_PROPERTY},
     ensure_import::ensure_import,
+    enum_helpers::{is_open_enum, unrecognized_type_name},
     get_relative_import::get_relative_import,
+    js_doc_comment::js_doc_comment,
+    long_type::import_long_type,
     message_name_to_encode_type_name::message_name_to_encode_type_name,
     ts_path::{TsPath, TsPathComponent},
 };
@@ -44,33 +47,78 @@ fn insert_encoded_input_interface(
         ProtoScope::Message(m) => m,
         _ => unreachable!(),
     };
+    interface.comment = js_doc_comment(&[&message_declaration.comments]);
+    let types_file_path = get_types_file_path(root, message_scope);
     for entry in &message_declaration.entries {
         match entry {
             MessageEntry::Field(f) => {
                 let property_type =
-                    import_encoding_input_type(&root, &message_scope, types_file, &f.field_type)?
-                        .or(&Type::Null);
-                interface.members.push(
-                    ast::PropertySignature::new_optional(f.json_name(), property_type).into(),
-                );
+                    import_encoding_input_type(&root, &types_file_path, types_file, &f.field_type)?;
+                let mut property = if f.is_required() {
+                    ast::PropertySignature::new(f.json_name(), property_type)
+                } else {
+                    ast::PropertySignature::new_optional(
+                        f.json_name(),
+                        property_type.or(&Type::Null),
+                    )
+                };
+                property.comment = js_doc_comment(&[&f.comments]);
+                interface.members.push(property.into());
+            }
+            MessageEntry::OneOf(one_of) if root.options.oneof_unions => {
+                let mut property = one_of_union_property(one_of, |option| {
+                    import_encoding_input_type(
+                        root,
+                        &types_file_path,
+                        types_file,
+                        &option.field_type,
+                    )
+                })?;
+                property.property_type = property.property_type.or(&Type::Null);
+                interface.members.push(property.into());
             }
             MessageEntry::OneOf(one_of) => {
-                for option in &one_of.options {
+                for option in &one_of.fields {
                     let property_type = import_encoding_input_type(
                         &root,
-                        &message_scope,
+                        &types_file_path,
                         types_file,
                         &option.field_type,
                     )?
                     .or(&Type::Null);
-                    interface.members.push(
-                        ast::PropertySignature::new_optional(option.json_name(), property_type)
-                            .into(),
-                    );
+                    let mut property =
+                        ast::PropertySignature::new_optional(option.json_name(), property_type);
+                    property.comment = js_doc_comment(&[&option.comments, &one_of.comments]);
+                    interface.members.push(property.into());
                 }
             }
         }
     }
+    if let Some(extensions) = root.extensions.get(&message_declaration.id) {
+        let extensions_name = format!("{}Extensions", message_name);
+        let mut extensions_interface = ast::InterfaceDeclaration::new_exported(
+            message_name_to_encode_type_name(&extensions_name).into(),
+        );
+        for extension in extensions {
+            let field = &extension.field;
+            let property_type =
+                import_encoding_input_type(root, &types_file_path, types_file, &field.field_type)?;
+            let mut property = ast::PropertySignature::new_optional(
+                field.tag.to_string().into(),
+                property_type.or(&Type::Null),
+            );
+            property.comment = js_doc_comment(&[&field.comments]);
+            extensions_interface.members.push(property.into());
+        }
+        interface.members.push(
+            ast::PropertySignature::new_optional(
+                EXTENSIONS_PROPERTY.into(),
+                Type::from_id(&extensions_interface.name.text).or(&Type::Null),
+            )
+            .into(),
+        );
+        types_file.ast.statements.push(extensions_interface.into());
+    }
 
     types_file.ast.statements.push(interface.into());
     Ok(())
@@ -86,46 +134,121 @@ fn insert_decode_result_interface(
         ProtoScope::Message(m) => m,
         _ => unreachable!(),
     };
+    interface.comment = js_doc_comment(&[&message_declaration.comments]);
+    let types_file_path = get_types_file_path(root, message_scope);
     for entry in &message_declaration.entries {
         use crate::proto::package::MessageEntry::*;
         match entry {
             Field(f) => {
                 let property_type =
-                    import_decode_result_type(&root, &message_scope, types_file, &f.field_type)?;
-                interface
-                    .members
-                    .push(ast::PropertySignature::new(f.json_name(), property_type).into())
+                    import_decode_result_type(&root, &types_file_path, types_file, &f.field_type)?;
+                let mut property = if f.is_optional() {
+                    ast::PropertySignature::new_optional(f.json_name(), property_type)
+                } else {
+                    ast::PropertySignature::new(f.json_name(), property_type)
+                };
+                property.comment = js_doc_comment(&[&f.comments]);
+                interface.members.push(property.into())
+            }
+            OneOf(one_of) if root.options.oneof_unions => {
+                let property = one_of_union_property(one_of, |option| {
+                    import_decode_result_type(
+                        root,
+                        &types_file_path,
+                        types_file,
+                        &option.field_type,
+                    )
+                })?;
+                interface.members.push(property.into());
             }
             OneOf(one_of) => {
-                for option in &one_of.options {
+                for option in &one_of.fields {
                     let property_type = import_decode_result_type(
                         &root,
-                        &message_scope,
+                        &types_file_path,
                         types_file,
                         &option.field_type,
                     )?
                     .or(&Type::Null);
-                    interface.members.push(
-                        ast::PropertySignature::new_optional(option.json_name(), property_type)
-                            .into(),
-                    );
+                    let mut property =
+                        ast::PropertySignature::new_optional(option.json_name(), property_type);
+                    property.comment = js_doc_comment(&[&option.comments, &one_of.comments]);
+                    interface.members.push(property.into());
                 }
             }
         }
     }
+    if let Some(extensions) = root.extensions.get(&message_declaration.id) {
+        let mut extensions_interface = ast::InterfaceDeclaration::new_exported(
+            format!("{}Extensions", message_scope.name()).into(),
+        );
+        for extension in extensions {
+            let field = &extension.field;
+            let property_type =
+                import_decode_result_type(root, &types_file_path, types_file, &field.field_type)?;
+            let mut property =
+                ast::PropertySignature::new_optional(field.tag.to_string().into(), property_type);
+            property.comment = js_doc_comment(&[&field.comments]);
+            extensions_interface.members.push(property.into());
+        }
+        interface.members.push(
+            ast::PropertySignature::new(
+                EXTENSIONS_PROPERTY.into(),
+                Type::from_id(&extensions_interface.name.text),
+            )
+            .into(),
+        );
+        types_file.ast.statements.push(extensions_interface.into());
+    }
 
     types_file.ast.statements.push(interface.into());
     Ok(())
 }
 
-fn import_encoding_input_type(
+/// `oneof` group as a single optional property, which is a union of options
+/// discriminated by `$case`, e.g. `result?: { $case: "ok"; ok: Ok } | { $case: "error"; error: Err }`
+fn one_of_union_property(
+    one_of: &package::OneOfGroup,
+    mut option_type: impl FnMut(&package::Field) -> Result<Type, ProtoError>,
+) -> Result<ast::PropertySignature, ProtoError> {
+    let mut cases = Vec::new();
+    for option in &one_of.fields {
+        let name = option.json_name();
+        cases.push(Type::ObjectLiteral(vec![
+            (
+                ONE_OF_CASE_PROPERTY.into(),
+                Type::StringLiteral(Rc::clone(&name)),
+            ),
+            (name, option_type(option)?),
+        ]));
+    }
+    let union_type = ast::UnionType::from(cases);
+    let mut property =
+        ast::PropertySignature::new_optional(Rc::clone(&one_of.name), union_type.into());
+    let comments: Vec<_> = std::iter::once(&one_of.comments)
+        .chain(one_of.fields.iter().map(|option| &option.comments))
+        .collect();
+    property.comment = js_doc_comment(&comments);
+    Ok(property)
+}
+
+fn get_types_file_path(root: &RootScope, message_scope: &ProtoScope) -> TsPath {
+    let message_path = root
+        .get_declaration_path(message_scope.id().unwrap())
+        .unwrap();
+    let mut res = TsPath::from(message_path);
+    res.push_file("types");
+    res
+}
+
+pub(super) fn import_encoding_input_type(
     root: &RootScope,
-    message_scope: &ProtoScope,
+    current_file_path: &TsPath,
     types_file: &mut ast::File,
     field_type: &package::Type,
 ) -> Result<Type, ProtoError> {
     match field_type {
-        package::Type::Enum(e_id) => import_enum_type(root, message_scope, types_file, *e_id),
+        package::Type::Enum(e_id) => import_enum_type(root, current_file_path, types_file, *e_id),
         package::Type::Message(m_id) => {
             let imported_message_id = *m_id;
             let imported_name = Rc::from(message_name_to_encode_type_name(
@@ -133,7 +256,7 @@ fn import_encoding_input_type(
             ));
             import_message_type(
                 root,
-                message_scope,
+                current_file_path,
                 types_file,
                 imported_message_id,
                 imported_name,
@@ -141,37 +264,26 @@ fn import_encoding_input_type(
         }
         package::Type::Repeated(field_type) => {
             let element_type =
-                import_encoding_input_type(root, message_scope, types_file, field_type)?;
+                import_encoding_input_type(root, current_file_path, types_file, field_type)?;
             return Ok(Type::array(element_type));
         }
         package::Type::Map(key, value) => {
             let key_type = resolve_key_type(key);
-            let value_type = import_encoding_input_type(root, message_scope, types_file, value)?;
+            let value_type =
+                import_encoding_input_type(root, current_file_path, types_file, value)?;
             return Ok(Type::Record(Box::new(key_type), Box::new(value_type)));
         }
         package::Type::Bool => Ok(Type::Boolean),
         package::Type::Bytes => Ok(Type::reference(ast::Identifier::new("Uint8Array").into())),
         package::Type::Double => Ok(Type::Number),
         package::Type::Fixed32 => Ok(Type::Number),
-        package::Type::Fixed64 => Ok(Type::Number),
         package::Type::Float => Ok(Type::Number),
         package::Type::Int32 => Ok(Type::Number),
-        package::Type::Int64
+        package::Type::Fixed64
+        | package::Type::Int64
         | package::Type::Sfixed64
         | package::Type::Sint64
-        | package::Type::Uint64 => {
-            let util_id: Rc<ast::Identifier> = Rc::new("util".into());
-            let util_import = ast::ImportDeclaration::import(
-                vec![ast::ImportSpecifier::new(Rc::clone(&util_id))],
-                PROTOBUF_MODULE.into(),
-            );
-            ensure_import(types_file, util_import);
-            Ok(Type::TypeReference(vec![
-                Rc::clone(&util_id),
-                Rc::new(ast::Identifier::new("Long")),
-            ])
-            .or(&Type::Number))
-        }
+        | package::Type::Uint64 => Ok(import_long_type(root, types_file, true)),
         package::Type::Sfixed32 => Ok(Type::Number),
         package::Type::Sint32 => Ok(Type::Number),
         package::Type::String => Ok(Type::String),
@@ -189,41 +301,36 @@ fn resolve_key_type(key: &Rc<package::Type>) -> Type {
     }
 }
 
-fn import_decode_result_type(
+pub(super) fn import_decode_result_type(
     root: &RootScope,
-    message_scope: &ProtoScope,
+    current_file_path: &TsPath,
     types_file: &mut ast::File,
     field_type: &package::Type,
 ) -> Result<Type, ProtoError> {
     match field_type {
-        package::Type::Enum(e_id) => import_enum_type(root, message_scope, types_file, *e_id),
+        package::Type::Enum(e_id) => import_enum_type(root, current_file_path, types_file, *e_id),
         package::Type::Message(m_id) => {
             let message_id = *m_id;
             let imported_name = root.get_declaration_name(message_id).unwrap();
-            import_message_type(root, message_scope, types_file, message_id, imported_name)
+            import_message_type(
+                root,
+                current_file_path,
+                types_file,
+                message_id,
+                imported_name,
+            )
         }
         package::Type::Bool => Ok(Type::Boolean),
         package::Type::Bytes => Ok(Type::reference(ast::Identifier::new("Uint8Array").into())),
         package::Type::Double => Ok(Type::Number),
         package::Type::Fixed32 => Ok(Type::Number),
-        package::Type::Fixed64 => Ok(Type::Number),
         package::Type::Float => Ok(Type::Number),
         package::Type::Int32 => Ok(Type::Number),
-        package::Type::Int64
+        package::Type::Fixed64
+        | package::Type::Int64
         | package::Type::Sfixed64
         | package::Type::Sint64
-        | package::Type::Uint64 => {
-            let util_id: Rc<ast::Identifier> = Rc::new("util".into());
-            let util_import = ast::ImportDeclaration::import(
-                vec![ast::ImportSpecifier::new(Rc::clone(&util_id))],
-                PROTOBUF_MODULE.into(),
-            );
-            ensure_import(types_file, util_import);
-            Ok(Type::TypeReference(vec![
-                Rc::clone(&util_id),
-                Rc::new(ast::Identifier::new("Long")),
-            ]))
-        }
+        | package::Type::Uint64 => Ok(import_long_type(root, types_file, false)),
         package::Type::Sfixed32 => Ok(Type::Number),
         package::Type::Sint32 => Ok(Type::Number),
         package::Type::String => Ok(Type::String),
@@ -231,20 +338,20 @@ fn import_decode_result_type(
 
         package::Type::Repeated(field_type) => {
             let element_type =
-                import_decode_result_type(root, message_scope, types_file, field_type)?;
+                import_decode_result_type(root, current_file_path, types_file, field_type)?;
             return Ok(Type::array(element_type));
         }
         package::Type::Map(key, value) => {
             let key_type = resolve_key_type(key);
-            let value_type = import_decode_result_type(root, message_scope, types_file, value)?;
+            let value_type = import_decode_result_type(root, current_file_path, types_file, value)?;
             return Ok(Type::Record(Box::new(key_type), Box::new(value_type)));
         }
     }
 }
 
-fn import_enum_type(
+pub(super) fn import_enum_type(
     root: &RootScope,
-    message_scope: &ProtoScope,
+    current_file_path: &TsPath,
     types_file: &mut ast::File,
     enum_declaration_id: usize,
 ) -> Result<Type, ProtoError> {
@@ -255,27 +362,35 @@ fn import_enum_type(
         res.push(TsPathComponent::Enum(Rc::clone(&enum_name)));
         res
     };
-    let types_file_path = {
-        let message_id = message_scope.id().unwrap();
-        let declaration_proto_path = root.get_declaration_path(message_id).unwrap();
-        let mut res = TsPath::from(declaration_proto_path);
-        res.push(TsPathComponent::File("types".into()));
-        res
-    };
-
-    match get_relative_import(&types_file_path, &enum_ts_path) {
+    match get_relative_import(current_file_path, &enum_ts_path) {
         Some(import_declaration) => {
             ensure_import(types_file, import_declaration);
         }
         _ => {}
     }
+    let enum_type = Type::reference(Rc::new(Rc::clone(&enum_name).into()));
+    if !is_open_enum(root, enum_declaration_id) {
+        return Ok(enum_type);
+    }
 
-    return Ok(Type::reference(Rc::new(enum_name.into())));
+    // Unknown values of open enums are kept as `{ UNRECOGNIZED: number }`
+    let unrecognized_name = unrecognized_type_name(&enum_name);
+    let unrecognized_ts_path = {
+        let enum_proto_path = root.get_declaration_path(enum_declaration_id).unwrap();
+        let mut res = TsPath::from(enum_proto_path);
+        res.push(TsPathComponent::Interface(Rc::clone(&unrecognized_name)));
+        res
+    };
+    if let Some(import_declaration) = get_relative_import(current_file_path, &unrecognized_ts_path)
+    {
+        ensure_import(types_file, import_declaration);
+    }
+    Ok(enum_type.or(&Type::reference(Rc::new(unrecognized_name.into()))))
 }
 
 fn import_message_type(
     root: &RootScope,
-    message_scope: &ProtoScope,
+    current_file_path: &TsPath,
     types_file: &mut ast::File,
     imported_message_id: usize,
     imported_name: Rc<str>,
@@ -286,16 +401,7 @@ fn import_message_type(
         res.push(TsPathComponent::Interface(Rc::clone(&imported_name)));
         res
     };
-    let current_file_path = {
-        let current_message_path = root
-            .get_declaration_path(message_scope.id().unwrap())
-            .unwrap();
-        let mut res = TsPath::from(current_message_path);
-        res.push(TsPathComponent::File("types".into()));
-        res
-    };
-
-    match get_relative_import(&current_file_path, &requested_ts_path) {
+    match get_relative_import(current_file_path, &requested_ts_path) {
         Some(import_declaration) => {
             ensure_import(types_file, import_declaration);
         }
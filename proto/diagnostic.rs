@@ -0,0 +1,359 @@
+use std::{collections::HashMap, rc::Rc};
+
+use super::{
+    error::{Label, ProtoError},
+    lexems::SourceRange,
+};
+
+/// Renders errors the way rustc does: header with error code, location
+/// and source snippet with the offending range underlined
+pub(crate) fn render_diagnostics(error: &ProtoError) -> String {
+    let mut sources = SourceCache::new(|path| std::fs::read_to_string(path).ok());
+    render_with_sources(error, &mut sources)
+}
+
+/// Renders errors like `render_diagnostics`, but without source snippets
+pub(crate) fn render_plain_diagnostics(error: &ProtoError) -> String {
+    render_with_sources(error, &mut SourceCache::new(|_| None))
+}
+
+/// Renders every diagnostic as JSON object on its own line.
+/// Lines and columns are 1-based, range end is inclusive.
+/// Context lines of the message, e.g. `in Hello`, go to `context`
+pub(crate) fn render_json_diagnostics(error: &ProtoError) -> String {
+    match error {
+        ProtoError::Multiple(errors) => errors
+            .iter()
+            .map(render_json_diagnostics)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        error => {
+            let labels = error
+                .labels()
+                .iter()
+                .map(|label| {
+                    format!(
+                        "{{{},\"message\":{}}}",
+                        json_location(Some(&label.range.start.file_path), Some(&label.range)),
+                        json_string(&label.message)
+                    )
+                })
+                .collect::<Vec<_>>();
+            let message = error.message();
+            let (message, context) = split_context(&message);
+            format!(
+                "{{\"severity\":\"{}\",\"code\":{},\"message\":{},\"context\":{},{},\"labels\":[{}],\"help\":[{}]}}",
+                error.severity(),
+                error
+                    .code()
+                    .map(|code| json_string(code.as_str()))
+                    .unwrap_or_else(|| "null".to_string()),
+                json_string(message),
+                context
+                    .map(|context| json_string(&context))
+                    .unwrap_or_else(|| "null".to_string()),
+                json_location(error.path().as_deref(), error.range()),
+                labels.join(","),
+                error
+                    .help()
+                    .iter()
+                    .map(|help| json_string(help))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            )
+        }
+    }
+}
+
+/// Splits indented lines after the first one off the message
+fn split_context(message: &str) -> (&str, Option<String>) {
+    match message.split_once('\n') {
+        Some((message, context)) => (
+            message,
+            Some(
+                context
+                    .lines()
+                    .map(str::trim)
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+        ),
+        None => (message, None),
+    }
+}
+
+fn json_location(file: Option<&str>, range: Option<&SourceRange>) -> String {
+    let file = file.map(json_string).unwrap_or_else(|| "null".to_string());
+    match range {
+        Some(range) => format!(
+            "\"file\":{},\"range\":{{\"start\":{{\"line\":{},\"column\":{}}},\"end\":{{\"line\":{},\"column\":{}}}}}",
+            file,
+            range.start.line,
+            range.start.column,
+            range.end.line,
+            range.end.column
+        ),
+        None => format!("\"file\":{},\"range\":null", file),
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut res = String::with_capacity(value.len() + 2);
+    res.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+struct SourceCache<F: Fn(&str) -> Option<String>> {
+    load: F,
+    files: HashMap<Rc<str>, Option<String>>,
+}
+
+impl<F: Fn(&str) -> Option<String>> SourceCache<F> {
+    fn new(load: F) -> Self {
+        SourceCache {
+            load,
+            files: HashMap::new(),
+        }
+    }
+
+    fn line(&mut self, range: &SourceRange) -> Option<&str> {
+        let path = &range.start.file_path;
+        if !self.files.contains_key(path) {
+            let content = (self.load)(path);
+            self.files.insert(Rc::clone(path), content);
+        }
+        self.files
+            .get(path)
+            .and_then(|content| content.as_ref())
+            .and_then(|content| content.lines().nth(range.start.line.checked_sub(1)?))
+    }
+}
+
+fn render_with_sources<F: Fn(&str) -> Option<String>>(
+    error: &ProtoError,
+    sources: &mut SourceCache<F>,
+) -> String {
+    if let ProtoError::Multiple(errors) = error {
+        return errors
+            .iter()
+            .map(|error| render_with_sources(error, sources))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+    }
+    let mut res = match error.code() {
+        Some(code) => format!("{}[{}]: {}", error.severity(), code, error.message()),
+        None => format!("error: {}", error.message()),
+    };
+    let range = match error.range() {
+        Some(range) => range,
+        None => {
+            if let Some(path) = error.path() {
+                res.push_str(&format!("\n --> {}", path));
+            }
+            return res;
+        }
+    };
+    let gutter_width = std::iter::once(range)
+        .chain(error.labels().iter().map(|label| &label.range))
+        .map(|range| range.start.line.to_string().len())
+        .max()
+        .unwrap_or(1);
+    let gutter = " ".repeat(gutter_width);
+
+    res.push_str(&format!("\n{}--> {:?}", gutter, range.start));
+    if let Some(line) = sources.line(range) {
+        res.push_str(&format!("\n{} |", gutter));
+        res.push_str(&snippet(line, range, '^', "", gutter_width));
+    }
+    for Label {
+        range: label_range,
+        message,
+    } in error.labels()
+    {
+        match sources.line(label_range) {
+            Some(line) => {
+                if label_range.start.file_path != range.start.file_path {
+                    res.push_str(&format!("\n{}::: {:?}", gutter, label_range.start));
+                }
+                res.push_str(&format!("\n{} |", gutter));
+                res.push_str(&snippet(line, label_range, '-', message, gutter_width));
+            }
+            None => res.push_str(&format!(
+                "\n{} = {:?}: {}",
+                gutter, label_range.start, message
+            )),
+        }
+    }
+    for help in error.help() {
+        res.push_str(&format!("\n{} = help: {}", gutter, help));
+    }
+    res
+}
+
+/// Source line with the range underlined by `mark`, ranges spanning
+/// several lines are underlined up to the end of the first one
+fn snippet(
+    line: &str,
+    range: &SourceRange,
+    mark: char,
+    message: &str,
+    gutter_width: usize,
+) -> String {
+    let start = range.start.column.max(1);
+    let line_length = line.chars().count();
+    let end = if range.end.line == range.start.line {
+        range.end.column
+    } else {
+        line_length
+    };
+    let width = (end + 1).saturating_sub(start).max(1);
+    // Tabs are kept, so the underline is aligned with the source line in any terminal
+    let indent: String = line
+        .chars()
+        .take(start - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let underline = mark.to_string().repeat(width);
+    let message = if message.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", message)
+    };
+    format!(
+        "\n{:>gutter_width$} | {}\n{} | {}{}{}",
+        range.start.line,
+        line,
+        " ".repeat(gutter_width),
+        indent,
+        underline,
+        message,
+        gutter_width = gutter_width
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::{io, rc::Rc};
+
+    use crate::proto::{
+        error::{validation_error, ErrorCode, ProtoError},
+        lexems::{Position, SourceRange},
+    };
+
+    use super::{render_json_diagnostics, render_with_sources, SourceCache};
+
+    fn range(file_path: &str, line: usize, start: usize, end: usize) -> SourceRange {
+        let file_path: Rc<str> = file_path.into();
+        SourceRange {
+            start: Position {
+                file_path: Rc::clone(&file_path),
+                line,
+                column: start,
+            },
+            end: Position {
+                file_path,
+                line,
+                column: end,
+            },
+        }
+    }
+
+    #[test]
+    fn renders_snippet_with_labels() {
+        let mut sources = SourceCache::new(|path| match path {
+            "test.proto" => {
+                Some("message Hello {\n  reserved 10;\n  int32 id = 10;\n}\n".to_string())
+            }
+            _ => None,
+        });
+        let error = validation_error(
+            "Field id uses reserved number 10",
+            &range("test.proto", 3, 3, 16),
+        )
+        .with_code(ErrorCode::ReservedNumber)
+        .with_label(&range("test.proto", 2, 3, 14), "reserved here")
+        .with_help("use number which is not reserved");
+        assert_eq!(
+            render_with_sources(&error, &mut sources),
+            "error[E0201]: Field id uses reserved number 10\n \
+             --> test.proto:3:3\n  \
+             |\n\
+             3 |   int32 id = 10;\n  \
+             |   ^^^^^^^^^^^^^^\n  \
+             |\n\
+             2 |   reserved 10;\n  \
+             |   ------------ reserved here\n  \
+             = help: use number which is not reserved"
+        );
+    }
+
+    #[test]
+    fn renders_errors_without_sources() {
+        let error = validation_error("Name \"id\" is reserved", &range("a.proto", 3, 3, 16))
+            .with_code(ErrorCode::ReservedName)
+            .with_label(&range("a.proto", 2, 3, 14), "reserved here");
+        assert_eq!(
+            error.to_string(),
+            "error[E0202]: Name \"id\" is reserved\n \
+             --> a.proto:3:3\n  \
+             = a.proto:2:3: reserved here"
+        );
+        let error = ProtoError::CannotOpenFile {
+            path: "missing.proto".into(),
+            error: io::Error::new(io::ErrorKind::NotFound, "not found"),
+        };
+        assert_eq!(
+            error.to_string(),
+            "error[E0400]: Cannot open file: not found\n --> missing.proto"
+        );
+    }
+
+    #[test]
+    fn renders_json_diagnostics() {
+        let error = validation_error("Name \"id\" is reserved", &range("a.proto", 3, 3, 16))
+            .with_code(ErrorCode::ReservedName)
+            .with_label(&range("a.proto", 2, 3, 14), "reserved here");
+        assert_eq!(
+            render_json_diagnostics(&error),
+            "{\"severity\":\"error\",\"code\":\"E0202\",\"message\":\"Name \\\"id\\\" is reserved\",\"context\":null,\
+             \"file\":\"a.proto\",\"range\":{\"start\":{\"line\":3,\"column\":3},\"end\":{\"line\":3,\"column\":16}},\
+             \"labels\":[{\"file\":\"a.proto\",\"range\":{\"start\":{\"line\":2,\"column\":3},\"end\":{\"line\":2,\"column\":14}},\
+             \"message\":\"reserved here\"}],\"help\":[]}"
+        );
+    }
+
+    #[test]
+    fn renders_json_diagnostics_of_files() {
+        let error = ProtoError::io(
+            "proto/a.proto",
+            io::Error::new(io::ErrorKind::NotFound, "Input does not exist"),
+        );
+        assert_eq!(
+            render_json_diagnostics(&error),
+            "{\"severity\":\"error\",\"code\":\"E0401\",\"message\":\"IO Error: Input does not exist\",\"context\":null,\
+             \"file\":\"proto/a.proto\",\"range\":null,\"labels\":[],\"help\":[]}"
+        );
+    }
+
+    #[test]
+    fn renders_json_context_separately() {
+        let error = ProtoError::new("Cannot resolve Persn\n  in Hello");
+        assert_eq!(
+            render_json_diagnostics(&error),
+            "{\"severity\":\"error\",\"code\":\"E0000\",\"message\":\"Cannot resolve Persn\",\
+             \"context\":\"in Hello\",\"file\":null,\"range\":null,\"labels\":[],\"help\":[]}"
+        );
+    }
+}
@@ -1,87 +1,341 @@
 use std::{
     fmt::{Display, Formatter},
     io,
+    rc::Rc,
 };
 
-use super::lexems::{self};
+use super::{
+    diagnostic::render_plain_diagnostics,
+    lexems::{self, SourceRange},
+};
+
+/// Stable identifier of an error kind, printed as `error[E0201]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ErrorCode {
+    /// Error without more specific kind
+    Other,
+    UnknownCharacter,
+    InvalidIntLiteral,
+    InvalidFloatLiteral,
+    InvalidStringLiteral,
+    Syntax,
+    /// Declaration is not allowed in its place or with its syntax
+    InvalidDeclaration,
+    ReservedNumber,
+    ReservedName,
+    /// Field number is inside of `extensions` range of its message
+    ExtensionRangeConflict,
+    /// Extension number is outside of `extensions` ranges of extended message
+    ExtensionNotInRange,
+    DuplicateExtensionNumber,
+    InvalidDefault,
+    InvalidFeature,
+    DuplicateFieldNumber,
+    DuplicateFieldName,
+    /// Field number is out of allowed range or in the range reserved by protobuf
+    InvalidFieldNumber,
+    /// Enum value is used twice without `allow_alias`
+    DuplicateEnumValue,
+    /// First value of open enum is not zero
+    InvalidEnumZeroValue,
+    /// Repeated or map field inside of oneof
+    InvalidOneofField,
+    /// Message, enum or service name is already used in the same package or message
+    DuplicateDeclaration,
+    UnresolvedType,
+    UnresolvedImport,
+    /// Type is resolved, but is of wrong kind, e.g. enum as rpc input
+    InvalidTypeKind,
+    ImportCycle,
+    /// Type is declared in a loaded file, which is not imported where it is used
+    MissingImport,
+    UnusedImport,
+    /// File is imported twice by the same file
+    DuplicateImport,
+    CannotOpenFile,
+    /// Reading inputs or writing generated files failed
+    Io,
+}
+
+/// Warnings are reported, but do not stop compilation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        use ErrorCode::*;
+        match self {
+            Other => "E0000",
+            UnknownCharacter => "E0001",
+            InvalidIntLiteral => "E0002",
+            InvalidFloatLiteral => "E0003",
+            InvalidStringLiteral => "E0004",
+            Syntax => "E0100",
+            InvalidDeclaration => "E0200",
+            ReservedNumber => "E0201",
+            ReservedName => "E0202",
+            ExtensionRangeConflict => "E0203",
+            ExtensionNotInRange => "E0204",
+            DuplicateExtensionNumber => "E0205",
+            InvalidDefault => "E0206",
+            InvalidFeature => "E0207",
+            DuplicateFieldNumber => "E0208",
+            DuplicateFieldName => "E0209",
+            InvalidFieldNumber => "E0210",
+            DuplicateEnumValue => "E0211",
+            InvalidEnumZeroValue => "E0212",
+            InvalidOneofField => "E0213",
+            DuplicateDeclaration => "E0214",
+            UnresolvedType => "E0300",
+            UnresolvedImport => "E0301",
+            InvalidTypeKind => "E0302",
+            ImportCycle => "E0303",
+            MissingImport => "E0304",
+            UnusedImport => "E0305",
+            DuplicateImport => "E0306",
+            CannotOpenFile => "E0400",
+            Io => "E0401",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            ErrorCode::UnusedImport => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Secondary location of an error, e.g. where a conflicting number is reserved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Label {
+    pub range: SourceRange,
+    pub message: String,
+}
 
 #[derive(Debug)]
 pub(crate) enum ProtoError {
-    Default(String),
-    CannotOpenFile(io::Error),
-    IOError(io::Error),
+    /// Error without source location, e.g. in built-in declarations
+    Default {
+        code: ErrorCode,
+        message: String,
+    },
+    CannotOpenFile {
+        path: Rc<str>,
+        error: io::Error,
+    },
+    IOError {
+        path: Rc<str>,
+        error: io::Error,
+    },
     UnknownCharacter {
-        file_path: String,
-        line: usize,
-        column: usize,
         char: char,
+        range: SourceRange,
     },
     InvalidIntLiteral {
         literal: String,
-        file_path: String,
-        line: usize,
-        start_column: usize,
-        end_column: usize,
+        range: SourceRange,
+    },
+    InvalidFloatLiteral {
+        literal: String,
+        range: SourceRange,
+    },
+    InvalidStringLiteral {
+        message: String,
+        range: SourceRange,
     },
     SyntaxError {
-        file_path: String,
-        line: usize,
-        column: usize,
         message: String,
+        range: SourceRange,
+    },
+    ValidationError {
+        code: ErrorCode,
+        message: String,
+        range: SourceRange,
+        labels: Vec<Label>,
+        /// Hints on how to fix the error, e.g. names similar to unresolved one
+        help: Vec<String>,
     },
+    /// Several errors collected before giving up, e.g. from different statements or files
+    Multiple(Vec<ProtoError>),
 }
 
 impl ProtoError {
     pub fn new(value: &str) -> Self {
-        ProtoError::Default(value.to_string())
+        ProtoError::Default {
+            code: ErrorCode::Other,
+            message: value.to_string(),
+        }
     }
-}
 
-impl Display for ProtoError {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    /// Error of reading or writing the file or folder at `path`
+    pub(crate) fn io<P: AsRef<std::path::Path>>(path: P, error: io::Error) -> Self {
+        ProtoError::IOError {
+            path: path.as_ref().display().to_string().into(),
+            error,
+        }
+    }
+
+    /// Returns `None` for no errors, the error itself for single one
+    /// and `Multiple` with flattened errors otherwise
+    pub(crate) fn from_errors(errors: Vec<ProtoError>) -> Option<Self> {
+        let mut errors = errors
+            .into_iter()
+            .flat_map(|error| match error {
+                ProtoError::Multiple(errors) => errors,
+                error => vec![error],
+            })
+            .collect::<Vec<_>>();
+        match errors.len() {
+            0 => None,
+            1 => errors.pop(),
+            _ => Some(ProtoError::Multiple(errors)),
+        }
+    }
+
+    /// Replaces code of validation error or error without location
+    pub(crate) fn with_code(mut self, new_code: ErrorCode) -> Self {
+        if let ProtoError::ValidationError { code, .. } | ProtoError::Default { code, .. } =
+            &mut self
+        {
+            *code = new_code;
+        }
+        self
+    }
+
+    /// Adds secondary location to validation error
+    pub(crate) fn with_label<T: Into<String>>(mut self, range: &SourceRange, message: T) -> Self {
+        if let ProtoError::ValidationError { labels, .. } = &mut self {
+            labels.push(Label {
+                range: range.clone(),
+                message: message.into(),
+            });
+        }
+        self
+    }
+
+    /// Adds hint to validation error
+    pub(crate) fn with_help<T: Into<String>>(mut self, message: T) -> Self {
+        if let ProtoError::ValidationError { help, .. } = &mut self {
+            help.push(message.into());
+        }
+        self
+    }
+
+    pub(crate) fn code(&self) -> Option<ErrorCode> {
+        use ProtoError::*;
+        match self {
+            Multiple(_) => None,
+            CannotOpenFile { .. } => Some(ErrorCode::CannotOpenFile),
+            IOError { .. } => Some(ErrorCode::Io),
+            UnknownCharacter { .. } => Some(ErrorCode::UnknownCharacter),
+            InvalidIntLiteral { .. } => Some(ErrorCode::InvalidIntLiteral),
+            InvalidFloatLiteral { .. } => Some(ErrorCode::InvalidFloatLiteral),
+            InvalidStringLiteral { .. } => Some(ErrorCode::InvalidStringLiteral),
+            SyntaxError { .. } => Some(ErrorCode::Syntax),
+            Default { code, .. } | ValidationError { code, .. } => Some(*code),
+        }
+    }
+
+    pub(crate) fn severity(&self) -> Severity {
+        self.code()
+            .map(|code| code.severity())
+            .unwrap_or(Severity::Error)
+    }
+
+    pub(crate) fn range(&self) -> Option<&SourceRange> {
+        use ProtoError::*;
+        match self {
+            Default { .. } | CannotOpenFile { .. } | IOError { .. } | Multiple(_) => None,
+            UnknownCharacter { range, .. }
+            | InvalidIntLiteral { range, .. }
+            | InvalidFloatLiteral { range, .. }
+            | InvalidStringLiteral { range, .. }
+            | SyntaxError { range, .. }
+            | ValidationError { range, .. } => Some(range),
+        }
+    }
+
+    /// File the error is about, the file of the range for located errors
+    pub(crate) fn path(&self) -> Option<Rc<str>> {
+        match self {
+            ProtoError::CannotOpenFile { path, .. } | ProtoError::IOError { path, .. } => {
+                Some(Rc::clone(path))
+            }
+            error => error.range().map(|range| Rc::clone(&range.start.file_path)),
+        }
+    }
+
+    pub(crate) fn labels(&self) -> &[Label] {
+        match self {
+            ProtoError::ValidationError { labels, .. } => labels,
+            _ => &[],
+        }
+    }
+
+    pub(crate) fn help(&self) -> &[String] {
+        match self {
+            ProtoError::ValidationError { help, .. } => help,
+            _ => &[],
+        }
+    }
+
+    /// Error text without location
+    pub(crate) fn message(&self) -> String {
         use ProtoError::*;
         match self {
-            Default(s) => f.write_str(s.as_str()),
-            CannotOpenFile(err) => write!(f, "Cannot open file: {}", err),
-            IOError(err) => write!(f, "IO Error: {}", err),
-            UnknownCharacter {
-                file_path,
-                line,
-                column,
-                char,
-            } => write!(
-                f,
-                "Unknown character at {}:{}:{}: {}",
-                file_path, line, column, char
-            ),
-            SyntaxError {
-                file_path,
-                line,
-                column,
-                message,
-            } => write!(
-                f,
-                "{}:{}:{}: SyntaxError: {}",
-                file_path, line, column, message
-            ),
-            InvalidIntLiteral {
-                file_path,
-                literal,
-                end_column,
-                line,
-                start_column,
-            } => {
-                write!(f, "Invalid integer literal: \"{}\"", literal)?;
-                write!(
-                    f,
-                    " at {}:{}:{} to {}",
-                    file_path, line, start_column, end_column
-                )
+            Default { message, .. } => message.clone(),
+            CannotOpenFile { error, .. } => format!("Cannot open file: {}", error),
+            IOError { error, .. } => format!("IO Error: {}", error),
+            UnknownCharacter { char, .. } => format!("Unknown character {}", char),
+            InvalidIntLiteral { literal, .. } => {
+                format!("Invalid integer literal: \"{}\"", literal)
             }
+            InvalidFloatLiteral { literal, .. } => {
+                format!("Invalid float literal: \"{}\"", literal)
+            }
+            InvalidStringLiteral { message, .. }
+            | SyntaxError { message, .. }
+            | ValidationError { message, .. } => message.clone(),
+            Multiple(errors) => errors
+                .iter()
+                .map(|error| error.message())
+                .collect::<Vec<_>>()
+                .join("\n"),
         }
     }
 }
 
+impl Display for ProtoError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(&render_plain_diagnostics(self))
+    }
+}
+
 impl From<ProtoError> for std::io::Error {
     fn from(err: ProtoError) -> std::io::Error {
         std::io::Error::new(std::io::ErrorKind::Other, format!("{}", err))
@@ -93,9 +347,45 @@ pub(super) fn syntax_error<T: Into<String>>(
     lexem: &lexems::LocatedLexem,
 ) -> ProtoError {
     ProtoError::SyntaxError {
-        file_path: lexem.range.start.file_path.to_string(),
-        line: lexem.range.start.line,
-        column: lexem.range.start.column,
-        message: format!("{}, but {} occurred", message.into(), lexem.lexem).into(),
+        message: message.into(),
+        range: lexem.range.clone(),
+    }
+}
+
+/// Syntax error at `lexem`, which is not the `expected` one,
+/// e.g. `Expected ;, but } occurred`
+pub(super) fn unexpected_lexem_error<T: Display>(
+    expected: T,
+    lexem: &lexems::LocatedLexem,
+) -> ProtoError {
+    syntax_error(
+        format!("Expected {}, but {} occurred", expected, lexem.lexem),
+        lexem,
+    )
+}
+
+pub(super) fn validation_error<T: Into<String>>(message: T, range: &SourceRange) -> ProtoError {
+    ProtoError::ValidationError {
+        code: ErrorCode::InvalidDeclaration,
+        message: message.into(),
+        range: range.clone(),
+        labels: Vec::new(),
+        help: Vec::new(),
+    }
+}
+
+/// Validation error at `range`, or plain error for declarations without location,
+/// i.e. built-in ones
+pub(super) fn located_error<T: Into<String>>(
+    code: ErrorCode,
+    message: T,
+    range: Option<&SourceRange>,
+) -> ProtoError {
+    match range {
+        Some(range) => validation_error(message, range).with_code(code),
+        None => ProtoError::Default {
+            code,
+            message: message.into(),
+        },
     }
 }
@@ -0,0 +1,268 @@
+use std::ops::Deref;
+
+use super::{
+    error::{validation_error, ErrorCode, ProtoError},
+    lexems::SourceRange,
+    package::{
+        find_option, Declaration, EnumDeclaration, FieldDeclaration, FieldLabel,
+        FieldTypeReference, MessageDeclaration, MessageDeclarationEntry, OptionValue, ProtoFile,
+        ProtoOption, ProtoVersion,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FieldPresence {
+    Explicit,
+    Implicit,
+    /// Same as `required` label of proto2
+    LegacyRequired,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EnumType {
+    Open,
+    /// Unknown values are not stored in enum fields
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RepeatedFieldEncoding {
+    Packed,
+    Expanded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MessageEncoding {
+    LengthPrefixed,
+    /// Message is framed by start/end group tags like proto2 groups
+    Delimited,
+}
+
+/// Names of `features.*` options, including ones which do not affect generated code
+const KNOWN_FEATURES: [&str; 6] = [
+    "field_presence",
+    "enum_type",
+    "repeated_field_encoding",
+    "message_encoding",
+    "utf8_validation",
+    "json_format",
+];
+
+/// Resolved values of `features.*` options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FeatureSet {
+    pub field_presence: FieldPresence,
+    pub enum_type: EnumType,
+    pub repeated_field_encoding: RepeatedFieldEncoding,
+    pub message_encoding: MessageEncoding,
+}
+
+impl FeatureSet {
+    /// Features which `proto2`, `proto3` or edition files start with
+    pub fn defaults(version: ProtoVersion) -> Self {
+        match version {
+            ProtoVersion::Proto2 => FeatureSet {
+                field_presence: FieldPresence::Explicit,
+                enum_type: EnumType::Closed,
+                repeated_field_encoding: RepeatedFieldEncoding::Expanded,
+                message_encoding: MessageEncoding::LengthPrefixed,
+            },
+            ProtoVersion::Proto3 => FeatureSet {
+                field_presence: FieldPresence::Implicit,
+                enum_type: EnumType::Open,
+                repeated_field_encoding: RepeatedFieldEncoding::Packed,
+                message_encoding: MessageEncoding::LengthPrefixed,
+            },
+            ProtoVersion::Edition2023 => FeatureSet {
+                field_presence: FieldPresence::Explicit,
+                enum_type: EnumType::Open,
+                repeated_field_encoding: RepeatedFieldEncoding::Packed,
+                message_encoding: MessageEncoding::LengthPrefixed,
+            },
+        }
+    }
+
+    /// Applies `features.*` overrides from `options` on top of inherited features
+    fn with_options(self, options: &[ProtoOption]) -> Result<Self, String> {
+        let mut res = self;
+        for (name, value) in options.iter().filter_map(feature_option) {
+            if !KNOWN_FEATURES.contains(&name) {
+                return Err(format!("Unknown feature {}", name));
+            }
+            let value = match value {
+                OptionValue::Identifier(value) => value.deref(),
+                _ => return Err(format!("Invalid value {} of feature {}", value, name)),
+            };
+            match (name, value) {
+                ("field_presence", "EXPLICIT") => res.field_presence = FieldPresence::Explicit,
+                ("field_presence", "IMPLICIT") => res.field_presence = FieldPresence::Implicit,
+                ("field_presence", "LEGACY_REQUIRED") => {
+                    res.field_presence = FieldPresence::LegacyRequired
+                }
+                ("enum_type", "OPEN") => res.enum_type = EnumType::Open,
+                ("enum_type", "CLOSED") => res.enum_type = EnumType::Closed,
+                ("repeated_field_encoding", "PACKED") => {
+                    res.repeated_field_encoding = RepeatedFieldEncoding::Packed
+                }
+                ("repeated_field_encoding", "EXPANDED") => {
+                    res.repeated_field_encoding = RepeatedFieldEncoding::Expanded
+                }
+                ("message_encoding", "LENGTH_PREFIXED") => {
+                    res.message_encoding = MessageEncoding::LengthPrefixed
+                }
+                ("message_encoding", "DELIMITED") => {
+                    res.message_encoding = MessageEncoding::Delimited
+                }
+                // Features which do not affect generated code
+                ("utf8_validation" | "json_format", _) => {}
+                _ => return Err(format!("Invalid value {} of feature {}", value, name)),
+            }
+        }
+        Ok(res)
+    }
+}
+
+/// Splits `features.name = value` option into feature name and value
+fn feature_option(option: &ProtoOption) -> Option<(&str, &OptionValue)> {
+    match &option.name.parts[..] {
+        [features, name] if !features.is_extension && features.name.deref() == "features" => {
+            Some((name.name.deref(), &option.value))
+        }
+        _ => None,
+    }
+}
+
+/// Resolves features of every declaration in the file and stores results
+/// as labels, `packed`, `delimited` and `closed` flags used by the compiler
+pub(crate) fn resolve_features(file: &mut ProtoFile) -> Result<(), ProtoError> {
+    let version = file.version;
+    let file_features = scope_features(FeatureSet::defaults(version), version, &file.options)
+        .map_err(|message| in_scope_error(message, &file.name, None))?;
+    for declaration in &mut file.declarations {
+        resolve_declaration(declaration, version, file_features)?;
+    }
+    Ok(())
+}
+
+fn resolve_declaration(
+    declaration: &mut Declaration,
+    version: ProtoVersion,
+    parent: FeatureSet,
+) -> Result<(), ProtoError> {
+    match declaration {
+        Declaration::Message(message) => resolve_message(message, version, parent),
+        Declaration::Enum(enum_declaration) => resolve_enum(enum_declaration, version, parent),
+        Declaration::Extend(extend) => {
+            for field in &mut extend.fields {
+                resolve_field(field, version, parent, false)?;
+            }
+            Ok(())
+        }
+        Declaration::Service(_) => Ok(()),
+    }
+}
+
+fn resolve_message(
+    message: &mut MessageDeclaration,
+    version: ProtoVersion,
+    parent: FeatureSet,
+) -> Result<(), ProtoError> {
+    let features = scope_features(parent, version, &message.options).map_err(|message_text| {
+        in_scope_error(message_text, &message.name, message.range.as_ref())
+    })?;
+    for entry in &mut message.entries {
+        match entry {
+            MessageDeclarationEntry::Field(field) => {
+                resolve_field(field, version, features, false)?
+            }
+            MessageDeclarationEntry::OneOf(one_of) => {
+                let one_of_features =
+                    scope_features(features, version, &one_of.options).map_err(|message_text| {
+                        in_scope_error(message_text, &one_of.name, message.range.as_ref())
+                    })?;
+                for field in &mut one_of.fields {
+                    resolve_field(field, version, one_of_features, true)?;
+                }
+            }
+            MessageDeclarationEntry::Declaration(declaration) => {
+                resolve_declaration(declaration, version, features)?
+            }
+        }
+    }
+    Ok(())
+}
+
+fn resolve_enum(
+    enum_declaration: &mut EnumDeclaration,
+    version: ProtoVersion,
+    parent: FeatureSet,
+) -> Result<(), ProtoError> {
+    let features =
+        scope_features(parent, version, &enum_declaration.options).map_err(|message| {
+            in_scope_error(
+                message,
+                &enum_declaration.name,
+                enum_declaration.range.as_ref(),
+            )
+        })?;
+    enum_declaration.closed = features.enum_type == EnumType::Closed;
+    Ok(())
+}
+
+fn resolve_field(
+    field: &mut FieldDeclaration,
+    version: ProtoVersion,
+    parent: FeatureSet,
+    in_one_of: bool,
+) -> Result<(), ProtoError> {
+    let features = scope_features(parent, version, &field.options)
+        .map_err(|message| in_scope_error(message, &field.name, field.range.as_ref()))?;
+    let is_repeated = matches!(field.field_type_ref, FieldTypeReference::Repeated(_));
+    let is_map = matches!(field.field_type_ref, FieldTypeReference::Map(_, _));
+    field.packed = match (version, find_option(&field.options, "packed")) {
+        (ProtoVersion::Edition2023, _) => {
+            features.repeated_field_encoding == RepeatedFieldEncoding::Packed
+        }
+        (_, Some(OptionValue::Bool(packed))) => *packed,
+        _ => features.repeated_field_encoding == RepeatedFieldEncoding::Packed,
+    };
+    if version != ProtoVersion::Edition2023 {
+        return Ok(());
+    }
+    if !is_map && features.message_encoding == MessageEncoding::Delimited {
+        // Applies only to message typed fields, which is known after type resolution
+        field.delimited = true;
+    }
+    if !is_repeated && !is_map && !in_one_of {
+        field.label = match features.field_presence {
+            FieldPresence::Explicit => Some(FieldLabel::Optional),
+            FieldPresence::Implicit => None,
+            FieldPresence::LegacyRequired => Some(FieldLabel::Required),
+        };
+    }
+    Ok(())
+}
+
+/// Features of declaration with `options`, inheriting `parent` ones
+fn scope_features(
+    parent: FeatureSet,
+    version: ProtoVersion,
+    options: &[ProtoOption],
+) -> Result<FeatureSet, String> {
+    let has_features = options
+        .iter()
+        .any(|option| feature_option(option).is_some());
+    if has_features && version != ProtoVersion::Edition2023 {
+        return Err("Features are only available in editions".to_string());
+    }
+    parent.with_options(options)
+}
+
+/// Located feature error, or the one naming the scope for built-in declarations
+fn in_scope_error(message: String, name: &str, range: Option<&SourceRange>) -> ProtoError {
+    match range {
+        Some(range) => validation_error(message, range).with_code(ErrorCode::InvalidFeature),
+        None => ProtoError::new(&format!("{}\n  in {}", message, name))
+            .with_code(ErrorCode::InvalidFeature),
+    }
+}
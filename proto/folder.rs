@@ -1,14 +1,23 @@
-use std::{io, path::PathBuf};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use super::error::ProtoError;
 
 #[derive(Debug)]
 pub(crate) struct ProtoFolder {
+    /// Files to compile
     pub files: Vec<PathBuf>,
-    path: PathBuf,
+    /// Folders which imports are searched in, in order of priority
+    pub proto_paths: Vec<PathBuf>,
 }
 
 impl std::fmt::Display for ProtoFolder {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        writeln!(f, "{}", self.path.display())?;
+        for proto_path in self.proto_paths.iter() {
+            writeln!(f, "{}", proto_path.display())?;
+        }
         for file in self.files.iter() {
             writeln!(f, "- {}", file.display())?;
         }
@@ -17,14 +26,17 @@ impl std::fmt::Display for ProtoFolder {
 }
 
 /// Recursively goes through the folder and collects all .proto files
-pub(crate) fn read_proto_folder(folder_path: PathBuf) -> io::Result<ProtoFolder> {
+pub(crate) fn read_proto_folder(folder_path: PathBuf) -> Result<ProtoFolder, ProtoError> {
     let mut folders = vec![folder_path.clone()];
 
     let mut all_proto_file_paths: Vec<PathBuf> = Vec::new();
 
     while let Some(folder) = folders.pop() {
-        for entry in folder.read_dir()? {
-            let entry = entry?;
+        let entries = folder
+            .read_dir()
+            .map_err(|error| ProtoError::io(&folder, error))?;
+        for entry in entries {
+            let entry = entry.map_err(|error| ProtoError::io(&folder, error))?;
             let path = entry.path();
             if path.is_dir() {
                 folders.push(path);
@@ -36,6 +48,74 @@ pub(crate) fn read_proto_folder(folder_path: PathBuf) -> io::Result<ProtoFolder>
 
     Ok(ProtoFolder {
         files: all_proto_file_paths,
-        path: folder_path,
+        proto_paths: vec![folder_path],
     })
 }
+
+/// Collects .proto files of `input_paths`, which are files or folders.
+/// Without explicit `proto_paths` imports are searched in the input folders
+/// and the folders of the input files
+pub(crate) fn read_proto_inputs(
+    input_paths: &[PathBuf],
+    proto_paths: &[PathBuf],
+) -> Result<ProtoFolder, ProtoError> {
+    let mut files = Vec::new();
+    let mut input_folders = Vec::new();
+    for input_path in input_paths {
+        if input_path.is_dir() {
+            let folder = read_proto_folder(input_path.clone())?;
+            files.extend(folder.files);
+            input_folders.extend(folder.proto_paths);
+        } else if input_path.is_file() {
+            files.push(input_path.clone());
+            let parent = match input_path.parent() {
+                Some(parent) if parent != Path::new("") => parent.to_path_buf(),
+                _ => PathBuf::from("."),
+            };
+            input_folders.push(parent);
+        } else {
+            return Err(ProtoError::io(
+                input_path,
+                io::Error::new(io::ErrorKind::NotFound, "Input does not exist"),
+            ));
+        }
+    }
+    let proto_paths = if proto_paths.is_empty() {
+        let mut unique = Vec::new();
+        for folder in input_folders {
+            if !unique.contains(&folder) {
+                unique.push(folder);
+            }
+        }
+        unique
+    } else {
+        proto_paths.to_vec()
+    };
+    Ok(ProtoFolder { files, proto_paths })
+}
+
+/// Returns index of the first proto path containing `file`
+/// and path of the file relative to it, the one used by imports
+pub(crate) fn find_import_path(proto_paths: &[PathBuf], file: &Path) -> Option<(usize, String)> {
+    let file = file.canonicalize().ok()?;
+    proto_paths
+        .iter()
+        .enumerate()
+        .find_map(|(index, proto_path)| {
+            let relative = file.strip_prefix(proto_path.canonicalize().ok()?).ok()?;
+            let parts = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>();
+            Some((index, parts.join("/")))
+        })
+}
+
+/// Returns files of proto paths matching `import_path`, in order of priority
+pub(crate) fn find_imported_files(proto_paths: &[PathBuf], import_path: &str) -> Vec<PathBuf> {
+    proto_paths
+        .iter()
+        .map(|proto_path| proto_path.join(import_path))
+        .filter(|file| file.is_file())
+        .collect()
+}
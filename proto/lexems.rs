@@ -2,19 +2,25 @@ use std::{fmt::Display, rc::Rc};
 
 use super::error::ProtoError;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub(super) enum Lexem {
     Id(Rc<str>),
     Equal,
-    StringLiteral(Rc<str>),
+    /// Decoded bytes, which are not valid UTF-8 for some `bytes` values, e.g. `"\xff"`
+    StringLiteral(Rc<[u8]>),
     SemiColon,
+    Colon,
     Dot,
-    IntLiteral(i64),
+    /// Value from `-2^63` to `2^64 - 1`, i.e. fitting either `int64` or `uint64`
+    IntLiteral(i128),
+    FloatLiteral(f64),
     OpenCurly,
     CloseCurly,
     Comma,
     OpenBracket,
     CloseBracket,
+    OpenParen,
+    CloseParen,
     Less,
     Greater,
     EOF,
@@ -24,15 +30,20 @@ impl Display for Lexem {
         match self {
             Lexem::Id(s) => write!(f, "{}", s),
             Lexem::Equal => write!(f, "="),
-            Lexem::StringLiteral(s) => write!(f, "\"{}\"", s),
+            Lexem::StringLiteral(s) => write!(f, "\"{}\"", String::from_utf8_lossy(s)),
             Lexem::SemiColon => write!(f, ";"),
+            Lexem::Colon => write!(f, ":"),
             Lexem::Dot => write!(f, "."),
             Lexem::IntLiteral(i) => write!(f, "{}", i),
+            Lexem::FloatLiteral(x) if x.is_nan() => write!(f, "nan"),
+            Lexem::FloatLiteral(x) => write!(f, "{}", x),
             Lexem::OpenCurly => write!(f, "{{"),
             Lexem::Comma => write!(f, ","),
             Lexem::CloseCurly => write!(f, "}}"),
             Lexem::OpenBracket => write!(f, "["),
             Lexem::CloseBracket => write!(f, "]"),
+            Lexem::OpenParen => write!(f, "("),
+            Lexem::CloseParen => write!(f, ")"),
             Lexem::Less => write!(f, "<"),
             Lexem::Greater => write!(f, ">"),
             Lexem::EOF => write!(f, "EOF"),
@@ -40,59 +51,61 @@ impl Display for Lexem {
     }
 }
 
-pub(super) struct Position<'file_path> {
-    pub(super) file_path: &'file_path str,
-    pub(super) line: usize,
-    pub(super) column: usize,
-}
-
-impl Clone for Position<'_> {
-    fn clone(&self) -> Self {
-        Position {
-            file_path: self.file_path,
-            line: self.line,
-            column: self.column,
-        }
-    }
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct Position {
+    pub(crate) file_path: Rc<str>,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
 }
 
-impl Copy for Position<'_> {}
-
-impl std::fmt::Debug for Position<'_> {
+impl std::fmt::Debug for Position {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}:{}:{}", self.file_path, self.line, self.column)
     }
 }
 
-#[derive(Copy, Clone)]
-struct LocatedChar<'file_path> {
+#[derive(Clone)]
+struct LocatedChar {
     char: char,
-    position: Position<'file_path>,
+    position: Position,
 }
-impl std::fmt::Debug for LocatedChar<'_> {
+impl std::fmt::Debug for LocatedChar {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{:?}:\"{}\"", self.position, self.char)
     }
 }
 
-#[derive(Debug)]
-pub(super) struct SourceRange<'file_path> {
-    pub(super) start: Position<'file_path>,
-    pub(super) end: Position<'file_path>,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SourceRange {
+    pub(crate) start: Position,
+    pub(crate) end: Position,
 }
 
-impl Display for SourceRange<'_> {
+impl Display for SourceRange {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{:?}-{:?}", self.start, self.end)
     }
 }
 
-pub(super) struct LocatedLexem<'file_path> {
+pub(super) struct LocatedLexem {
     pub(super) lexem: Lexem,
-    pub(super) range: SourceRange<'file_path>,
+    pub(super) range: SourceRange,
+    /// Comments between the previous lexem and this one
+    pub(super) comments: Vec<Comment>,
 }
 
-impl std::fmt::Debug for LocatedLexem<'_> {
+/// Comment text without `//` or `/* */` markers.
+/// Consecutive `//` lines are merged into a single comment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Comment {
+    pub(super) text: String,
+    pub(super) range: SourceRange,
+    /// Comment starts on the line where the previous lexem ends
+    pub(super) trailing: bool,
+    is_line_comment: bool,
+}
+
+impl std::fmt::Debug for LocatedLexem {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{:?}: \"{:?}\"", self.range, self.lexem)
     }
@@ -102,23 +115,31 @@ fn is_id_char(char: char) -> bool {
     char.is_alphanumeric() || char == '_'
 }
 
-pub(super) fn read_lexems<'file_path>(
-    file_path: &'file_path str,
-    content: &str,
-) -> Result<Vec<LocatedLexem<'file_path>>, ProtoError> {
+pub(super) fn read_lexems(file_path: &str, content: &str) -> Result<Vec<LocatedLexem>, ProtoError> {
     let located_chars = read_chars(file_path, content);
     let mut current_char_index = 0;
-    let mut located_lexems = Vec::new();
+    let mut located_lexems: Vec<LocatedLexem> = Vec::new();
+    let mut comments: Vec<Comment> = Vec::new();
+    let mut commented_lexems_count = 0;
     while current_char_index < located_chars.len() {
-        let located_char = located_chars[current_char_index];
+        if located_lexems.len() > commented_lexems_count {
+            commented_lexems_count = located_lexems.len();
+            located_lexems.last_mut().unwrap().comments = std::mem::take(&mut comments);
+        }
+        let located_char = located_chars[current_char_index].clone();
         let LocatedChar { char, position } = located_char;
         if char::is_whitespace(char) {
             current_char_index += 1;
             continue;
         }
-        if char::is_digit(char, 10) || char == '-' {
-            let located_int_lexem = try_read_int(&located_chars, &mut current_char_index)?;
-            located_lexems.push(located_int_lexem);
+        let starts_fraction = char == '.'
+            && matches!(
+                located_chars.get(current_char_index + 1),
+                Some(LocatedChar { char, .. }) if char::is_digit(*char, 10)
+            );
+        if char::is_digit(char, 10) || char == '-' || starts_fraction {
+            let located_number_lexem = try_read_number(&located_chars, &mut current_char_index)?;
+            located_lexems.push(located_number_lexem);
             continue;
         }
         if is_id_char(char) {
@@ -126,29 +147,48 @@ pub(super) fn read_lexems<'file_path>(
             located_lexems.push(located_id_lexem);
             continue;
         }
-        if char == '"' {
+        if char == '"' || char == '\'' {
             let string_lexem = try_read_string_literal(&located_chars, &mut current_char_index)?;
-            located_lexems.push(string_lexem);
+            // Adjacent string literals are concatenated: `"foo" "bar"` is `"foobar"`
+            match located_lexems.last_mut() {
+                Some(LocatedLexem {
+                    lexem: Lexem::StringLiteral(prev),
+                    range,
+                    ..
+                }) => {
+                    if let Lexem::StringLiteral(next) = string_lexem.lexem {
+                        *prev = [&prev[..], &next[..]].concat().into();
+                    }
+                    range.end = string_lexem.range.end;
+                }
+                _ => located_lexems.push(string_lexem),
+            }
             continue;
         }
         if char == '/' {
-            match located_chars.get(current_char_index + 1) {
+            let mut comment = match located_chars.get(current_char_index + 1) {
                 Some(LocatedChar { char: '/', .. }) => {
-                    try_read_single_line_comment(&located_chars, &mut current_char_index)?;
+                    try_read_single_line_comment(&located_chars, &mut current_char_index)?
                 }
                 Some(LocatedChar { char: '*', .. }) => {
                     current_char_index += 2;
-                    try_read_multiline_comment(&located_chars, &mut current_char_index)?;
+                    try_read_multiline_comment(&located_chars, &mut current_char_index)?
                 }
                 _ => {
                     return Err(ProtoError::UnknownCharacter {
-                        file_path: position.file_path.to_string(),
-                        line: position.line,
-                        column: position.column,
-                        char: char,
+                        char,
+                        range: SourceRange {
+                            start: position.clone(),
+                            end: position,
+                        },
                     })
                 }
-            }
+            };
+            comment.trailing = match located_lexems.last() {
+                Some(located_lexem) => located_lexem.range.end.line == comment.range.start.line,
+                None => false,
+            };
+            push_comment(&mut comments, comment);
             continue;
         }
         current_char_index += 1;
@@ -158,55 +198,72 @@ pub(super) fn read_lexems<'file_path>(
             ',' => Some(Lexem::Comma),
             '>' => Some(Lexem::Greater),
             ';' => Some(Lexem::SemiColon),
+            ':' => Some(Lexem::Colon),
             '.' => Some(Lexem::Dot),
             '{' => Some(Lexem::OpenCurly),
             '}' => Some(Lexem::CloseCurly),
             '[' => Some(Lexem::OpenBracket),
             ']' => Some(Lexem::CloseBracket),
+            '(' => Some(Lexem::OpenParen),
+            ')' => Some(Lexem::CloseParen),
             _ => None,
         };
         if let Some(lexem) = single_char_lexem {
             let located_lexem = LocatedLexem {
                 lexem,
                 range: SourceRange {
-                    start: position,
+                    start: position.clone(),
                     end: position,
                 },
+                comments: Vec::new(),
             };
             located_lexems.push(located_lexem);
             continue;
         }
         return Err(ProtoError::UnknownCharacter {
-            file_path: position.file_path.to_string(),
-            line: position.line,
-            column: position.column,
-            char: char,
+            char,
+            range: SourceRange {
+                start: position.clone(),
+                end: position,
+            },
         });
     }
-    let last_char_position = located_chars[located_chars.len() - 1].position;
+    // Empty files end at their first position
+    let last_char_position = match located_chars.last() {
+        Some(located_char) => located_char.position.clone(),
+        None => Position {
+            file_path: file_path.into(),
+            line: 1,
+            column: 1,
+        },
+    };
+    if located_lexems.len() > commented_lexems_count {
+        located_lexems.last_mut().unwrap().comments = std::mem::take(&mut comments);
+    }
     located_lexems.push(LocatedLexem {
         lexem: Lexem::EOF,
         range: SourceRange {
-            start: last_char_position,
+            start: last_char_position.clone(),
             end: last_char_position,
         },
+        comments,
     });
 
     Ok(located_lexems)
 }
 
-fn try_read_id<'file_path>(
-    located_chars: &[LocatedChar<'file_path>],
+fn try_read_id(
+    located_chars: &[LocatedChar],
     located_char_index: &mut usize,
-) -> Result<LocatedLexem<'file_path>, ProtoError> {
+) -> Result<LocatedLexem, ProtoError> {
     let mut int_str = String::new();
-    let start = located_chars[*located_char_index].position;
-    let mut end = start;
+    let start = located_chars[*located_char_index].position.clone();
+    let mut end = start.clone();
     loop {
         if *located_char_index >= located_chars.len() {
             break;
         }
-        let LocatedChar { char, position } = located_chars[*located_char_index];
+        let LocatedChar { char, position } = located_chars[*located_char_index].clone();
         if !is_id_char(char) {
             break;
         }
@@ -219,92 +276,175 @@ fn try_read_id<'file_path>(
     }
     let lexem = Lexem::Id(Rc::from(int_str));
     let range = SourceRange { start, end };
-    let located_lexem: LocatedLexem<'file_path> = LocatedLexem { lexem, range };
+    let located_lexem: LocatedLexem = LocatedLexem {
+        lexem,
+        range,
+        comments: Vec::new(),
+    };
     Ok(located_lexem)
 }
-fn try_read_int<'file_path>(
-    located_chars: &[LocatedChar<'file_path>],
+/// Reads integer (decimal, `0x` hex or `0` octal) or float literal with optional minus sign.
+/// `-inf` and `-nan` are read as float literals, unsigned `inf` and `nan` are identifiers
+fn try_read_number(
+    located_chars: &[LocatedChar],
     located_char_index: &mut usize,
-) -> Result<LocatedLexem<'file_path>, ProtoError> {
-    let mut digits = String::new();
-    let start = located_chars[*located_char_index].position;
-    let mut end = start;
-    let mut minus_found = false;
-    loop {
-        if *located_char_index >= located_chars.len() {
+) -> Result<LocatedLexem, ProtoError> {
+    let mut literal = String::new();
+    let start = located_chars[*located_char_index].position.clone();
+    let mut end = start.clone();
+    let negative = located_chars[*located_char_index].char == '-';
+    if negative {
+        literal.push('-');
+        *located_char_index += 1;
+    }
+    let is_hex = {
+        let mut next_chars = located_chars[*located_char_index..].iter().map(|c| c.char);
+        next_chars.next() == Some('0') && matches!(next_chars.next(), Some('x' | 'X'))
+    };
+    let mut last_char = '-';
+    while let Some(LocatedChar { char, position }) = located_chars.get(*located_char_index) {
+        let is_exponent_sign =
+            !is_hex && (*char == '+' || *char == '-') && (last_char == 'e' || last_char == 'E');
+        if !is_id_char(*char) && *char != '.' && !is_exponent_sign {
             break;
         }
-        let LocatedChar { char, position } = located_chars[*located_char_index];
-        if char::is_digit(char, 10) {
-            end = position;
-            *located_char_index += 1;
-            digits.push(char);
-            continue;
+        end = position.clone();
+        *located_char_index += 1;
+        literal.push(*char);
+        last_char = *char;
+    }
+    let body = &literal[negative as usize..];
+
+    let is_float = !is_hex && body.contains(['.', 'e', 'E']);
+    let lexem = match body {
+        "inf" if negative => Some(Lexem::FloatLiteral(f64::NEG_INFINITY)),
+        "nan" if negative => Some(Lexem::FloatLiteral(f64::NAN)),
+        _ if is_hex => parse_int_literal(&body[2..], 16, negative).map(Lexem::IntLiteral),
+        _ if is_float => parse_float_literal(body, negative).map(Lexem::FloatLiteral),
+        _ if body.len() > 1 && body.starts_with('0') => {
+            parse_int_literal(&body[1..], 8, negative).map(Lexem::IntLiteral)
         }
-        if !minus_found && char == '-' {
-            end = position;
-            *located_char_index += 1;
-            digits.push(char);
-            minus_found = true;
-            continue;
+        _ => parse_int_literal(body, 10, negative).map(Lexem::IntLiteral),
+    };
+
+    match lexem {
+        Some(lexem) => {
+            let range = SourceRange { start, end };
+            Ok(LocatedLexem {
+                lexem,
+                range,
+                comments: Vec::new(),
+            })
         }
-        break;
+        None if is_float => Err(ProtoError::InvalidFloatLiteral {
+            literal,
+            range: SourceRange { start, end },
+        }),
+        None => Err(ProtoError::InvalidIntLiteral {
+            literal,
+            range: SourceRange { start, end },
+        }),
     }
-    if digits.len() <= 0 {
-        unreachable!()
+}
+
+/// Parses digits of int literal in given radix, returns `None` if they are invalid
+/// or the value fits neither `int64` nor `uint64`
+fn parse_int_literal(digits: &str, radix: u32, negative: bool) -> Option<i128> {
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
     }
-    let num = i64::from_str_radix(&digits, 10);
-    match num {
-        Ok(value) => {
-            let lexem = Lexem::IntLiteral(value);
-            let range = SourceRange { start, end };
-            let located_lexem: LocatedLexem<'file_path> = LocatedLexem { lexem, range };
-            Ok(located_lexem)
-        }
-        Err(_) => {
-            return Err(ProtoError::InvalidIntLiteral {
-                literal: digits,
-                file_path: start.file_path.to_string(),
-                line: start.line,
-                start_column: start.column,
-                end_column: end.column,
-            })
+    let value = i128::from_str_radix(digits, radix).ok()?;
+    let value = if negative { -value } else { value };
+    let range = i64::MIN as i128..=u64::MAX as i128;
+    range.contains(&value).then_some(value)
+}
+
+/// Parses `decimals "." [decimals] [exponent] | decimals exponent | "." decimals [exponent]`
+fn parse_float_literal(body: &str, negative: bool) -> Option<f64> {
+    let (mantissa, exponent) = match body.find(['e', 'E']) {
+        Some(exponent_start) => (&body[..exponent_start], Some(&body[exponent_start + 1..])),
+        None => (body, None),
+    };
+    let (int_part, fraction) = match mantissa.split_once('.') {
+        Some((int_part, fraction)) => (int_part, Some(fraction)),
+        None => (mantissa, None),
+    };
+    let is_decimals = |s: &str| s.chars().all(|c| char::is_digit(c, 10));
+    if !is_decimals(int_part) || !fraction.is_none_or(is_decimals) {
+        return None;
+    }
+    if int_part.is_empty() && fraction.is_none_or(str::is_empty) {
+        return None;
+    }
+    if let Some(exponent) = exponent {
+        let exponent_digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        if exponent_digits.is_empty() || !is_decimals(exponent_digits) {
+            return None;
         }
     }
+    let value: f64 = body.parse().ok()?;
+    Some(if negative { -value } else { value })
 }
 
-fn try_read_single_line_comment<'file_path>(
-    located_chars: &[LocatedChar<'file_path>],
+fn try_read_single_line_comment(
+    located_chars: &[LocatedChar],
     located_char_index: &mut usize,
-) -> Result<(), ProtoError> {
+) -> Result<Comment, ProtoError> {
+    let start = located_chars[*located_char_index].position.clone();
+    let mut end = start.clone();
     while let Some(located_char) = located_chars.get(*located_char_index) {
         if located_char.char == '/' {
+            end = located_char.position.clone();
             *located_char_index += 1;
             continue;
         }
         break;
     }
+    let mut text = String::new();
     while let Some(located_char) = located_chars.get(*located_char_index) {
         if located_char.char == '\n' {
             break;
         }
+        end = located_char.position.clone();
+        text.push(located_char.char);
         *located_char_index += 1
     }
-    Ok(())
+    let text = text
+        .strip_prefix(' ')
+        .unwrap_or(&text)
+        .trim_end()
+        .to_string();
+    Ok(Comment {
+        text,
+        range: SourceRange { start, end },
+        trailing: false,
+        is_line_comment: true,
+    })
 }
-fn try_read_multiline_comment<'file_path>(
-    located_chars: &[LocatedChar<'file_path>],
+fn try_read_multiline_comment(
+    located_chars: &[LocatedChar],
     located_char_index: &mut usize,
-) -> Result<(), ProtoError> {
+) -> Result<Comment, ProtoError> {
+    let start = located_chars[*located_char_index - 2].position.clone();
+    let text_start = *located_char_index;
     let mut last_char: Option<&LocatedChar> = None;
     let mut current_char = located_chars.get(*located_char_index);
-    loop {
+    let end = loop {
         match (last_char, current_char) {
+            (_, None) => {
+                return Err(ProtoError::SyntaxError {
+                    message: "Unterminated comment".into(),
+                    range: SourceRange {
+                        start: start.clone(),
+                        end: start,
+                    },
+                });
+            }
             (Some(last_char), Some(current_char))
                 if last_char.char == '*' && current_char.char == '/' =>
             {
                 *located_char_index += 1;
-                break;
+                break current_char.position.clone();
             }
             _ => {
                 last_char = current_char;
@@ -312,43 +452,164 @@ fn try_read_multiline_comment<'file_path>(
                 current_char = located_chars.get(*located_char_index);
             }
         }
+    };
+    let raw_text = located_chars[text_start..*located_char_index - 2]
+        .iter()
+        .map(|c| c.char)
+        .collect::<String>();
+    Ok(Comment {
+        text: strip_block_comment(&raw_text),
+        range: SourceRange { start, end },
+        trailing: false,
+        is_line_comment: false,
+    })
+}
+
+/// Removes leading `*` of every line and surrounding empty lines of `/* */` comment
+fn strip_block_comment(raw_text: &str) -> String {
+    let lines = raw_text
+        .lines()
+        .map(|line| {
+            let line = line.trim_start();
+            let line = line.strip_prefix('*').unwrap_or(line);
+            line.strip_prefix(' ').unwrap_or(line).trim_end()
+        })
+        .collect::<Vec<_>>();
+    let first = lines.iter().position(|line| !line.is_empty());
+    let last = lines.iter().rposition(|line| !line.is_empty());
+    match (first, last) {
+        (Some(first), Some(last)) => lines[first..=last].join("\n"),
+        _ => String::new(),
     }
-    Ok(())
 }
-fn try_read_string_literal<'file_path>(
-    located_chars: &[LocatedChar<'file_path>],
+
+/// Appends comment, merging `//` comment into the one on the previous line
+fn push_comment(comments: &mut Vec<Comment>, comment: Comment) {
+    if let Some(last) = comments.last_mut() {
+        let is_continuation = last.is_line_comment
+            && comment.is_line_comment
+            && !last.trailing
+            && !comment.trailing
+            && last.range.end.line + 1 == comment.range.start.line;
+        if is_continuation {
+            last.text.push('\n');
+            last.text.push_str(&comment.text);
+            last.range.end = comment.range.end;
+            return;
+        }
+    }
+    comments.push(comment);
+}
+
+/// Reads string literal enclosed in `"` or `'` and decodes its escape sequences
+fn try_read_string_literal(
+    located_chars: &[LocatedChar],
     located_char_index: &mut usize,
-) -> Result<LocatedLexem<'file_path>, ProtoError> {
-    let mut string_literal = String::new();
-    let mut last_char = located_chars[*located_char_index].char;
-    let start = located_chars[*located_char_index].position;
-    let mut end = start;
+) -> Result<LocatedLexem, ProtoError> {
+    let quote = located_chars[*located_char_index].char;
+    let start = located_chars[*located_char_index].position.clone();
+    let string_error = |message: &str, position: &Position| ProtoError::InvalidStringLiteral {
+        message: message.to_string(),
+        range: SourceRange {
+            start: position.clone(),
+            end: position.clone(),
+        },
+    };
+    let mut bytes = Vec::new();
     *located_char_index += 1;
-    loop {
-        if *located_char_index >= located_chars.len() {
-            break;
+    let end = loop {
+        let LocatedChar { char, position } = match located_chars.get(*located_char_index) {
+            Some(located_char) => located_char.clone(),
+            None => return Err(string_error("Unterminated string literal", &start)),
+        };
+        *located_char_index += 1;
+        if char == quote {
+            break position;
         }
-        let LocatedChar { char, position } = located_chars[*located_char_index];
-        if char == '"' && last_char != '\\' {
-            *located_char_index += 1;
-            end = position;
-            break;
+        match char {
+            '\n' => return Err(string_error("Unterminated string literal", &start)),
+            '\\' => read_escape_sequence(located_chars, located_char_index, &mut bytes)
+                .map_err(|message| string_error(message, &position))?,
+            _ => bytes.extend_from_slice(char.encode_utf8(&mut [0; 4]).as_bytes()),
         }
-        end = position;
-        *located_char_index += 1;
-        string_literal.push(char);
-        last_char = char;
-    }
-    let lexem = Lexem::StringLiteral(Rc::from(string_literal));
+    };
+    let lexem = Lexem::StringLiteral(Rc::from(bytes));
     let range = SourceRange { start, end };
-    let located_lexem: LocatedLexem<'file_path> = LocatedLexem { lexem, range };
+    let located_lexem: LocatedLexem = LocatedLexem {
+        lexem,
+        range,
+        comments: Vec::new(),
+    };
     Ok(located_lexem)
 }
 
-fn read_chars<'file_path>(
-    file_path: &'file_path str,
-    content: &str,
-) -> Vec<LocatedChar<'file_path>> {
+/// Decodes escape sequence after `\`: `\n`, `\x41`, `\101`, `\u00e9`, `\U0001F600`, etc.
+fn read_escape_sequence(
+    located_chars: &[LocatedChar],
+    located_char_index: &mut usize,
+    bytes: &mut Vec<u8>,
+) -> Result<(), &'static str> {
+    let char = match located_chars.get(*located_char_index) {
+        Some(located_char) => located_char.char,
+        None => return Err("Unterminated escape sequence"),
+    };
+    *located_char_index += 1;
+    let byte = match char {
+        'a' => 0x07,
+        'b' => 0x08,
+        'f' => 0x0c,
+        'n' => b'\n',
+        'r' => b'\r',
+        't' => b'\t',
+        'v' => 0x0b,
+        '\\' | '\'' | '"' | '?' => char as u8,
+        'x' | 'X' => read_digits(located_chars, located_char_index, 16, 1, 2)
+            .ok_or("Invalid hex escape sequence")? as u8,
+        '0'..='7' => {
+            *located_char_index -= 1;
+            let value = read_digits(located_chars, located_char_index, 8, 1, 3).unwrap();
+            u8::try_from(value).map_err(|_| "Octal escape sequence is out of range")?
+        }
+        'u' | 'U' => {
+            let len = if char == 'u' { 4 } else { 8 };
+            let code = read_digits(located_chars, located_char_index, 16, len, len)
+                .ok_or("Invalid unicode escape sequence")?;
+            let char = char::from_u32(code).ok_or("Invalid unicode code point")?;
+            bytes.extend_from_slice(char.encode_utf8(&mut [0; 4]).as_bytes());
+            return Ok(());
+        }
+        _ => return Err("Invalid escape sequence"),
+    };
+    bytes.push(byte);
+    Ok(())
+}
+
+/// Reads from `min` to `max` digits of escape sequence in given radix
+fn read_digits(
+    located_chars: &[LocatedChar],
+    located_char_index: &mut usize,
+    radix: u32,
+    min: usize,
+    max: usize,
+) -> Option<u32> {
+    let mut digits = String::new();
+    while digits.len() < max {
+        match located_chars.get(*located_char_index) {
+            Some(LocatedChar { char, .. }) if char.is_digit(radix) => {
+                digits.push(*char);
+                *located_char_index += 1;
+            }
+            _ => break,
+        }
+    }
+    if digits.len() < min {
+        return None;
+    }
+    u32::from_str_radix(&digits, radix).ok()
+}
+
+fn read_chars(file_path: &str, content: &str) -> Vec<LocatedChar> {
+    let file_path: Rc<str> = file_path.into();
     let mut located_chars = Vec::new();
     let mut line = 1;
     let mut column = 1;
@@ -359,7 +620,7 @@ fn read_chars<'file_path>(
         let located_char = LocatedChar {
             char,
             position: Position {
-                file_path: file_path,
+                file_path: Rc::clone(&file_path),
                 line,
                 column,
             },
@@ -1,6 +1,7 @@
 use super::{
     compiler::ts::ast,
-    error::ProtoError,
+    error::{located_error, ErrorCode, ProtoError},
+    folder::{find_import_path, find_imported_files},
     id_generator::{IdGenerator, UniqueId},
     lexems,
     proto_scope::{
@@ -9,14 +10,23 @@ use super::{
     },
     syntax,
 };
-use lexems::read_lexems;
-use std::{fmt::Display, io::Read, ops::Deref, path::PathBuf, rc::Rc};
+use lexems::{read_lexems, SourceRange};
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    io::Read,
+    ops::Deref,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 use syntax::parse_package;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum ProtoVersion {
     Proto2,
     Proto3,
+    /// `edition = "2023"`, behavior is defined by resolved features
+    Edition2023,
 }
 
 impl std::fmt::Display for ProtoVersion {
@@ -25,26 +35,139 @@ impl std::fmt::Display for ProtoVersion {
         match self {
             Proto2 => write!(f, "proto2"),
             Proto3 => write!(f, "proto3"),
+            Edition2023 => write!(f, "2023"),
+        }
+    }
+}
+
+/// Largest field number, used by `max` in message ranges
+pub(crate) const MAX_FIELD_NUMBER: i64 = 536_870_911;
+/// Largest enum value, used by `max` in enum ranges
+pub(crate) const MAX_ENUM_VALUE: i64 = i32::MAX as i64;
+
+/// Inclusive range of field numbers or enum values, e.g. `9 to 11`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TagRange {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl TagRange {
+    pub fn contains(&self, tag: i64) -> bool {
+        self.start <= tag && tag <= self.end
+    }
+}
+
+impl std::fmt::Display for TagRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{} to {}", self.start, self.end)
         }
     }
 }
 
+/// Single `reserved` statement of a message or enum
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Reserved {
+    pub ranges: Vec<TagRange>,
+    pub names: Vec<Rc<str>>,
+    pub range: SourceRange,
+}
+
+impl Reserved {
+    pub fn contains_tag(&self, tag: i64) -> bool {
+        self.ranges.iter().any(|range| range.contains(tag))
+    }
+    pub fn contains_name(&self, name: &str) -> bool {
+        self.names.iter().any(|n| n.deref() == name)
+    }
+}
+
+impl std::fmt::Display for Reserved {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let ranges = self.ranges.iter().map(|r| r.to_string());
+        let names = self.names.iter().map(|n| format!("\"{}\"", n));
+        let entries = ranges.chain(names).collect::<Vec<_>>();
+        write!(f, "reserved {};", entries.join(", "))
+    }
+}
+
+/// Single `extensions` statement of a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Extensions {
+    pub ranges: Vec<TagRange>,
+    pub options: Vec<ProtoOption>,
+    pub range: SourceRange,
+}
+
+impl Extensions {
+    pub fn contains_tag(&self, tag: i64) -> bool {
+        self.ranges.iter().any(|range| range.contains(tag))
+    }
+}
+
+impl std::fmt::Display for Extensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let ranges = self.ranges.iter().map(|r| r.to_string());
+        write!(f, "extensions {}", ranges.collect::<Vec<_>>().join(", "))?;
+        write_compact_options(f, &self.options)?;
+        write!(f, ";")
+    }
+}
+
+/// Comments attached to a declaration the same way protoc does:
+/// leading comment is right above the declaration, trailing one is on the same line after it,
+/// detached ones are above the leading one and separated by empty lines
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Comments {
+    pub leading: Option<Rc<str>>,
+    pub trailing: Option<Rc<str>>,
+    pub detached: Vec<Rc<str>>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct EnumEntry {
     pub name: Rc<str>,
     pub value: i64,
+    pub options: Vec<ProtoOption>,
+    pub range: Option<SourceRange>,
+    pub comments: Comments,
 }
 
 impl From<(Rc<str>, i64)> for EnumEntry {
     fn from((name, value): (Rc<str>, i64)) -> Self {
-        Self { name, value }
+        Self {
+            name,
+            value,
+            options: Vec::new(),
+            range: None,
+            comments: Comments::default(),
+        }
     }
 }
 
 impl std::fmt::Display for EnumEntry {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{} = {}", self.name, self.value)
+        write!(f, "{} = {}", self.name, self.value)?;
+        write_compact_options(f, &self.options)
+    }
+}
+
+/// Writes options in the `[a = 1, b = 2]` form used by fields and enum values
+fn write_compact_options(f: &mut std::fmt::Formatter, options: &[ProtoOption]) -> std::fmt::Result {
+    if options.is_empty() {
+        return Ok(());
+    }
+    write!(f, " [")?;
+    for (i, option) in options.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", option)?;
     }
+    write!(f, "]")
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -52,6 +175,13 @@ pub(crate) struct EnumDeclaration {
     pub id: usize,
     pub name: Rc<str>,
     pub entries: Vec<EnumEntry>,
+    pub options: Vec<ProtoOption>,
+    pub reserved: Vec<Reserved>,
+    pub comments: Comments,
+    /// Closed enums drop unknown values on decoding
+    pub closed: bool,
+    /// Range of the name, missing for built-in declarations
+    pub range: Option<SourceRange>,
 }
 impl UniqueId for EnumDeclaration {
     type Args = (Rc<str>, Vec<EnumEntry>);
@@ -61,12 +191,23 @@ impl UniqueId for EnumDeclaration {
             id,
             name: args.0,
             entries: args.1,
+            options: Vec::new(),
+            reserved: Vec::new(),
+            comments: Comments::default(),
+            closed: false,
+            range: None,
         }
     }
 }
 impl std::fmt::Display for EnumDeclaration {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "enum {} {{\n", self.name)?;
+        for option in &self.options {
+            writeln!(f, "  option {};", option)?;
+        }
+        for reserved in &self.reserved {
+            writeln!(f, "  {}", reserved)?;
+        }
         for entry in &self.entries {
             let entry_str = format!("{};", entry);
             let lines = entry_str.lines();
@@ -158,6 +299,21 @@ impl Type {
         }
     }
 
+    /// Values integer literals of the type may have, `None` for non-integer types
+    pub fn int_range(&self) -> Option<std::ops::RangeInclusive<i128>> {
+        match self {
+            Self::Int32 | Self::Sint32 | Self::Sfixed32 => {
+                Some(i32::MIN as i128..=i32::MAX as i128)
+            }
+            Self::Uint32 | Self::Fixed32 => Some(0..=u32::MAX as i128),
+            Self::Int64 | Self::Sint64 | Self::Sfixed64 => {
+                Some(i64::MIN as i128..=i64::MAX as i128)
+            }
+            Self::Uint64 | Self::Fixed64 => Some(0..=u64::MAX as i128),
+            _ => None,
+        }
+    }
+
     pub fn to_string(&self) -> String {
         match self {
             Self::Bool => "bool".to_string(),
@@ -179,6 +335,9 @@ impl Type {
         }
     }
 
+    pub const START_GROUP_WIRE_TYPE: u32 = 3;
+    pub const END_GROUP_WIRE_TYPE: u32 = 4;
+
     pub fn get_basic_wire_type(&self) -> u32 {
         match self {
             Self::Bool => 0,
@@ -270,6 +429,8 @@ impl Clone for Type {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum FieldTypeReference {
     IdPath(Vec<Rc<str>>),
+    /// Fully qualified name starting with `.`, e.g. `.my.pkg.Message`
+    AbsoluteIdPath(Vec<Rc<str>>),
     Repeated(Box<FieldTypeReference>),
     Map(Box<FieldTypeReference>, Box<FieldTypeReference>),
     Bool,     // bool
@@ -295,7 +456,7 @@ impl FieldTypeReference {
     }
     pub fn trivial_resolve(&self) -> Option<Type> {
         match self {
-            FieldTypeReference::IdPath(_) => None,
+            FieldTypeReference::IdPath(_) | FieldTypeReference::AbsoluteIdPath(_) => None,
             FieldTypeReference::Repeated(t) => {
                 t.trivial_resolve().map(|t| Type::Repeated(t.into()))
             }
@@ -346,6 +507,9 @@ impl FieldTypeReference {
 impl From<Vec<Rc<str>>> for FieldTypeReference {
     fn from(id_path: Vec<Rc<str>>) -> Self {
         assert!(id_path.len() > 0);
+        if id_path[0].is_empty() {
+            return FieldTypeReference::AbsoluteIdPath(id_path[1..].to_vec());
+        }
         if id_path.len() == 1 {
             let id = Rc::clone(&id_path[0]);
             if id.deref() == "bool" {
@@ -392,6 +556,7 @@ impl std::fmt::Display for FieldTypeReference {
         use FieldTypeReference::*;
         match self {
             IdPath(path) => write!(f, "{}", path.join(".")),
+            AbsoluteIdPath(path) => write!(f, ".{}", path.join(".")),
             Repeated(field_type) => write!(f, "repeated {}", field_type),
             Map(key_type, value_type) => write!(f, "map<{}, {}>", key_type, value_type),
             Bool => write!(f, "bool"),
@@ -413,21 +578,184 @@ impl std::fmt::Display for FieldTypeReference {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct OptionNamePart {
+    pub name: Rc<str>,
+    /// Parenthesized part, e.g. `(my.custom)`, refers to an extension
+    pub is_extension: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct OptionName {
+    pub parts: Vec<OptionNamePart>,
+}
+
+impl From<&str> for OptionName {
+    fn from(name: &str) -> Self {
+        OptionName {
+            parts: vec![OptionNamePart {
+                name: name.into(),
+                is_extension: false,
+            }],
+        }
+    }
+}
+
+impl OptionName {
+    /// Checks if this is a built-in option with the given name, e.g. `packed`
+    pub fn is(&self, name: &str) -> bool {
+        match &self.parts[..] {
+            [part] => !part.is_extension && part.name.deref() == name,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for OptionName {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, part) in self.parts.iter().enumerate() {
+            if i > 0 {
+                write!(f, ".")?;
+            }
+            if part.is_extension {
+                write!(f, "({})", part.name)?;
+            } else {
+                write!(f, "{}", part.name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum OptionValue {
+    Bool(bool),
+    /// Value from `-2^63` to `2^64 - 1`, checked against the type of the option when used
+    Int(i128),
+    Float(f64),
+    Identifier(Rc<str>),
+    /// Bytes of string literal, which are checked to be UTF-8 only for `string` values
+    String(Rc<[u8]>),
+    /// Text format message value, e.g. `{ get: "/v1/{id}" body: "*" }`.
+    /// Repeated fields are stored as several entries with the same name.
+    Aggregate(Vec<(Rc<str>, OptionValue)>),
+}
+
+impl OptionValue {
+    /// Text of string value, `None` for other values and strings which are not valid UTF-8
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            OptionValue::String(s) => std::str::from_utf8(s).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for OptionValue {
+    fn eq(&self, other: &Self) -> bool {
+        use OptionValue::*;
+        match (self, other) {
+            (Bool(a), Bool(b)) => a == b,
+            (Int(a), Int(b)) => a == b,
+            (Float(a), Float(b)) => a.to_bits() == b.to_bits(),
+            (Identifier(a), Identifier(b)) => a == b,
+            (String(a), String(b)) => a == b,
+            (Aggregate(a), Aggregate(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+impl Eq for OptionValue {}
+
+impl std::fmt::Display for OptionValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use OptionValue::*;
+        match self {
+            Bool(b) => write!(f, "{}", b),
+            Int(i) => write!(f, "{}", i),
+            Float(x) if x.is_nan() => write!(f, "nan"),
+            Float(x) if x.is_infinite() && *x > 0f64 => write!(f, "inf"),
+            Float(x) if x.is_infinite() => write!(f, "-inf"),
+            Float(x) => write!(f, "{:?}", x),
+            Identifier(id) => write!(f, "{}", id),
+            String(s) => write!(f, "\"{}\"", s.escape_ascii()),
+            Aggregate(entries) => {
+                write!(f, "{{")?;
+                for (name, value) in entries {
+                    write!(f, " {}: {}", name, value)?;
+                }
+                write!(f, " }}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ProtoOption {
+    pub name: OptionName,
+    pub value: OptionValue,
+}
+
+/// Finds the value of a built-in option, the last one wins
+pub(crate) fn find_option<'a>(options: &'a [ProtoOption], name: &str) -> Option<&'a OptionValue> {
+    options
+        .iter()
+        .rev()
+        .find(|option| option.name.is(name))
+        .map(|option| &option.value)
+}
+
+impl std::fmt::Display for ProtoOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} = {}", self.name, self.value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FieldLabel {
+    /// Field with explicit presence, i.e. `optional int32 x = 1;`
+    Optional,
+    /// proto2 field which must be present on the wire
+    Required,
+}
+
+impl std::fmt::Display for FieldLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FieldLabel::Optional => write!(f, "optional"),
+            FieldLabel::Required => write!(f, "required"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct FieldDeclaration {
     pub name: Rc<str>,
+    pub label: Option<FieldLabel>,
     pub field_type_ref: FieldTypeReference,
     pub tag: i64,
-    pub attributes: Vec<(Rc<str>, Rc<str>)>,
+    pub options: Vec<ProtoOption>,
+    /// Missing for built-in declarations, e.g. well-known types
+    pub range: Option<SourceRange>,
+    /// Message is framed by start/end group tags instead of length prefix
+    pub delimited: bool,
+    /// Repeated scalar elements are encoded in a single length-delimited record
+    pub packed: bool,
+    pub comments: Comments,
 }
 
 impl FieldDeclaration {
     pub fn new(name: &str, field_type_ref: FieldTypeReference, tag: i64) -> Self {
         FieldDeclaration {
             name: Rc::from(name),
+            label: None,
             field_type_ref,
             tag,
-            attributes: Vec::new(),
+            options: Vec::new(),
+            range: None,
+            delimited: false,
+            packed: true,
+            comments: Comments::default(),
         }
     }
 }
@@ -435,56 +763,105 @@ impl FieldDeclaration {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct Field {
     pub name: Rc<str>,
+    pub label: Option<FieldLabel>,
     pub field_type: Type,
     pub tag: i64,
-    pub attributes: Vec<(Rc<str>, Rc<str>)>,
+    pub options: Vec<ProtoOption>,
+    /// Message is framed by start/end group tags instead of length prefix
+    pub delimited: bool,
+    /// Repeated scalar elements are encoded in a single length-delimited record
+    pub packed: bool,
+    pub range: Option<SourceRange>,
+    pub comments: Comments,
 }
 
 impl Field {
     pub fn json_name(&self) -> Rc<str> {
-        for (key, value) in &self.attributes {
-            if key.deref() == "json_name" {
-                return Rc::clone(value);
+        match find_option(&self.options, "json_name").and_then(OptionValue::as_str) {
+            Some(json_name) => json_name.into(),
+            None => Rc::clone(&self.name),
+        }
+    }
+
+    /// Key of the field in proto3 JSON: `json_name` option or lowerCamelCase of the name
+    pub fn canonical_json_name(&self) -> Rc<str> {
+        if let Some(json_name) =
+            find_option(&self.options, "json_name").and_then(OptionValue::as_str)
+        {
+            return json_name.into();
+        }
+        let mut res = String::new();
+        let mut capitalize_next = false;
+        for c in self.name.chars() {
+            if c == '_' {
+                capitalize_next = true;
+            } else if capitalize_next {
+                res.extend(c.to_uppercase());
+                capitalize_next = false;
+            } else {
+                res.push(c);
             }
         }
-        Rc::clone(&self.name)
+        res.into()
+    }
+
+    /// Custom default value from `[default = ...]`
+    pub fn default_value(&self) -> Option<&OptionValue> {
+        find_option(&self.options, "default")
+    }
+
+    /// Whether the field is absent unless set, i.e. it has explicit presence
+    /// and no custom default to fall back to
+    pub fn is_optional(&self) -> bool {
+        self.label == Some(FieldLabel::Optional) && self.default_value().is_none()
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.label == Some(FieldLabel::Required)
     }
 }
 
+/// Field declared in `extend` block, resolved against the extended message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Extension {
+    /// Id of the extended message
+    pub extendee: usize,
+    pub field: Field,
+}
+
 impl std::fmt::Display for FieldDeclaration {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(label) = self.label {
+            write!(f, "{} ", label)?;
+        }
         write!(f, "{} {} = {}", self.field_type_ref, self.name, self.tag)?;
-        if !self.attributes.is_empty() {
-            write!(f, " [")?;
-            for (i, (name, value)) in self.attributes.iter().enumerate() {
-                if i > 0 {
-                    write!(f, ", ")?;
-                }
-                write!(f, "{} = \"{}\"", name, value)?;
-            }
-            write!(f, "]")?;
-        };
-        Ok(())
+        write_compact_options(f, &self.options)
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct OneOfGroup {
     pub name: Rc<str>,
-    pub options: Vec<Field>,
+    pub fields: Vec<Field>,
+    pub comments: Comments,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct OneOfDeclaration {
     pub name: Rc<str>,
-    pub options: Vec<FieldDeclaration>,
+    pub fields: Vec<FieldDeclaration>,
+    pub options: Vec<ProtoOption>,
+    pub comments: Comments,
 }
 
 impl std::fmt::Display for OneOfDeclaration {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "oneof {} {{\n", self.name)?;
         for option in &self.options {
-            write!(f, "  {};\n", option)?;
+            writeln!(f, "  option {};", option)?;
+        }
+        for field in &self.fields {
+            write!(f, "  {};\n", field)?;
         }
         write!(f, "}}\n")
     }
@@ -524,6 +901,12 @@ pub(crate) struct MessageDeclaration {
     pub id: usize,
     pub name: Rc<str>,
     pub entries: Vec<MessageDeclarationEntry>,
+    pub options: Vec<ProtoOption>,
+    pub reserved: Vec<Reserved>,
+    pub extensions: Vec<Extensions>,
+    pub comments: Comments,
+    /// Range of the name, missing for built-in declarations
+    pub range: Option<SourceRange>,
 }
 
 impl UniqueId for MessageDeclaration {
@@ -534,6 +917,11 @@ impl UniqueId for MessageDeclaration {
             id,
             name: args.0,
             entries: args.1,
+            options: Vec::new(),
+            reserved: Vec::new(),
+            extensions: Vec::new(),
+            comments: Comments::default(),
+            range: None,
         }
     }
 }
@@ -541,6 +929,15 @@ impl UniqueId for MessageDeclaration {
 impl std::fmt::Display for MessageDeclaration {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "message {} {{\n", self.name)?;
+        for option in &self.options {
+            writeln!(f, "  option {};", option)?;
+        }
+        for reserved in &self.reserved {
+            writeln!(f, "  {}", reserved)?;
+        }
+        for extensions in &self.extensions {
+            writeln!(f, "  {}", extensions)?;
+        }
         for entry in &self.entries {
             let entry_str = format!("{}", entry);
             let lines = entry_str.lines();
@@ -552,10 +949,111 @@ impl std::fmt::Display for MessageDeclaration {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MethodDeclaration {
+    pub name: Rc<str>,
+    pub input_type_ref: FieldTypeReference,
+    pub output_type_ref: FieldTypeReference,
+    pub client_streaming: bool,
+    pub server_streaming: bool,
+    pub options: Vec<ProtoOption>,
+    /// Range of the name, missing for built-in declarations
+    pub range: Option<SourceRange>,
+}
+
+impl std::fmt::Display for MethodDeclaration {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let stream = |streaming: bool| if streaming { "stream " } else { "" };
+        write!(
+            f,
+            "rpc {}({}{}) returns ({}{})",
+            self.name,
+            stream(self.client_streaming),
+            self.input_type_ref,
+            stream(self.server_streaming),
+            self.output_type_ref
+        )?;
+        if self.options.is_empty() {
+            return write!(f, ";");
+        }
+        writeln!(f, " {{")?;
+        for option in &self.options {
+            writeln!(f, "  option {};", option)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Method {
+    pub name: Rc<str>,
+    pub input_type: Type,
+    pub output_type: Type,
+    pub client_streaming: bool,
+    pub server_streaming: bool,
+    pub options: Vec<ProtoOption>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ServiceDeclaration {
+    pub name: Rc<str>,
+    pub methods: Vec<MethodDeclaration>,
+    pub options: Vec<ProtoOption>,
+    /// Range of the name
+    pub range: Option<SourceRange>,
+}
+
+impl ServiceDeclaration {
+    pub fn new(name: Rc<str>) -> Self {
+        ServiceDeclaration {
+            name,
+            methods: Vec::new(),
+            options: Vec::new(),
+            range: None,
+        }
+    }
+}
+
+impl std::fmt::Display for ServiceDeclaration {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "service {} {{", self.name)?;
+        for option in &self.options {
+            writeln!(f, "  option {};", option)?;
+        }
+        for method in &self.methods {
+            let method_str = format!("{}", method);
+            for line in method_str.lines() {
+                writeln!(f, "  {}", line)?;
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+/// `extend Foo { ... }` block adding fields to a message declared elsewhere
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ExtendDeclaration {
+    pub extendee: FieldTypeReference,
+    pub fields: Vec<FieldDeclaration>,
+    pub range: SourceRange,
+}
+
+impl std::fmt::Display for ExtendDeclaration {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "extend {} {{", self.extendee)?;
+        for field in &self.fields {
+            writeln!(f, "  {};", field)?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum Declaration {
     Enum(EnumDeclaration),
     Message(MessageDeclaration),
+    Service(ServiceDeclaration),
+    Extend(ExtendDeclaration),
 }
 
 impl From<EnumDeclaration> for Declaration {
@@ -568,6 +1066,16 @@ impl From<MessageDeclaration> for Declaration {
         Declaration::Message(decl)
     }
 }
+impl From<ServiceDeclaration> for Declaration {
+    fn from(decl: ServiceDeclaration) -> Self {
+        Declaration::Service(decl)
+    }
+}
+impl From<ExtendDeclaration> for Declaration {
+    fn from(decl: ExtendDeclaration) -> Self {
+        Declaration::Extend(decl)
+    }
+}
 
 impl std::fmt::Display for Declaration {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -575,18 +1083,47 @@ impl std::fmt::Display for Declaration {
         match self {
             Enum(e) => write!(f, "{}", e),
             Message(m) => write!(f, "{}", m),
+            Service(s) => write!(f, "{}", s),
+            Extend(e) => write!(f, "{}", e),
         }
     }
 }
 
-#[derive(Debug)]
+/// Modifier of `import` statement
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum ImportKind {
+    #[default]
+    Default,
+    /// `import public`: symbols are visible to files importing the importer
+    Public,
+    /// `import weak`: resolved as plain import, the dependency is optional at runtime
+    Weak,
+}
+
+impl Display for ImportKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportKind::Default => Ok(()),
+            ImportKind::Public => write!(f, "public "),
+            ImportKind::Weak => write!(f, "weak "),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub(crate) struct ImportPath {
     pub file_name: Rc<str>,
     pub packages: Vec<Rc<str>>,
+    pub kind: ImportKind,
+    /// Missing for imports of built-in files
+    pub range: Option<SourceRange>,
 }
 
 impl Display for ImportPath {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.packages.is_empty() {
+            return write!(f, "{}", self.file_name);
+        }
         write!(f, "{}/{}", self.packages.join("/"), self.file_name)
     }
 }
@@ -622,11 +1159,17 @@ pub(crate) struct ProtoFile {
     pub imports: Vec<ImportPath>,
     pub path: Vec<Rc<str>>,
     pub name: Rc<str>,
+    /// Path which imports refer to the file by, e.g. `foo/bar.proto`
+    pub import_path: Rc<str>,
+    pub options: Vec<ProtoOption>,
 }
 
 impl std::fmt::Display for ProtoFile {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "syntax = \"{}\";\n", self.version)?;
+        match self.version {
+            ProtoVersion::Edition2023 => writeln!(f, "edition = \"{}\";", self.version)?,
+            _ => writeln!(f, "syntax = \"{}\";", self.version)?,
+        }
 
         let ref imports = self.imports;
         if !imports.is_empty() {
@@ -634,7 +1177,13 @@ impl std::fmt::Display for ProtoFile {
             for imprt in imports {
                 let ref packages = imprt.packages;
                 let ref file_name = imprt.file_name;
-                write!(f, "import \"{}/{}\";\n", packages.join("/"), file_name)?;
+                writeln!(
+                    f,
+                    "import {}\"{}/{}\";",
+                    imprt.kind,
+                    packages.join("/"),
+                    file_name
+                )?;
             }
         }
 
@@ -642,6 +1191,13 @@ impl std::fmt::Display for ProtoFile {
             write!(f, "\npackage {};\n", self.path.join("."))?;
         }
 
+        if !self.options.is_empty() {
+            writeln!(f)?;
+            for option in &self.options {
+                writeln!(f, "option {};", option)?;
+            }
+        }
+
         for decl in &self.declarations {
             writeln!(f)?;
             writeln!(f, "{}", decl)?;
@@ -651,26 +1207,127 @@ impl std::fmt::Display for ProtoFile {
     }
 }
 
-pub(crate) fn read_root_scope(files: &[PathBuf]) -> Result<RootScope, ProtoError> {
+/// Reads input `files` and the files they import from `proto_paths`.
+/// Imported files are only used for resolution and are not compiled
+pub(crate) fn read_root_scope(
+    files: &[PathBuf],
+    proto_paths: &[PathBuf],
+) -> Result<RootScope, ProtoError> {
     let builder = ScopeBuilder::new_ref();
     let mut id_generator = IdGenerator::new();
+    let mut proto_files = Vec::new();
+    let mut errors = Vec::new();
+    let mut missing_imports = Vec::new();
+    let mut loaded_paths = HashSet::new();
+    // File paths with import paths and whether the file is compiled
+    let mut queue = Vec::new();
     for file in files {
-        let proto_file = read_proto_file(&mut id_generator, file)?;
-        for imprt in proto_file
-            .imports
-            .iter()
-            .filter(|imp| is_well_known_import(imp))
-        {
-            builder.load_well_known(&mut id_generator, &imprt.file_name);
+        if loaded_paths.insert(canonical_path(file)) {
+            if let Err(error) = check_input_path(proto_paths, file) {
+                errors.push(error);
+            }
+            let import_path = match find_import_path(proto_paths, file) {
+                Some((_, import_path)) => import_path,
+                None => file_name(file),
+            };
+            queue.push((file.clone(), import_path, true));
+        }
+    }
+    let mut index = 0;
+    while index < queue.len() {
+        let (file, import_path, generated) = queue[index].clone();
+        index += 1;
+        let proto_file = match read_proto_file(&mut id_generator, &file, &import_path) {
+            Ok(proto_file) => proto_file,
+            Err(error) => {
+                errors.push(error);
+                continue;
+            }
+        };
+        for imprt in &proto_file.imports {
+            if is_well_known_import(imprt) {
+                builder.load_well_known(&mut id_generator, &imprt.file_name);
+                continue;
+            }
+            match find_imported_files(proto_paths, &imprt.to_string()).first() {
+                Some(imported) => {
+                    if loaded_paths.insert(canonical_path(imported)) {
+                        queue.push((imported.clone(), imprt.to_string(), false));
+                    }
+                }
+                None => missing_imports.push(imprt.clone()),
+            }
+        }
+        proto_files.push((proto_file, generated));
+    }
+    // Imports are resolved by packages of loaded files as well,
+    // so only ones matching none of them are missing
+    for imprt in missing_imports {
+        let loaded = proto_files.iter().any(|(proto_file, _)| {
+            proto_file.path == imprt.packages && proto_file.name == imprt.file_name
+        });
+        if !loaded {
+            errors.push(located_error(
+                ErrorCode::UnresolvedImport,
+                format!(
+                    "Cannot find import {} in proto paths {}",
+                    imprt,
+                    proto_paths
+                        .iter()
+                        .map(|proto_path| proto_path.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                imprt.range.as_ref(),
+            ));
+        }
+    }
+    // Syntax errors of all files are reported before building scopes
+    if let Some(error) = ProtoError::from_errors(errors) {
+        return Err(error);
+    }
+    for (proto_file, generated) in proto_files {
+        if generated {
+            builder.load(proto_file)?;
+        } else {
+            builder.load_dependency(proto_file)?;
         }
-        builder.load(proto_file)?;
     }
     builder.finish()
 }
 
+/// Input file must be in one of proto paths and must not be hidden
+/// by a file with the same import path in preceding proto path
+fn check_input_path(proto_paths: &[PathBuf], file: &Path) -> Result<(), ProtoError> {
+    let (index, import_path) = match find_import_path(proto_paths, file) {
+        Some(res) => res,
+        None => {
+            return Err(ProtoError::new(&format!(
+                "Input {} is not in any of proto paths",
+                file.display()
+            )))
+        }
+    };
+    match find_imported_files(&proto_paths[..index], &import_path).first() {
+        Some(shadowing) => Err(ProtoError::new(&format!(
+            "Input {} is shadowed by {} with the same import path {}, \
+             reorder proto paths or use the latter file as input",
+            file.display(),
+            shadowing.display(),
+            import_path
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn canonical_path(file: &Path) -> PathBuf {
+    file.canonicalize().unwrap_or_else(|_| file.to_path_buf())
+}
+
 fn read_proto_file(
     id_generator: &mut IdGenerator,
     file_path: &PathBuf,
+    import_path: &str,
 ) -> Result<ProtoFile, ProtoError> {
     let content = read_file_content(file_path)?;
 
@@ -678,14 +1335,14 @@ fn read_proto_file(
 
     let lexems = read_lexems(&*relative_file_path, content.as_str())?;
 
-    let file_name = file_path.file_name().unwrap().to_str().unwrap().to_string();
-
     let mut res = ProtoFile {
         version: super::package::ProtoVersion::Proto2,
         declarations: vec![],
         imports: vec![],
         path: vec![],
-        name: file_name.into(),
+        name: file_name(file_path).into(),
+        import_path: import_path.into(),
+        options: vec![],
     };
 
     parse_package(id_generator, &lexems, &mut res)?;
@@ -693,6 +1350,10 @@ fn read_proto_file(
     Ok(res)
 }
 
+fn file_name(file_path: &Path) -> String {
+    file_path.file_name().unwrap().to_str().unwrap().to_string()
+}
+
 fn get_relative_path(file_path: &PathBuf) -> String {
     let cur_dir = std::env::current_dir().unwrap();
     let relative_file_path = relative_file_path(&cur_dir, file_path);
@@ -701,45 +1362,193 @@ fn get_relative_path(file_path: &PathBuf) -> String {
 
 fn read_file_content(file_path: &PathBuf) -> Result<String, ProtoError> {
     let mut content = String::new();
-    let mut file = std::fs::File::open(file_path).map_err(ProtoError::CannotOpenFile)?;
+    let mut file = std::fs::File::open(file_path).map_err(|error| ProtoError::CannotOpenFile {
+        path: file_path.display().to_string().into(),
+        error,
+    })?;
 
     file.read_to_string(&mut content)
-        .map_err(ProtoError::IOError)?;
+        .map_err(|error| ProtoError::io(file_path, error))?;
 
     Ok(content)
 }
 
-fn relative_file_path(cur_dir: &PathBuf, file_path: &PathBuf) -> String {
-    let cur_dir_cannonical = cur_dir.canonicalize().unwrap();
-    let mut cur_dir_comps = cur_dir_cannonical.components();
-    let file_path_canonical = file_path.canonicalize().unwrap();
-    let mut file_path_components = file_path_canonical.components();
-    let mut res = String::new();
-    res.push_str(".");
-    loop {
-        let left = cur_dir_comps.next();
-        let right = file_path_components.next();
-        if right.is_none() {
-            break;
-        }
-        if left.is_none() {
-            if let std::path::Component::Normal(x) = right.unwrap() {
-                res.push_str("/");
-                res.push_str(x.to_str().unwrap());
-            } else {
-                todo!();
+/// Path of the file shown in errors, relative to the current folder when the file is
+/// inside of it, e.g. `./proto/a.proto`, otherwise the path as given, e.g. `../third/a.proto`
+fn relative_file_path(cur_dir: &Path, file_path: &Path) -> String {
+    match canonical_path(file_path).strip_prefix(canonical_path(cur_dir)) {
+        Ok(relative) => {
+            let mut res = String::from(".");
+            for component in relative.components() {
+                res.push('/');
+                res.push_str(&component.as_os_str().to_string_lossy());
             }
-            break;
+            res
+        }
+        Err(_) => file_path.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use crate::proto::{error::ProtoError, proto_scope::root_scope::RootScope};
+
+    /// Writes `files`, pairs of path and content, into a fresh folder under `target`.
+    /// Files have to be inside of the current folder, which is the crate folder for tests
+    fn write_files(test_name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let folder = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("target")
+            .join("proto-path-tests")
+            .join(test_name);
+        write_files_into(folder, files)
+    }
+
+    fn write_files_into(folder: PathBuf, files: &[(&str, &str)]) -> PathBuf {
+        if folder.exists() {
+            fs::remove_dir_all(&folder).unwrap();
         }
-        if left != right {
-            todo!();
+        for (path, content) in files {
+            let file = folder.join(path);
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, content).unwrap();
         }
+        folder
+    }
+
+    fn read(folder: &Path, input: &str, proto_paths: &[&str]) -> Result<RootScope, ProtoError> {
+        let proto_paths = proto_paths
+            .iter()
+            .map(|proto_path| folder.join(proto_path))
+            .collect::<Vec<_>>();
+        super::read_root_scope(&[folder.join(input)], &proto_paths)
     }
 
-    while let Some(std::path::Component::Normal(x)) = file_path_components.next() {
-        res.push_str("/");
-        res.push_str(x.to_str().unwrap());
+    fn type_names(root: &RootScope) -> Vec<String> {
+        let mut names = root
+            .types
+            .values()
+            .map(|path| path.join("."))
+            .collect::<Vec<_>>();
+        names.sort();
+        names
     }
 
-    res
+    #[test]
+    fn resolves_imports_in_all_proto_paths() {
+        let folder = write_files(
+            "all_proto_paths",
+            &[
+                (
+                    "main/a.proto",
+                    r#"syntax = "proto3";
+                    import "b/b.proto";
+                    import "c.proto";
+                    message A { b.B b = 1; C c = 2; }"#,
+                ),
+                (
+                    "deps/b/b.proto",
+                    r#"syntax = "proto3"; package b; message B {}"#,
+                ),
+                ("vendor/c.proto", r#"syntax = "proto3"; message C {}"#),
+            ],
+        );
+        let root = read(&folder, "main/a.proto", &["main", "deps", "vendor"]).unwrap();
+        assert!(root.warnings.is_empty());
+        assert_eq!(
+            type_names(&root),
+            vec!["a.proto.A", "b.b.proto.B", "c.proto.C"]
+        );
+    }
+
+    #[test]
+    fn prefers_first_proto_path() {
+        let folder = write_files(
+            "first_proto_path",
+            &[
+                (
+                    "main/a.proto",
+                    r#"syntax = "proto3"; import "b.proto"; message A { first.B b = 1; }"#,
+                ),
+                (
+                    "first/b.proto",
+                    r#"syntax = "proto3"; package first; message B {}"#,
+                ),
+                (
+                    "second/b.proto",
+                    r#"syntax = "proto3"; package second; message B {}"#,
+                ),
+            ],
+        );
+        let root = read(&folder, "main/a.proto", &["main", "first", "second"]).unwrap();
+        assert_eq!(type_names(&root), vec!["a.proto.A", "first.b.proto.B"]);
+
+        let error = read(&folder, "main/a.proto", &["main", "second", "first"]).unwrap_err();
+        assert!(error.to_string().contains("first.B"));
+    }
+
+    #[test]
+    fn binds_import_to_file_with_package_unlike_its_folder() {
+        let folder = write_files(
+            "package_unlike_folder",
+            &[
+                (
+                    "a.proto",
+                    r#"syntax = "proto3";
+                    import "b/b.proto";
+                    message A { other.pkg.B b = 1; }"#,
+                ),
+                (
+                    "b/b.proto",
+                    r#"syntax = "proto3"; package other.pkg; message B {}"#,
+                ),
+            ],
+        );
+        let root = read(&folder, "a.proto", &["."]).unwrap();
+        assert!(root.warnings.is_empty());
+        assert_eq!(type_names(&root), vec!["a.proto.A", "other.pkg.b.proto.B"]);
+    }
+
+    #[test]
+    fn reads_proto_paths_outside_of_current_folder() {
+        // The current folder of tests is the crate folder
+        let folder = write_files_into(
+            std::env::temp_dir()
+                .join("protos-ts-tests")
+                .join("outside_proto_path"),
+            &[
+                (
+                    "service/s.proto",
+                    r#"syntax = "proto3"; import "lib/p.proto"; message S { lib.P p = 1; }"#,
+                ),
+                (
+                    "third/lib/p.proto",
+                    r#"syntax = "proto3"; package lib; message P { Missing m = 1; }"#,
+                ),
+            ],
+        );
+        let error = read(&folder, "service/s.proto", &["service", "third"]).unwrap_err();
+        assert!(error.to_string().contains(
+            &folder
+                .join("third")
+                .join("lib/p.proto")
+                .display()
+                .to_string()
+        ));
+
+        let service = folder.join("service");
+        let sibling = service.join("../third/lib/p.proto");
+        assert_eq!(
+            super::relative_file_path(&service, &service.join("s.proto")),
+            "./s.proto"
+        );
+        assert_eq!(
+            super::relative_file_path(&service, &sibling),
+            sibling.display().to_string()
+        );
+    }
 }
@@ -2,7 +2,7 @@ use std::{rc::Rc, fmt::Formatter};
 
 use self::{
     enum_scope::EnumScope, file::FileScope, message::MessageScope, package::PackageScope,
-    root_scope::RootScope, traits::ChildrenScopes,
+    root_scope::RootScope, service::ServiceScope, traits::ChildrenScopes,
 };
 
 use super::protopath::PathComponent;
@@ -13,6 +13,7 @@ pub(super) mod file;
 pub(super) mod message;
 pub(super) mod package;
 pub(crate) mod root_scope;
+pub(super) mod service;
 pub(super) mod traits;
 
 #[derive(Debug)]
@@ -22,6 +23,7 @@ pub(crate) enum ProtoScope {
     File(FileScope),
     Enum(EnumScope),
     Message(MessageScope),
+    Service(ServiceScope),
 }
 
 impl ProtoScope {
@@ -32,6 +34,7 @@ impl ProtoScope {
             ProtoScope::File(_) => None,
             ProtoScope::Enum(e) => Some(e.id),
             ProtoScope::Message(m) => Some(m.id),
+            ProtoScope::Service(_) => None,
         }
     }
     pub fn as_path_component(&self) -> PathComponent {
@@ -41,6 +44,7 @@ impl ProtoScope {
             ProtoScope::File(f) => PathComponent::File(Rc::clone(&f.name)),
             ProtoScope::Enum(e) => PathComponent::Enum(Rc::clone(&e.name)),
             ProtoScope::Message(m) => PathComponent::Message(Rc::clone(&m.name)),
+            ProtoScope::Service(_) => unreachable!(),
         }
     }
     pub fn get_message_declaration(&self) -> Option<&MessageScope> {
@@ -50,6 +54,7 @@ impl ProtoScope {
             ProtoScope::File(_) => None,
             ProtoScope::Enum(_) => None,
             ProtoScope::Message(m) => Some(m),
+            ProtoScope::Service(_) => None,
         }
     }
     pub fn name(&self) -> Rc<str> {
@@ -59,6 +64,7 @@ impl ProtoScope {
             ProtoScope::File(f) => Rc::clone(&f.name),
             ProtoScope::Enum(e) => Rc::clone(&e.name),
             ProtoScope::Message(m) => Rc::clone(&m.name),
+            ProtoScope::Service(s) => Rc::clone(&s.name),
         }
     }
 }
@@ -93,6 +99,12 @@ impl From<MessageScope> for ProtoScope {
     }
 }
 
+impl From<ServiceScope> for ProtoScope {
+    fn from(service_scope: ServiceScope) -> Self {
+        ProtoScope::Service(service_scope)
+    }
+}
+
 impl ChildrenScopes for ProtoScope {
     fn children(&self) -> &[Rc<ProtoScope>] {
         match self {
@@ -101,6 +113,7 @@ impl ChildrenScopes for ProtoScope {
             ProtoScope::File(file) => file.children(),
             ProtoScope::Enum(enum_scope) => enum_scope.children(),
             ProtoScope::Message(message_scope) => message_scope.children(),
+            ProtoScope::Service(service_scope) => service_scope.children(),
         }
     }
 }
@@ -113,6 +126,7 @@ impl std::fmt::Display for ProtoScope {
             ProtoScope::File(file) => file.fmt(f),
             ProtoScope::Enum(_) => todo!(),
             ProtoScope::Message(m) => m.fmt(f),
+            ProtoScope::Service(s) => s.fmt(f),
         }
     }
 }
@@ -2,18 +2,21 @@ pub(crate) mod well_known;
 
 use std::{
     cell::RefCell,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    iter,
     ops::Deref,
     rc::{Rc, Weak},
 };
 
 use crate::proto::{
-    error::ProtoError,
+    error::{located_error, validation_error, ErrorCode, ProtoError},
     id_generator::{IdGenerator, UniqueId},
+    lexems::SourceRange,
     package::{
-        Declaration, EnumDeclaration, Field, FieldDeclaration, FieldTypeReference, ImportPath,
-        MessageDeclaration, MessageDeclarationEntry, MessageEntry, OneOfDeclaration, OneOfGroup,
-        ProtoFile, Type,
+        find_option, Comments, Declaration, EnumDeclaration, ExtendDeclaration, Extension, Field,
+        FieldDeclaration, FieldTypeReference, ImportKind, ImportPath, MessageDeclaration,
+        MessageDeclarationEntry, MessageEntry, Method, OneOfDeclaration, OneOfGroup, OptionValue,
+        ProtoFile, ServiceDeclaration, TagRange, Type,
     },
 };
 
@@ -21,7 +24,7 @@ use self::well_known::create_well_known_file;
 
 use super::{
     enum_scope::EnumScope, file::FileScope, message::MessageScope, package::PackageScope,
-    root_scope::RootScope, ProtoScope,
+    root_scope::RootScope, service::ServiceScope, traits::ChildrenScopes, ProtoScope,
 };
 
 #[derive(Debug)]
@@ -32,7 +35,12 @@ struct PackageData {
 #[derive(Debug)]
 struct FileData {
     name: Rc<str>,
+    /// Path which imports refer to the file by, relative to its proto path
+    import_path: Rc<str>,
     imports: Vec<ImportPath>,
+    extends: Vec<ExtendDeclaration>,
+    /// False for files loaded only to resolve imports
+    generated: bool,
 }
 
 #[derive(Debug)]
@@ -57,6 +65,11 @@ struct MessageData {
     id: usize,
     name: Rc<str>,
     fields: Vec<FieldOrOneOf>,
+    extension_ranges: Vec<TagRange>,
+    extends: Vec<ExtendDeclaration>,
+    comments: Comments,
+    /// Range of the name, missing for built-in declarations
+    range: Option<SourceRange>,
 }
 
 impl UniqueId for MessageData {
@@ -67,6 +80,10 @@ impl UniqueId for MessageData {
             id,
             name: args.0,
             fields: args.1,
+            extension_ranges: Vec::new(),
+            extends: Vec::new(),
+            comments: Comments::default(),
+            range: None,
         }
     }
 }
@@ -77,6 +94,7 @@ enum ScopeData {
     File(FileData),
     Enum(EnumDeclaration),
     Message(MessageData),
+    Service(ServiceDeclaration),
 }
 
 impl ScopeData {
@@ -87,6 +105,7 @@ impl ScopeData {
             ScopeData::File(p) => Some(Rc::clone(&p.name)),
             ScopeData::Enum(p) => Some(Rc::clone(&p.name)),
             ScopeData::Message(p) => Some(Rc::clone(&p.name)),
+            ScopeData::Service(p) => Some(Rc::clone(&p.name)),
         }
     }
     fn id(&self) -> Option<usize> {
@@ -96,6 +115,7 @@ impl ScopeData {
             ScopeData::File(_) => None,
             ScopeData::Enum(e) => Some(e.id),
             ScopeData::Message(m) => Some(m.id),
+            ScopeData::Service(_) => None,
         }
     }
     fn is_root(&self) -> bool {
@@ -123,6 +143,7 @@ impl std::fmt::Display for ScopeData {
             ScopeData::File(data) => write!(f, "{}", data.name),
             ScopeData::Enum(data) => write!(f, "Enum {}", data.name),
             ScopeData::Message(data) => write!(f, "Message {}", data.name),
+            ScopeData::Service(data) => write!(f, "Service {}", data.name),
         }
     }
 }
@@ -135,6 +156,7 @@ impl std::fmt::Debug for ScopeData {
             Self::File(file) => file.fmt(f),
             Self::Enum(e) => e.fmt(f),
             Self::Message(m) => m.fmt(f),
+            Self::Service(s) => s.fmt(f),
         }
     }
 }
@@ -171,27 +193,31 @@ impl ScopeBuilder {
             .for_parent(|p| p.get_builder_by_absolute_path(path))
             .flatten();
     }
-    fn matches(&self, full_path: &[Rc<str>]) -> bool {
-        if self.is_root() {
-            return false;
+    /// Fully qualified protobuf name of the scope, files are not part of it
+    fn full_name(&self) -> Vec<Rc<str>> {
+        let mut res = self.for_parent(|p| p.full_name()).unwrap_or_default();
+        if !self.is_file() {
+            if let Some(name) = self.name() {
+                res.push(name);
+            }
         }
+        res
+    }
+
+    /// Absolute path of the file containing the scope
+    fn file_path(&self) -> Option<Vec<Rc<str>>> {
         if self.is_file() {
-            return self
-                .for_parent(|parent| parent.matches(&full_path))
-                .unwrap_or(false);
+            return Some(self.path());
         }
-        if full_path.len() == 0 {
-            return false;
-        }
-        let self_name = self.name().unwrap();
-        if full_path.len() == 1 {
-            return self_name == full_path[0];
+        self.for_parent(|p| p.file_path()).flatten()
+    }
+
+    /// Path which imports refer to the file containing the scope by
+    fn import_path(&self) -> Option<Rc<str>> {
+        if let ScopeData::File(f) = &self.data {
+            return Some(Rc::clone(&f.import_path));
         }
-        let last_name = Rc::clone(&full_path[full_path.len() - 1]);
-        last_name == self_name
-            && self
-                .for_parent(|parent| parent.matches(&full_path[..full_path.len() - 1]))
-                .unwrap_or(false)
+        self.for_parent(|p| p.import_path()).flatten()
     }
 
     fn get_by_path(&self, path: &[Rc<str>]) -> Option<Rc<RefCell<ScopeBuilder>>> {
@@ -244,23 +270,6 @@ impl ScopeBuilder {
         }
     }
 
-    fn get_all_declaration_builders(&self) -> Vec<Rc<RefCell<ScopeBuilder>>> {
-        let mut res = Vec::new();
-        for child_ref in &self.children {
-            let child_is_declaration = {
-                let child = child_ref.borrow();
-                child.is_message() || child.is_enum()
-            };
-            if child_is_declaration {
-                res.push(Rc::clone(child_ref));
-            }
-            {
-                let child_types = child_ref.borrow().get_all_declaration_builders();
-                res.extend(child_types);
-            }
-        }
-        return res;
-    }
     fn is_package(&self) -> bool {
         self.data.is_package()
     }
@@ -326,11 +335,19 @@ impl ScopeBuilder {
     }
     fn new_file(
         name: Rc<str>,
+        import_path: Rc<str>,
         imports: Vec<ImportPath>,
+        generated: bool,
         parent: Rc<RefCell<ScopeBuilder>>,
     ) -> Self {
         Self {
-            data: ScopeData::File(FileData { name, imports }),
+            data: ScopeData::File(FileData {
+                name,
+                import_path,
+                imports,
+                extends: Vec::new(),
+                generated,
+            }),
             children: Vec::new(),
             parent: Some(Rc::downgrade(&parent)),
         }
@@ -340,10 +357,22 @@ impl ScopeBuilder {
         id: usize,
         name: Rc<str>,
         fields: Vec<FieldOrOneOf>,
+        extension_ranges: Vec<TagRange>,
+        extends: Vec<ExtendDeclaration>,
+        comments: Comments,
+        range: Option<SourceRange>,
         parent: Rc<RefCell<ScopeBuilder>>,
     ) -> Self {
         Self {
-            data: ScopeData::Message(MessageData { name, fields, id }),
+            data: ScopeData::Message(MessageData {
+                name,
+                fields,
+                id,
+                extension_ranges,
+                extends,
+                comments,
+                range,
+            }),
             children: Vec::new(),
             parent: Some(Rc::downgrade(&parent)),
         }
@@ -357,6 +386,14 @@ impl ScopeBuilder {
         }
     }
 
+    fn new_service(s: ServiceDeclaration, parent: Rc<RefCell<ScopeBuilder>>) -> Self {
+        Self {
+            data: ScopeData::Service(s),
+            children: Vec::new(),
+            parent: Some(Rc::downgrade(&parent)),
+        }
+    }
+
     pub(crate) fn is_file_with_name(&self, name: &str) -> bool {
         match &self.data {
             ScopeData::File(f) => f.name.deref() == name,
@@ -367,21 +404,34 @@ impl ScopeBuilder {
 
 pub(crate) trait ScopeBuilderTrait {
     fn load(&self, file: ProtoFile) -> Result<(), ProtoError>;
+    /// Loads file which is imported, but not compiled
+    fn load_dependency(&self, file: ProtoFile) -> Result<(), ProtoError>;
     fn load_well_known(&self, id_gen: &mut IdGenerator, file_name: &str);
     fn finish(self) -> Result<RootScope, ProtoError>;
 }
 
 trait ScopeBuilderPrivate {
-    fn load_file(&self, file: ProtoFile, package_path: &[Rc<str>]) -> Result<(), ProtoError>;
+    fn load_file(
+        &self,
+        file: ProtoFile,
+        package_path: &[Rc<str>],
+        generated: bool,
+    ) -> Result<(), ProtoError>;
     fn load_declaration(&self, declaration: Declaration) -> Result<(), ProtoError>;
     fn load_enum(&self, enum_declaration: EnumDeclaration) -> Result<(), ProtoError>;
     fn load_message(&self, message_declaration: MessageDeclaration) -> Result<(), ProtoError>;
+    fn load_service(&self, service_declaration: ServiceDeclaration) -> Result<(), ProtoError>;
 }
 
 impl ScopeBuilderTrait for Rc<RefCell<ScopeBuilder>> {
     fn load(&self, file: ProtoFile) -> Result<(), ProtoError> {
         let package_path = file.path.clone();
-        self.load_file(file, &package_path)
+        self.load_file(file, &package_path, true)
+    }
+
+    fn load_dependency(&self, file: ProtoFile) -> Result<(), ProtoError> {
+        let package_path = file.path.clone();
+        self.load_file(file, &package_path, false)
     }
 
     fn load_well_known(&self, id_gen: &mut IdGenerator, imp: &str) {
@@ -416,14 +466,25 @@ impl ScopeBuilderTrait for Rc<RefCell<ScopeBuilder>> {
     fn finish(self) -> Result<RootScope, ProtoError> {
         let root_builder = self.borrow();
         assert!(root_builder.is_root());
+        let mut files = Vec::new();
+        collect_files(&root_builder, &mut files);
+        check_duplicate_imports(&files)?;
+        check_import_cycles(&files)?;
         let mut children: Vec<Rc<ProtoScope>> = Vec::new();
         let mut types: HashMap<usize, Vec<Rc<str>>> = Default::default();
+        let mut errors = Vec::new();
 
         for child_ref in root_builder.children.iter() {
             let ResolveResult {
                 scope,
                 declaration_paths,
-            } = resolve(child_ref)?;
+            } = match resolve(child_ref) {
+                Ok(res) => res,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
             let name = scope.name();
             children.push(scope);
             for (id, mut path) in declaration_paths {
@@ -432,8 +493,23 @@ impl ScopeBuilderTrait for Rc<RefCell<ScopeBuilder>> {
                 types.insert(id, path);
             }
         }
+        if let Some(error) = ProtoError::from_errors(errors) {
+            return Err(error);
+        }
 
-        Ok(RootScope { children, types })
+        let mut usage = TypeUsage::default();
+        for child in &children {
+            collect_type_usage(child, &[], &mut usage);
+        }
+        let mut root = RootScope {
+            children,
+            types,
+            extensions: Default::default(),
+            warnings: find_unused_imports(&files, &usage),
+            options: Default::default(),
+        };
+        root.extensions = collect_extensions(&root)?;
+        Ok(root)
     }
 }
 
@@ -497,20 +573,82 @@ fn ensure_well_known_package(root_ref: &Rc<RefCell<ScopeBuilder>>) -> Rc<RefCell
     }
 }
 
+/// Groups extensions by extended message, checking that every extension
+/// number is declared in the `extensions` ranges of that message and is unique
+fn collect_extensions(root: &RootScope) -> Result<HashMap<usize, Vec<Rc<Extension>>>, ProtoError> {
+    let mut res: HashMap<usize, Vec<Rc<Extension>>> = HashMap::new();
+    let mut scopes: Vec<Rc<ProtoScope>> = root.children.clone();
+    while let Some(scope) = scopes.pop() {
+        scopes.extend(scope.children().iter().cloned());
+        let extensions = match scope.deref() {
+            ProtoScope::File(file) => &file.extensions,
+            ProtoScope::Message(message) => &message.extensions,
+            _ => continue,
+        };
+        for extension in extensions {
+            let field = &extension.field;
+            let extendee = root
+                .get_declaration(extension.extendee)
+                .expect("Extendee is resolved");
+            let extendee = extendee.get_message_declaration().unwrap();
+            let range = field.range.as_ref().unwrap();
+            if !extendee.extension_ranges.iter().any(|r| r.contains(field.tag)) {
+                return Err(validation_error(
+                    format!(
+                        "Extension {} uses number {} which is not in an extension range of {}",
+                        field.name, field.tag, extendee.name
+                    ),
+                    range,
+                )
+                .with_code(ErrorCode::ExtensionNotInRange));
+            }
+            let known = res.entry(extension.extendee).or_default();
+            if let Some(other) = known.iter().find(|e| e.field.tag == field.tag) {
+                let error = validation_error(
+                    format!(
+                        "Extension {} of {} uses number {} already used by extension {}",
+                        field.name, extendee.name, field.tag, other.field.name
+                    ),
+                    range,
+                )
+                .with_code(ErrorCode::DuplicateExtensionNumber);
+                return Err(match &other.field.range {
+                    Some(other_range) => error.with_label(other_range, "first used here"),
+                    None => error,
+                });
+            }
+            known.push(Rc::clone(extension));
+        }
+    }
+    for extensions in res.values_mut() {
+        extensions.sort_by_key(|e| e.field.tag);
+    }
+    Ok(res)
+}
+
 struct ResolveResult {
     scope: Rc<ProtoScope>,
     declaration_paths: Vec<(usize, Vec<Rc<str>>)>,
 }
 
+/// Resolves types referenced in the scope and its children,
+/// errors of all unresolved references are reported together
 fn resolve(builder_ref: &Rc<RefCell<ScopeBuilder>>) -> Result<ResolveResult, ProtoError> {
     let builder = builder_ref.borrow();
     let mut children: Vec<Rc<ProtoScope>> = Vec::new();
     let mut declaration_paths: Vec<(usize, Vec<Rc<str>>)> = Vec::new();
+    let mut errors = Vec::new();
     for child in &builder.children {
         let ResolveResult {
             scope,
             declaration_paths: declaration_scopes,
-        } = resolve(child)?;
+        } = match resolve(child) {
+            Ok(res) => res,
+            Err(error) => {
+                errors.push(error);
+                continue;
+            }
+        };
         let name = scope.name();
         children.push(scope);
         for (id, mut path) in declaration_scopes {
@@ -528,12 +666,21 @@ fn resolve(builder_ref: &Rc<RefCell<ScopeBuilder>>) -> Result<ResolveResult, Pro
         ScopeData::File(f) => Rc::new(ProtoScope::File(FileScope {
             children,
             name: Rc::clone(&f.name),
+            extensions: resolve_extends(&builder, &f.extends, &mut errors),
+            generated: f.generated,
         })),
         ScopeData::Enum(e) => {
             let enum_scope = Rc::new(ProtoScope::Enum(EnumScope {
                 id: e.id,
                 name: Rc::clone(&e.name),
                 entries: e.entries.clone(),
+                comments: e.comments.clone(),
+                closed: e.closed,
+                allow_alias: matches!(
+                    find_option(&e.options, "allow_alias"),
+                    Some(OptionValue::Bool(true))
+                ),
+                range: e.range.clone(),
             }));
 
             declaration_paths.push((e.id, vec![]));
@@ -545,30 +692,61 @@ fn resolve(builder_ref: &Rc<RefCell<ScopeBuilder>>) -> Result<ResolveResult, Pro
             for field in m.fields.iter() {
                 match field {
                     FieldOrOneOf::Field(f) => {
-                        let field_type = resolve_type(&builder, &f.field_type_ref)?;
+                        let field_type =
+                            match resolve_type(&builder, &f.field_type_ref, f.range.as_ref()) {
+                                Ok(field_type) => field_type,
+                                Err(error) => {
+                                    errors.push(error);
+                                    continue;
+                                }
+                            };
 
                         let entry = MessageEntry::Field(Field {
                             name: Rc::clone(&f.name),
+                            label: f.label,
+                            delimited: is_delimited(f, &field_type),
+                            packed: f.packed,
                             field_type: field_type,
                             tag: f.tag,
-                            attributes: f.attributes.clone(),
+                            options: f.options.clone(),
+                            range: f.range.clone(),
+                            comments: f.comments.clone(),
                         });
 
                         entries.push(entry);
                     }
                     FieldOrOneOf::OneOf(one_of_decl) => {
                         let name = Rc::clone(&one_of_decl.name);
-                        let mut options = Vec::new();
-                        for option in &one_of_decl.options {
-                            let field_type = resolve_type(&builder, &option.field_type_ref)?;
-                            options.push(Field {
-                                name: Rc::clone(&option.name),
+                        let mut fields = Vec::new();
+                        for field in &one_of_decl.fields {
+                            let field_type = match resolve_type(
+                                &builder,
+                                &field.field_type_ref,
+                                field.range.as_ref(),
+                            ) {
+                                Ok(field_type) => field_type,
+                                Err(error) => {
+                                    errors.push(error);
+                                    continue;
+                                }
+                            };
+                            fields.push(Field {
+                                name: Rc::clone(&field.name),
+                                label: field.label,
+                                delimited: is_delimited(field, &field_type),
+                                packed: field.packed,
                                 field_type: field_type,
-                                tag: option.tag,
-                                attributes: option.attributes.clone(),
+                                tag: field.tag,
+                                options: field.options.clone(),
+                                range: field.range.clone(),
+                                comments: field.comments.clone(),
                             });
                         }
-                        let entry = MessageEntry::OneOf(OneOfGroup { name, options });
+                        let entry = MessageEntry::OneOf(OneOfGroup {
+                            name,
+                            fields,
+                            comments: one_of_decl.comments.clone(),
+                        });
                         entries.push(entry)
                     }
                 }
@@ -578,11 +756,48 @@ fn resolve(builder_ref: &Rc<RefCell<ScopeBuilder>>) -> Result<ResolveResult, Pro
                 name: Rc::clone(&m.name),
                 children,
                 entries,
+                extension_ranges: m.extension_ranges.clone(),
+                extensions: resolve_extends(&builder, &m.extends, &mut errors),
+                comments: m.comments.clone(),
+                range: m.range.clone(),
             }));
             declaration_paths.push((m.id, vec![]));
             message_scope
         }
+        ScopeData::Service(s) => {
+            let mut methods: Vec<Method> = Vec::new();
+            for method in &s.methods {
+                let range = method.range.as_ref();
+                let input_type = resolve_method_type(&builder, &method.input_type_ref, range);
+                let output_type = resolve_method_type(&builder, &method.output_type_ref, range);
+                let (input_type, output_type) = match (input_type, output_type) {
+                    (Ok(input_type), Ok(output_type)) => (input_type, output_type),
+                    (input_type, output_type) => {
+                        errors.extend(input_type.err());
+                        errors.extend(output_type.err());
+                        continue;
+                    }
+                };
+                methods.push(Method {
+                    name: Rc::clone(&method.name),
+                    input_type,
+                    output_type,
+                    client_streaming: method.client_streaming,
+                    server_streaming: method.server_streaming,
+                    options: method.options.clone(),
+                });
+            }
+            Rc::new(ProtoScope::Service(ServiceScope {
+                name: Rc::clone(&s.name),
+                methods,
+                options: s.options.clone(),
+                range: s.range.clone(),
+            }))
+        }
     };
+    if let Some(error) = ProtoError::from_errors(errors) {
+        return Err(error);
+    }
 
     Ok(ResolveResult {
         scope: scope,
@@ -590,64 +805,456 @@ fn resolve(builder_ref: &Rc<RefCell<ScopeBuilder>>) -> Result<ResolveResult, Pro
     })
 }
 
+/// Resolves type referenced by declaration at `range`
 fn resolve_type(
     builder: &ScopeBuilder,
     field_type_ref: &FieldTypeReference,
+    range: Option<&SourceRange>,
 ) -> Result<Type, ProtoError> {
     let trivial = field_type_ref.trivial_resolve();
     if trivial.is_some() {
         return Ok(trivial.unwrap());
     }
     match field_type_ref {
-        FieldTypeReference::IdPath(ids) => resolve_full_path(builder, ids),
+        FieldTypeReference::IdPath(ids) => resolve_full_path(builder, ids, range),
+        FieldTypeReference::AbsoluteIdPath(ids) => resolve_absolute_path(builder, ids, range),
         FieldTypeReference::Repeated(v) => {
-            let value_type = resolve_type(builder, v)?;
+            let value_type = resolve_type(builder, v, range)?;
             return Ok(Type::Repeated(Rc::new(value_type)));
         }
         FieldTypeReference::Map(k, v) => {
-            let key_type = resolve_type(builder, k)?;
-            let value_type = resolve_type(builder, v)?;
+            let key_type = resolve_type(builder, k, range)?;
+            let value_type = resolve_type(builder, v, range)?;
             return Ok(Type::Map(Rc::new(key_type), Rc::new(value_type)));
         }
         _ => unreachable!(),
     }
 }
 
-fn resolve_full_path(builder: &ScopeBuilder, full_path: &[Rc<str>]) -> Result<Type, ProtoError> {
+/// Extensions of resolved extendees, `errors` get unresolved references
+fn resolve_extends(
+    builder: &ScopeBuilder,
+    extends: &[ExtendDeclaration],
+    errors: &mut Vec<ProtoError>,
+) -> Vec<Rc<Extension>> {
+    let mut res = Vec::new();
+    for extend in extends {
+        let extendee = match resolve_type(builder, &extend.extendee, Some(&extend.range)) {
+            Ok(Type::Message(id)) => id,
+            Ok(_) => {
+                errors.push(
+                    validation_error(
+                        format!("Extended type {} must be a message", extend.extendee),
+                        &extend.range,
+                    )
+                    .with_code(ErrorCode::InvalidTypeKind),
+                );
+                continue;
+            }
+            Err(error) => {
+                errors.push(error);
+                continue;
+            }
+        };
+        for field in &extend.fields {
+            let field_type =
+                match resolve_type(builder, &field.field_type_ref, field.range.as_ref()) {
+                    Ok(field_type) => field_type,
+                    Err(error) => {
+                        errors.push(error);
+                        continue;
+                    }
+                };
+            res.push(Rc::new(Extension {
+                extendee,
+                field: Field {
+                    name: Rc::clone(&field.name),
+                    label: field.label,
+                    delimited: is_delimited(field, &field_type),
+                    packed: field.packed,
+                    field_type,
+                    tag: field.tag,
+                    options: field.options.clone(),
+                    range: field.range.clone(),
+                    comments: field.comments.clone(),
+                },
+            }));
+        }
+    }
+    res
+}
+
+/// `features.message_encoding = DELIMITED` is inherited by all fields,
+/// but only message fields are encoded as groups
+fn is_delimited(field: &FieldDeclaration, field_type: &Type) -> bool {
+    let element_type = match field_type {
+        Type::Repeated(element_type) => element_type,
+        _ => field_type,
+    };
+    field.delimited && matches!(element_type, Type::Message(_))
+}
+
+fn resolve_method_type(
+    builder: &ScopeBuilder,
+    type_ref: &FieldTypeReference,
+    range: Option<&SourceRange>,
+) -> Result<Type, ProtoError> {
+    match resolve_type(builder, type_ref, range)? {
+        Type::Message(id) => Ok(Type::Message(id)),
+        _ => Err(located_error(
+            ErrorCode::InvalidTypeKind,
+            format!(
+                "Method type {} must be a message\n  in {}",
+                type_ref,
+                builder.name().unwrap_or("".into()),
+            ),
+            range,
+        )),
+    }
+}
+
+/// Symbol found by fully qualified name
+enum Symbol {
+    Package,
+    Type(Type),
+    /// Declaration which may contain other ones, but is not a type, i.e. service
+    Service,
+}
+
+/// Resolves relative reference the way protoc does: the first part of the name
+/// is searched in the innermost scope first, then in enclosing ones.
+/// The scope where the first part is found decides the meaning of the whole name
+fn resolve_full_path(
+    builder: &ScopeBuilder,
+    full_path: &[Rc<str>],
+    range: Option<&SourceRange>,
+) -> Result<Type, ProtoError> {
     if full_path.is_empty() {
         return Err(ProtoError::new("Cannot resolve empty full path"));
     }
-    let in_file_resolution = resolve_in_file(&builder, &full_path);
-    if in_file_resolution.is_some() {
-        return Ok(in_file_resolution.unwrap());
-    }
-    let imports = get_imports(&builder)?;
-    let imported_files = imports
-        .into_iter()
-        .map(|p| builder.get_builder_by_absolute_path(&p).unwrap());
-    for file_builder_ref in imported_files {
-        let file_builder = file_builder_ref.borrow();
-        let resolved = resolve_in_imported_file(&file_builder, &full_path);
-        if resolved.is_some() {
-            return Ok(resolved.unwrap());
+    let visible_files = get_visible_files(builder)?;
+    resolve_path_in_files(builder, &visible_files, full_path, range)
+        .map_err(|error| {
+            with_missing_import(builder, error, full_path.join("."), |files| {
+                resolve_path_in_files(builder, files, full_path, range).ok()
+            })
+        })
+        .map_err(|error| with_suggestions(builder, error, full_path))
+}
+
+fn resolve_path_in_files(
+    builder: &ScopeBuilder,
+    visible_files: &[Rc<RefCell<ScopeBuilder>>],
+    full_path: &[Rc<str>],
+    range: Option<&SourceRange>,
+) -> Result<Type, ProtoError> {
+    let mut scope = builder.full_name();
+    loop {
+        let mut candidate = scope.clone();
+        candidate.push(Rc::clone(&full_path[0]));
+        match find_symbol(visible_files, &candidate) {
+            Some(Symbol::Type(resolved)) if full_path.len() == 1 => return Ok(resolved),
+            // Every found symbol may contain the rest of the name
+            Some(_) if full_path.len() > 1 => {
+                candidate.extend(full_path[1..].iter().cloned());
+                match find_symbol(visible_files, &candidate) {
+                    Some(Symbol::Type(resolved)) => return Ok(resolved),
+                    Some(_) => return Err(not_a_type_error(&candidate, range)),
+                    // Reference is already fully qualified
+                    None if scope.is_empty() => break,
+                    None => {
+                        return Err(located_error(
+                            ErrorCode::UnresolvedType,
+                            format!(
+                                "{} is resolved to {}, which is not defined. \
+                                 The innermost scope is searched first in name resolution, \
+                                 use a leading dot (i.e. .{}) to start from the outermost scope",
+                                full_path.join("."),
+                                candidate.join("."),
+                                full_path.join(".")
+                            ),
+                            range,
+                        ))
+                    }
+                }
+            }
+            // Symbols which are not types are skipped
+            _ => {}
+        }
+        if scope.pop().is_none() {
+            break;
         }
     }
-
-    return Err(ProtoError::new(
+    Err(located_error(
+        ErrorCode::UnresolvedType,
         format!(
             "Cannot resolve {}\n  in {}",
-            &full_path[0],
+            full_path.join("."),
             builder.name().unwrap_or("".into()),
-        )
-        .as_str(),
-    ));
+        ),
+        range,
+    ))
+}
+
+/// Resolves fully qualified reference starting with `.`
+fn resolve_absolute_path(
+    builder: &ScopeBuilder,
+    full_path: &[Rc<str>],
+    range: Option<&SourceRange>,
+) -> Result<Type, ProtoError> {
+    let visible_files = get_visible_files(builder)?;
+    match find_symbol(&visible_files, full_path) {
+        Some(Symbol::Type(resolved)) => Ok(resolved),
+        Some(_) => Err(not_a_type_error(full_path, range)),
+        None => Err(with_missing_import(
+            builder,
+            located_error(
+                ErrorCode::UnresolvedType,
+                format!(
+                    "Cannot resolve .{}\n  in {}",
+                    full_path.join("."),
+                    builder.name().unwrap_or("".into()),
+                ),
+                range,
+            ),
+            format!(".{}", full_path.join(".")),
+            |files| match find_symbol(files, full_path) {
+                Some(Symbol::Type(resolved)) => Some(resolved),
+                _ => None,
+            },
+        ))
+        .map_err(|error| with_suggestions(builder, error, full_path)),
+    }
+}
+
+/// Replaces unresolved type error with the hint to import the file declaring the type,
+/// when `resolve` finds it in one of the loaded files
+fn with_missing_import<F>(
+    builder: &ScopeBuilder,
+    error: ProtoError,
+    name: String,
+    resolve: F,
+) -> ProtoError
+where
+    F: Fn(&[Rc<RefCell<ScopeBuilder>>]) -> Option<Type>,
+{
+    if error.code() != Some(ErrorCode::UnresolvedType) {
+        return error;
+    }
+    let files = get_all_files(builder);
+    let declaring_file = match resolve(&files) {
+        Some(Type::Message(id)) | Some(Type::Enum(id)) => files
+            .iter()
+            .find(|file_ref| declares(&file_ref.borrow(), id)),
+        _ => None,
+    };
+    match declaring_file {
+        Some(file_ref) => located_error(
+            ErrorCode::MissingImport,
+            format!(
+                "{} is declared in {}, which is not imported by {}",
+                name,
+                file_ref.borrow().import_path().unwrap_or_default(),
+                builder.import_path().unwrap_or_default(),
+            ),
+            error.range(),
+        ),
+        None => error,
+    }
+}
+
+/// Maximal number of names suggested for unresolved reference
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Adds names of loaded types similar to unresolved `full_path` to the error:
+/// ones ending with it, e.g. without package prefix, and ones with close spelling.
+/// Each suggestion tells the file declaring it and whether the file is imported
+fn with_suggestions(
+    builder: &ScopeBuilder,
+    error: ProtoError,
+    full_path: &[Rc<str>],
+) -> ProtoError {
+    if error.code() != Some(ErrorCode::UnresolvedType) {
+        return error;
+    }
+    let own_file_name = builder.import_path().unwrap_or_default();
+    let imported_file_names = get_imports(builder)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|path| builder.get_builder_by_absolute_path(path))
+        .filter_map(|file_ref| file_ref.borrow().import_path())
+        .collect::<Vec<_>>();
+    let mut names = Vec::new();
+    for file_ref in get_all_files(builder) {
+        let file = file_ref.borrow();
+        collect_type_names(&file, &file.import_path().unwrap_or_default(), &mut names);
+    }
+    let mut suggestions = names
+        .into_iter()
+        .filter_map(|(name, file_name)| {
+            Some((suggestion_distance(full_path, &name)?, name, file_name))
+        })
+        .collect::<Vec<_>>();
+    suggestions.sort();
+    suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .fold(error, |error, (_, name, file_name)| {
+            error.with_help(if file_name == own_file_name {
+                format!(
+                    "did you mean {}? It is declared in this file",
+                    name.join(".")
+                )
+            } else if imported_file_names.contains(&file_name) {
+                format!(
+                    "did you mean {}? It is declared in {}, which is imported",
+                    name.join("."),
+                    file_name
+                )
+            } else {
+                format!(
+                    "did you mean {}? It is declared in {}, which is not imported, \
+                     add import \"{}\" to use it",
+                    name.join("."),
+                    file_name,
+                    file_name
+                )
+            })
+        })
+}
+
+/// Pairs of fully qualified names of messages and enums and import paths of their files
+fn collect_type_names(
+    scope: &ScopeBuilder,
+    file_name: &Rc<str>,
+    res: &mut Vec<(Vec<Rc<str>>, Rc<str>)>,
+) {
+    for child_ref in &scope.children {
+        let child = child_ref.borrow();
+        if child.is_message() || child.is_enum() {
+            res.push((child.full_name(), Rc::clone(file_name)));
+            collect_type_names(&child, file_name, res);
+        }
+    }
+}
+
+/// Zero for names ending with the reference, edit distance of the last parts
+/// for names with close spelling and `None` for unrelated names
+fn suggestion_distance(reference: &[Rc<str>], name: &[Rc<str>]) -> Option<usize> {
+    if name.ends_with(reference) {
+        return Some(0);
+    }
+    let suffix = &name[name.len().saturating_sub(reference.len())..];
+    let reference = reference.join(".").to_lowercase();
+    let distance = edit_distance(&reference, &suffix.join(".").to_lowercase());
+    // Names differing only in case are still worse than exact matches
+    let distance = distance.max(1);
+    if distance <= (reference.chars().count() / 3).max(1) {
+        Some(distance)
+    } else {
+        None
+    }
+}
+
+/// Levenshtein distance counted in chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a_char != *b_char);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn declares(scope: &ScopeBuilder, id: usize) -> bool {
+    scope.id() == Some(id)
+        || scope
+            .children
+            .iter()
+            .any(|child_ref| declares(&child_ref.borrow(), id))
+}
+
+/// All loaded files including built-in ones
+fn get_all_files(builder: &ScopeBuilder) -> Vec<Rc<RefCell<ScopeBuilder>>> {
+    if !builder.is_root() {
+        return builder.for_parent(get_all_files).unwrap_or_default();
+    }
+    let mut res = Vec::new();
+    collect_files(builder, &mut res);
+    res
+}
+
+fn collect_files(scope: &ScopeBuilder, res: &mut Vec<Rc<RefCell<ScopeBuilder>>>) {
+    for child_ref in &scope.children {
+        let child = child_ref.borrow();
+        if child.is_file() {
+            res.push(Rc::clone(child_ref));
+        } else if child.is_package() {
+            collect_files(&child, res);
+        }
+    }
+}
+
+fn not_a_type_error(full_path: &[Rc<str>], range: Option<&SourceRange>) -> ProtoError {
+    located_error(
+        ErrorCode::InvalidTypeKind,
+        format!("{} is not a message or enum", full_path.join(".")),
+        range,
+    )
+}
+
+/// The file containing `builder` followed by the files it imports
+fn get_visible_files(builder: &ScopeBuilder) -> Result<Vec<Rc<RefCell<ScopeBuilder>>>, ProtoError> {
+    let mut paths = builder.file_path().into_iter().collect::<Vec<_>>();
+    paths.extend(get_imports(builder)?);
+    Ok(paths
+        .iter()
+        .filter_map(|path| builder.get_builder_by_absolute_path(path))
+        .collect())
 }
 
-fn resolve_in_imported_file(file_builder: &ScopeBuilder, full_path: &[Rc<str>]) -> Option<Type> {
-    for declaration_builder_ref in file_builder.get_all_declaration_builders() {
-        let declaration_builder = declaration_builder_ref.borrow();
-        if declaration_builder.matches(&full_path) {
-            return declaration_builder.get_type();
+/// Finds declaration or package with fully qualified `full_name` in one of `files`
+fn find_symbol(files: &[Rc<RefCell<ScopeBuilder>>], full_name: &[Rc<str>]) -> Option<Symbol> {
+    let mut is_package = false;
+    for file_ref in files {
+        let file = file_ref.borrow();
+        let package = file.full_name();
+        if full_name.len() <= package.len() {
+            is_package |= package.starts_with(full_name);
+            continue;
+        }
+        if !full_name.starts_with(&package) {
+            continue;
+        }
+        let found = find_declaration(&file, &full_name[package.len()..]);
+        if found.is_some() {
+            return found;
+        }
+    }
+    if is_package {
+        return Some(Symbol::Package);
+    }
+    None
+}
+
+fn find_declaration(scope: &ScopeBuilder, path: &[Rc<str>]) -> Option<Symbol> {
+    for child_ref in scope.resolve_child_by_name(&path[0]) {
+        let child = child_ref.borrow();
+        if path.len() == 1 {
+            return Some(match child.get_type() {
+                Some(resolved) => Symbol::Type(resolved),
+                None => Symbol::Service,
+            });
+        }
+        let found = find_declaration(&child, &path[1..]);
+        if found.is_some() {
+            return found;
         }
     }
     None
@@ -671,11 +1278,18 @@ fn get_imports(builder: &ScopeBuilder) -> Result<Vec<Vec<Rc<str>>>, ProtoError>
     let mut res = Vec::new();
 
     for import_decl in &data.imports {
-        match resolve_import(&builder, &import_decl.packages, &import_decl.file_name) {
-            Some(imprt) => res.push(imprt),
+        match resolve_import(builder, import_decl) {
+            Some(imprt) => {
+                if !res.contains(&imprt) {
+                    res.push(imprt.clone());
+                    push_public_imports(builder, &imprt, &mut res)?;
+                }
+            }
             None => {
-                return Err(ProtoError::new(
-                    format!("Cannot resolve import {}", import_decl).as_str(),
+                return Err(located_error(
+                    ErrorCode::UnresolvedImport,
+                    format!("Cannot resolve import {}", import_decl),
+                    import_decl.range.as_ref(),
                 ));
             }
         }
@@ -683,123 +1297,333 @@ fn get_imports(builder: &ScopeBuilder) -> Result<Vec<Vec<Rc<str>>>, ProtoError>
     Ok(res)
 }
 
-fn resolve_import(
+/// Appends files that `file_path` re-exports with `import public`, following chains of them
+fn push_public_imports(
+    builder: &ScopeBuilder,
+    file_path: &[Rc<str>],
+    res: &mut Vec<Vec<Rc<str>>>,
+) -> Result<(), ProtoError> {
+    let file_builder_ref = match builder.get_builder_by_absolute_path(file_path) {
+        Some(file_builder_ref) => file_builder_ref,
+        None => return Ok(()),
+    };
+    let file_builder = file_builder_ref.borrow();
+    let data = match &file_builder.data {
+        ScopeData::File(f) => f,
+        _ => return Ok(()),
+    };
+    for import_decl in data.imports.iter().filter(|i| i.kind == ImportKind::Public) {
+        match resolve_import(&file_builder, import_decl) {
+            Some(imprt) => {
+                if !res.contains(&imprt) {
+                    res.push(imprt.clone());
+                    push_public_imports(builder, &imprt, res)?;
+                }
+            }
+            None => {
+                return Err(located_error(
+                    ErrorCode::UnresolvedImport,
+                    format!(
+                        "Cannot resolve import {}\n  in {}",
+                        import_decl,
+                        file_builder.name().unwrap_or("".into())
+                    ),
+                    import_decl.range.as_ref(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Absolute path of the file loaded by the import path of `import_decl`,
+/// or of the file with matching package and name, when none is loaded by it
+fn resolve_import(builder: &ScopeBuilder, import_decl: &ImportPath) -> Option<Vec<Rc<str>>> {
+    let import_path = import_decl.to_string();
+    let loaded = get_all_files(builder).into_iter().find(|file_ref| {
+        matches!(&file_ref.borrow().data, ScopeData::File(f) if *f.import_path == import_path)
+    });
+    match loaded {
+        Some(file_ref) => Some(file_ref.borrow().path()),
+        None => resolve_import_by_package(builder, &import_decl.packages, &import_decl.file_name),
+    }
+}
+
+fn resolve_import_by_package(
     builder: &ScopeBuilder,
     packages: &[Rc<str>],
     file_name: &str,
 ) -> Option<Vec<Rc<str>>> {
     if packages.len() <= 0 {
         let children = builder.resolve_child_by_name(file_name);
-        if children.is_empty() {
-            return None;
-        }
         for child_ref in &children {
             let child = child_ref.borrow();
             if child.is_file() {
                 return Some(child.path());
             }
         }
-        return None;
+        return builder
+            .for_parent(|b| resolve_import_by_package(b, packages, file_name))
+            .flatten();
     }
     let first_package_name = &packages[0];
     let children = builder.resolve_child_by_name(first_package_name);
     for child_ref in &children {
         let child = child_ref.borrow();
-        let resolved = resolve_import(&child, &packages[1..], &file_name);
+        let resolved = resolve_import_by_package(&child, &packages[1..], file_name);
         if resolved.is_some() {
             return resolved;
         }
     }
-    let parent_resolution = builder.for_parent(|b| resolve_import(b, packages, file_name));
+    let parent_resolution =
+        builder.for_parent(|b| resolve_import_by_package(b, packages, file_name));
     match parent_resolution {
         Some(x) => x,
         _ => None,
     }
 }
 
-fn resolve_in_file(builder: &ScopeBuilder, full_path: &[Rc<str>]) -> Option<Type> {
-    let resolved = resolve_in_direct_children(builder, full_path);
-    if resolved.is_some() {
-        return resolved;
+fn file_imports(file: &ScopeBuilder) -> &[ImportPath] {
+    match &file.data {
+        ScopeData::File(f) => &f.imports,
+        _ => &[],
     }
-    let resolved = resolve_in_itself(&builder, full_path);
-    if resolved.is_some() {
-        return resolved;
-    }
-    resolve_in_parents_until_file(&builder, full_path)
 }
 
-fn resolve_in_direct_children(builder: &ScopeBuilder, full_path: &[Rc<str>]) -> Option<Type> {
-    assert!(full_path.len() > 0);
-    if full_path.len() == 1 {
-        let id = Rc::clone(&full_path[0]);
-        let resolved_children = builder.resolve_child_by_name(&id);
-        return resolved_children
-            .first()
-            .and_then(|w| w.borrow().get_type());
-    }
-    let resolved_scopes = builder.resolve_child_by_name(&full_path[0]);
-    for child_ref in &resolved_scopes {
-        let child = child_ref.borrow();
-        match resolve_in_direct_children(&child, &full_path[1..]) {
-            Some(t) => return Some(t),
-            None => {}
+/// Reports imports repeating a preceding import of the same file
+fn check_duplicate_imports(files: &[Rc<RefCell<ScopeBuilder>>]) -> Result<(), ProtoError> {
+    let mut errors = Vec::new();
+    for file_ref in files {
+        let file = file_ref.borrow();
+        let imports = file_imports(&file);
+        for (index, import_decl) in imports.iter().enumerate() {
+            let first = match imports[..index].iter().find(|i| *i == import_decl) {
+                Some(first) => first,
+                None => continue,
+            };
+            let mut error = located_error(
+                ErrorCode::DuplicateImport,
+                format!("Import {} is listed twice", import_decl),
+                import_decl.range.as_ref(),
+            );
+            if let Some(range) = &first.range {
+                error = error.with_label(range, "first imported here");
+            }
+            errors.push(error);
         }
     }
-    None
+    match ProtoError::from_errors(errors) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
 }
 
-fn resolve_in_itself(builder: &ScopeBuilder, full_path: &[Rc<str>]) -> Option<Type> {
-    if full_path.is_empty() {
-        return None;
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum VisitState {
+    New,
+    InProgress,
+    Done,
+}
+
+/// Reports every import which closes a cycle, with the files of the cycle
+fn check_import_cycles(files: &[Rc<RefCell<ScopeBuilder>>]) -> Result<(), ProtoError> {
+    let paths = files
+        .iter()
+        .map(|file_ref| file_ref.borrow().path())
+        .collect::<Vec<_>>();
+    // Indexes of imported files with the import statements
+    let imports = files
+        .iter()
+        .map(|file_ref| {
+            let file = file_ref.borrow();
+            file_imports(&file)
+                .iter()
+                .filter_map(|import_decl| {
+                    let imported = resolve_import(&file, import_decl)?;
+                    let index = paths.iter().position(|path| *path == imported)?;
+                    Some((index, import_decl.clone()))
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let mut states = vec![VisitState::New; files.len()];
+    let mut errors = Vec::new();
+    for index in 0..files.len() {
+        visit_imports(
+            index,
+            &paths,
+            &imports,
+            &mut states,
+            &mut vec![],
+            &mut errors,
+        );
     }
-    let current_name = builder.name();
-    let name = &full_path[0];
-    match current_name {
-        Some(n) => {
-            if n.deref() == name.deref() {
-                if full_path.len() == 1 {
-                    return builder.get_type();
-                }
-                return resolve_in_direct_children(builder, &full_path[1..]);
+    match ProtoError::from_errors(errors) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+fn visit_imports(
+    index: usize,
+    paths: &[Vec<Rc<str>>],
+    imports: &[Vec<(usize, ImportPath)>],
+    states: &mut [VisitState],
+    stack: &mut Vec<usize>,
+    errors: &mut Vec<ProtoError>,
+) {
+    if states[index] != VisitState::New {
+        return;
+    }
+    states[index] = VisitState::InProgress;
+    stack.push(index);
+    for (imported, import_decl) in &imports[index] {
+        match states[*imported] {
+            VisitState::New => visit_imports(*imported, paths, imports, states, stack, errors),
+            VisitState::InProgress => {
+                let start = stack.iter().position(|i| i == imported).unwrap_or(0);
+                let cycle = stack[start..]
+                    .iter()
+                    .chain(iter::once(imported))
+                    .map(|i| paths[*i].join("/"))
+                    .collect::<Vec<_>>();
+                errors.push(located_error(
+                    ErrorCode::ImportCycle,
+                    format!("Import cycle: {}", cycle.join(" -> ")),
+                    import_decl.range.as_ref(),
+                ));
             }
+            VisitState::Done => {}
         }
-        None => return None,
     }
-    None
+    stack.pop();
+    states[index] = VisitState::Done;
 }
 
-fn resolve_in_parents_until_file(builder: &ScopeBuilder, full_path: &[Rc<str>]) -> Option<Type> {
-    if builder.is_root() {
-        return None;
+/// Files declaring types and types used by files, files are identified by absolute paths
+#[derive(Default)]
+struct TypeUsage {
+    declared_in: HashMap<usize, Vec<Rc<str>>>,
+    used_by: HashMap<Vec<Rc<str>>, Vec<usize>>,
+    /// Files with `extend` blocks, they may be imported only to use custom options
+    extending: HashSet<Vec<Rc<str>>>,
+}
+
+/// `path` is the path of the parent package, or of the file for declarations
+fn collect_type_usage(scope: &ProtoScope, path: &[Rc<str>], usage: &mut TypeUsage) {
+    let mut path = path.to_vec();
+    let mut used = Vec::new();
+    match scope {
+        ProtoScope::Root(_) => {}
+        ProtoScope::Package(p) => path.push(Rc::clone(&p.name)),
+        ProtoScope::File(f) => {
+            path.push(Rc::clone(&f.name));
+            push_extension_type_ids(&f.extensions, &mut used);
+            if !f.extensions.is_empty() {
+                usage.extending.insert(path.clone());
+            }
+        }
+        ProtoScope::Enum(e) => {
+            usage.declared_in.insert(e.id, path.clone());
+        }
+        ProtoScope::Message(m) => {
+            usage.declared_in.insert(m.id, path.clone());
+            for field in m.get_fields() {
+                push_type_ids(&field.field_type, &mut used);
+            }
+            push_extension_type_ids(&m.extensions, &mut used);
+            if !m.extensions.is_empty() {
+                usage.extending.insert(path.clone());
+            }
+        }
+        ProtoScope::Service(s) => {
+            for method in &s.methods {
+                push_type_ids(&method.input_type, &mut used);
+                push_type_ids(&method.output_type, &mut used);
+            }
+        }
     }
-    if builder.is_package() {
-        return None;
+    if !used.is_empty() {
+        usage.used_by.entry(path.clone()).or_default().extend(used);
     }
-    if builder.is_file() {
-        return None;
+    for child in scope.children() {
+        collect_type_usage(child, &path, usage);
     }
-    match &builder.parent {
-        Some(parent_ref) => match parent_ref.upgrade() {
-            Some(parent_cell) => {
-                let parent = parent_cell.borrow();
-                match resolve_in_direct_children(&parent, &full_path) {
-                    Some(t) => return Some(t),
-                    _ => {}
-                }
-                match resolve_in_itself(&parent, &full_path) {
-                    Some(t) => Some(t),
-                    None => resolve_in_parents_until_file(&parent, &full_path),
-                }
+}
+
+fn push_extension_type_ids(extensions: &[Rc<Extension>], res: &mut Vec<usize>) {
+    for extension in extensions {
+        res.push(extension.extendee);
+        push_type_ids(&extension.field.field_type, res);
+    }
+}
+
+fn push_type_ids(t: &Type, res: &mut Vec<usize>) {
+    match t {
+        Type::Enum(id) | Type::Message(id) => res.push(*id),
+        Type::Repeated(t) => push_type_ids(t, res),
+        Type::Map(k, v) => {
+            push_type_ids(k, res);
+            push_type_ids(v, res);
+        }
+        _ => {}
+    }
+}
+
+/// Warns about imports of compiled files, which provide none of the types used by the file.
+/// Public imports are not reported, since they re-export files
+fn find_unused_imports(files: &[Rc<RefCell<ScopeBuilder>>], usage: &TypeUsage) -> Vec<ProtoError> {
+    let mut warnings = Vec::new();
+    for file_ref in files {
+        let file = file_ref.borrow();
+        let data = match &file.data {
+            ScopeData::File(f) if f.generated => f,
+            _ => continue,
+        };
+        let used_files = usage
+            .used_by
+            .get(&file.path())
+            .into_iter()
+            .flatten()
+            .filter_map(|id| usage.declared_in.get(id))
+            .collect::<HashSet<_>>();
+        for import_decl in &data.imports {
+            if import_decl.kind == ImportKind::Public {
+                continue;
             }
-            None => None,
-        },
-        None => None,
+            let range = match &import_decl.range {
+                Some(range) => range,
+                None => continue,
+            };
+            let imported = match resolve_import(&file, import_decl) {
+                Some(imported) => imported,
+                None => continue,
+            };
+            let mut provided = vec![imported.clone()];
+            if push_public_imports(&file, &imported, &mut provided).is_err() {
+                continue;
+            }
+            let used = provided
+                .iter()
+                .any(|path| used_files.contains(path) || usage.extending.contains(path));
+            if !used {
+                warnings.push(
+                    validation_error(format!("Import {} is unused", import_decl), range)
+                        .with_code(ErrorCode::UnusedImport),
+                );
+            }
+        }
     }
+    warnings
 }
 
 impl ScopeBuilderPrivate for Rc<RefCell<ScopeBuilder>> {
-    fn load_file(&self, file: ProtoFile, path: &[Rc<str>]) -> Result<(), ProtoError> {
+    fn load_file(
+        &self,
+        file: ProtoFile,
+        path: &[Rc<str>],
+        generated: bool,
+    ) -> Result<(), ProtoError> {
         if path.is_empty() {
             let present = {
                 let cell = self.borrow();
@@ -808,8 +1632,20 @@ impl ScopeBuilderPrivate for Rc<RefCell<ScopeBuilder>> {
                     child.is_file_with_name(&file.name)
                 })
             };
-            assert!(!present);
-            let file_builder = ScopeBuilder::new_file(file.name, file.imports, Rc::clone(self));
+            if present {
+                return Err(ProtoError::new(&format!(
+                    "File {} is loaded twice into package {}",
+                    file.name,
+                    file.path.join(".")
+                )));
+            }
+            let file_builder = ScopeBuilder::new_file(
+                file.name,
+                file.import_path,
+                file.imports,
+                generated,
+                Rc::clone(self),
+            );
             let file_builder_ref = Rc::new(RefCell::new(file_builder));
             for decl in file.declarations {
                 file_builder_ref.load_declaration(decl)?;
@@ -834,14 +1670,14 @@ impl ScopeBuilderPrivate for Rc<RefCell<ScopeBuilder>> {
             Some(ind) => {
                 let cell = self.borrow();
                 let child_ref = Rc::clone(&cell.children[ind]);
-                child_ref.load_file(file, &path[1..])?;
+                child_ref.load_file(file, &path[1..], generated)?;
                 Ok(())
             }
             None => {
                 let package_builder =
                     ScopeBuilder::new_package(Rc::clone(&path[0]), Rc::clone(self));
                 let package_ref = Rc::new(RefCell::new(package_builder));
-                package_ref.load_file(file, &path[1..])?;
+                package_ref.load_file(file, &path[1..], generated)?;
                 {
                     let mut cell = self.borrow_mut();
                     cell.children.push(package_ref);
@@ -855,6 +1691,14 @@ impl ScopeBuilderPrivate for Rc<RefCell<ScopeBuilder>> {
         match declaration {
             Declaration::Enum(e) => self.load_enum(e),
             Declaration::Message(m) => self.load_message(m),
+            Declaration::Service(s) => self.load_service(s),
+            Declaration::Extend(e) => {
+                match &mut self.borrow_mut().data {
+                    ScopeData::File(file) => file.extends.push(e),
+                    _ => unreachable!(),
+                }
+                Ok(())
+            }
         }
     }
 
@@ -872,12 +1716,15 @@ impl ScopeBuilderPrivate for Rc<RefCell<ScopeBuilder>> {
         let mut fields: Vec<FieldOrOneOf> = Vec::new();
         let mut sub_messages: Vec<MessageDeclaration> = Vec::new();
         let mut sub_enums: Vec<EnumDeclaration> = Vec::new();
+        let mut extends: Vec<ExtendDeclaration> = Vec::new();
         for entry in message_declaration.entries {
             match entry {
                 MessageDeclarationEntry::Field(f) => fields.push(FieldOrOneOf::Field(f)),
                 MessageDeclarationEntry::Declaration(decl) => match decl {
                     Declaration::Enum(e) => sub_enums.push(e),
                     Declaration::Message(m) => sub_messages.push(m),
+                    Declaration::Service(_) => unreachable!(),
+                    Declaration::Extend(e) => extends.push(e),
                 },
                 MessageDeclarationEntry::OneOf(o) => fields.push(FieldOrOneOf::OneOf(o)),
             }
@@ -887,6 +1734,14 @@ impl ScopeBuilderPrivate for Rc<RefCell<ScopeBuilder>> {
             message_declaration.id,
             message_declaration.name,
             fields,
+            message_declaration
+                .extensions
+                .iter()
+                .flat_map(|e| e.ranges.iter().copied())
+                .collect(),
+            extends,
+            message_declaration.comments,
+            message_declaration.range,
             Rc::clone(self),
         );
         let message_builder_ref = Rc::new(RefCell::new(message_builder));
@@ -902,6 +1757,16 @@ impl ScopeBuilderPrivate for Rc<RefCell<ScopeBuilder>> {
         }
         Ok(())
     }
+
+    fn load_service(&self, service_declaration: ServiceDeclaration) -> Result<(), ProtoError> {
+        let service_builder = ScopeBuilder::new_service(service_declaration, Rc::clone(self));
+        let service_ref = Rc::new(RefCell::new(service_builder));
+        {
+            let mut cell = self.borrow_mut();
+            cell.children.push(service_ref);
+        }
+        Ok(())
+    }
 }
 
 impl Default for ScopeBuilder {
@@ -909,3 +1774,209 @@ impl Default for ScopeBuilder {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::proto::{
+        error::{ErrorCode, ProtoError},
+        id_generator::IdGenerator,
+        lexems::read_lexems,
+        package::{ProtoFile, ProtoVersion},
+        proto_scope::root_scope::RootScope,
+        syntax::parse_package,
+    };
+
+    use super::{ScopeBuilder, ScopeBuilderTrait};
+
+    fn build(content: &str) -> Result<RootScope, ProtoError> {
+        build_files(&[("test.proto", content)])
+    }
+
+    /// Builds files without packages from pairs of name and content
+    fn build_files(files: &[(&str, &str)]) -> Result<RootScope, ProtoError> {
+        let mut id_generator = IdGenerator::new();
+        let builder = ScopeBuilder::new_ref();
+        for (name, content) in files {
+            let lexems = read_lexems(name, content).unwrap();
+            let mut file = ProtoFile {
+                version: ProtoVersion::Proto3,
+                declarations: vec![],
+                imports: vec![],
+                path: vec![],
+                name: (*name).into(),
+                import_path: (*name).into(),
+                options: vec![],
+            };
+            parse_package(&mut id_generator, &lexems, &mut file).unwrap();
+            builder.load(file).unwrap();
+        }
+        builder.finish()
+    }
+
+    #[test]
+    fn resolves_names_from_innermost_scope() {
+        let root = build(
+            r#"
+            syntax = "proto3";
+            package foo.bar;
+            message Baz {}
+            message Outer {
+                message foo {}
+                .foo.bar.Baz absolute = 1;
+                Baz simple = 2;
+                bar.Baz partial = 3;
+                foo inner = 4;
+            }
+        "#,
+        );
+        assert!(root.is_ok());
+    }
+
+    #[test]
+    fn rejects_partial_name_resolved_to_inner_scope() {
+        let error = build(
+            r#"
+            syntax = "proto3";
+            package foo.bar;
+            message Baz {}
+            message Outer {
+                message foo {}
+                foo.bar.Baz baz = 1;
+            }
+        "#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains(
+            "foo.bar.Baz is resolved to foo.bar.Outer.foo.bar.Baz, which is not defined"
+        ));
+    }
+
+    #[test]
+    fn reports_import_cycles() {
+        let error = build_files(&[
+            ("a.proto", r#"syntax = "proto3"; import "b.proto";"#),
+            ("b.proto", r#"syntax = "proto3"; import "a.proto";"#),
+        ])
+        .unwrap_err();
+        assert_eq!(error.code(), Some(ErrorCode::ImportCycle));
+        assert_eq!(
+            error.message(),
+            "Import cycle: a.proto -> b.proto -> a.proto"
+        );
+    }
+
+    #[test]
+    fn reports_unused_and_missing_imports() {
+        let root = build_files(&[
+            ("a.proto", r#"syntax = "proto3"; message A {}"#),
+            ("b.proto", r#"syntax = "proto3"; import public "a.proto";"#),
+            ("c.proto", r#"syntax = "proto3"; message C {}"#),
+            (
+                "d.proto",
+                r#"syntax = "proto3"; import "b.proto"; import "c.proto"; message D { A a = 1; }"#,
+            ),
+        ])
+        .unwrap();
+        assert_eq!(
+            root.warnings
+                .iter()
+                .map(|warning| (warning.code(), warning.message()))
+                .collect::<Vec<_>>(),
+            vec![(
+                Some(ErrorCode::UnusedImport),
+                "Import c.proto is unused".to_string()
+            )]
+        );
+
+        let error = build_files(&[
+            ("a.proto", r#"syntax = "proto3"; message A {}"#),
+            ("b.proto", r#"syntax = "proto3"; message B { A a = 1; }"#),
+        ])
+        .unwrap_err();
+        assert_eq!(error.code(), Some(ErrorCode::MissingImport));
+        assert_eq!(
+            error.message(),
+            "A is declared in a.proto, which is not imported by b.proto"
+        );
+
+        let error = build_files(&[
+            ("a.proto", r#"syntax = "proto3"; package x; message A {}"#),
+            (
+                "b.proto",
+                r#"syntax = "proto3"; package x; message B { A a = 1; }"#,
+            ),
+        ])
+        .unwrap_err();
+        assert_eq!(
+            error.message(),
+            "A is declared in a.proto, which is not imported by b.proto"
+        );
+    }
+
+    #[test]
+    fn reports_duplicate_imports() {
+        let error = build_files(&[
+            ("a.proto", r#"syntax = "proto3"; message A {}"#),
+            (
+                "b.proto",
+                r#"syntax = "proto3"; import "a.proto"; import "a.proto"; message B { A a = 1; }"#,
+            ),
+        ])
+        .unwrap_err();
+        assert_eq!(error.code(), Some(ErrorCode::DuplicateImport));
+        assert_eq!(error.message(), "Import a.proto is listed twice");
+        assert_eq!(error.labels()[0].message, "first imported here");
+        assert_eq!(error.labels()[0].range.start.column, 20);
+    }
+
+    #[test]
+    fn suggests_similar_names() {
+        let error = build_files(&[
+            (
+                "a.proto",
+                r#"syntax = "proto3"; package a; message Account {}"#,
+            ),
+            (
+                "b.proto",
+                r#"syntax = "proto3"; package b; message Acount {} message B { Accont x = 1; }"#,
+            ),
+        ])
+        .unwrap_err();
+        assert_eq!(
+            error.help(),
+            &[
+                "did you mean a.Account? It is declared in a.proto, which is not imported, \
+                 add import \"a.proto\" to use it"
+                    .to_string(),
+                "did you mean b.Acount? It is declared in this file".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_all_unresolved_references() {
+        let error = build(
+            r#"
+            syntax = "proto3";
+            message A { Missing a = 1; repeated Lost b = 2; oneof c { Gone d = 3; } }
+            message B { A a = 1; map<string, Absent> b = 2; }
+            service S { rpc Call(Unknown) returns (A); }
+        "#,
+        )
+        .unwrap_err();
+        let errors = match error {
+            ProtoError::Multiple(errors) => errors,
+            error => panic!("Expected multiple errors, got {}", error),
+        };
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| error.range().unwrap().start.line)
+                .collect::<Vec<_>>(),
+            vec![3, 3, 3, 4, 5]
+        );
+        assert!(errors
+            .iter()
+            .all(|error| error.code() == Some(ErrorCode::UnresolvedType)));
+    }
+}
@@ -5,6 +5,7 @@ use crate::proto::{id_generator::IdGenerator, package::ImportPath};
 use super::ScopeBuilder;
 
 mod any;
+mod descriptor;
 mod duration;
 mod empty;
 mod field_mask;
@@ -19,6 +20,7 @@ pub(super) fn create_well_known_file(
 ) -> Rc<RefCell<ScopeBuilder>> {
     match file_name {
         "any.proto" => any::create_file(id_gen),
+        "descriptor.proto" => descriptor::create_file(id_gen),
         "timestamp.proto" => timestamp::create_file(id_gen),
         "empty.proto" => empty::create_file(id_gen),
         "duration.proto" => duration::create_file(id_gen),
@@ -45,13 +47,8 @@ pub(crate) fn is_well_known_import(imp: &ImportPath) -> bool {
 }
 fn is_valid_well_known_import_file_name(imp: &str) -> bool {
     match imp {
-        "any.proto"
-        | "duration.proto"
-        | "empty.proto"
-        | "field_mask.proto"
-        | "struct.proto"
-        | "timestamp.proto"
-        | "wrappers.proto" => true,
+        "any.proto" | "descriptor.proto" | "duration.proto" | "empty.proto"
+        | "field_mask.proto" | "struct.proto" | "timestamp.proto" | "wrappers.proto" => true,
         _ => false,
     }
 }
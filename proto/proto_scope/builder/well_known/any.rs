@@ -10,7 +10,10 @@ pub(in crate::proto) fn create_file(id_gen: &mut IdGenerator) -> Rc<RefCell<Scop
     let res = ScopeBuilder {
         data: ScopeData::File(FileData {
             name: Rc::from("any.proto"),
+            import_path: Rc::from("google/protobuf/any.proto"),
             imports: Vec::new(),
+            extends: Vec::new(),
+            generated: true,
         }),
         parent: None,
         children: vec![],
@@ -0,0 +1,54 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::proto::{
+    id_generator::IdGenerator,
+    package::{TagRange, MAX_FIELD_NUMBER},
+    proto_scope::builder::{FileData, MessageData, ScopeBuilder, ScopeData},
+};
+
+/// Only option messages are declared, so custom options can extend them
+const OPTIONS_MESSAGES: [&str; 8] = [
+    "FileOptions",
+    "MessageOptions",
+    "FieldOptions",
+    "OneofOptions",
+    "EnumOptions",
+    "EnumValueOptions",
+    "ServiceOptions",
+    "MethodOptions",
+];
+
+pub(in crate::proto) fn create_file(id_gen: &mut IdGenerator) -> Rc<RefCell<ScopeBuilder>> {
+    let res = ScopeBuilder {
+        data: ScopeData::File(FileData {
+            name: Rc::from("descriptor.proto"),
+            import_path: Rc::from("google/protobuf/descriptor.proto"),
+            imports: Vec::new(),
+            extends: Vec::new(),
+            generated: true,
+        }),
+        parent: None,
+        children: vec![],
+    };
+    let res_ref = Rc::new(RefCell::new(res));
+    for name in OPTIONS_MESSAGES {
+        let mut message: MessageData = id_gen.create((name.into(), vec![]));
+        message.extension_ranges.push(TagRange {
+            start: 1000,
+            end: MAX_FIELD_NUMBER,
+        });
+        let child = {
+            let builder = ScopeBuilder {
+                data: ScopeData::Message(message),
+                parent: Some(Rc::downgrade(&res_ref)),
+                children: vec![],
+            };
+            Rc::new(RefCell::new(builder))
+        };
+        {
+            let mut res = res_ref.borrow_mut();
+            res.children.push(child);
+        }
+    }
+    res_ref
+}
@@ -2,7 +2,9 @@ use std::{cell::RefCell, rc::Rc};
 
 use crate::proto::{
     id_generator::IdGenerator,
-    package::{EnumDeclaration, FieldDeclaration, FieldTypeReference, OneOfDeclaration},
+    package::{
+        Comments, EnumDeclaration, FieldDeclaration, FieldTypeReference, OneOfDeclaration,
+    },
     proto_scope::builder::{FileData, MessageData, ScopeBuilder, ScopeData},
 };
 
@@ -10,7 +12,10 @@ pub(in crate::proto) fn create_file(id_gen: &mut IdGenerator) -> Rc<RefCell<Scop
     let res = ScopeBuilder {
         data: ScopeData::File(FileData {
             name: Rc::from("struct.proto"),
+            import_path: Rc::from("google/protobuf/struct.proto"),
             imports: Vec::new(),
+            extends: Vec::new(),
+            generated: true,
         }),
         parent: None,
         children: vec![],
@@ -71,7 +76,7 @@ fn create_messages(id_gen: &mut IdGenerator) -> Vec<MessageData> {
             "Value".into(),
             vec![OneOfDeclaration {
                 name: "kind".into(),
-                options: vec![
+                fields: vec![
                     FieldDeclaration::new("null_value", FieldTypeReference::id("NullValue"), 1)
                         .into(),
                     FieldDeclaration::new("number_value", FieldTypeReference::Double, 2).into(),
@@ -82,6 +87,8 @@ fn create_messages(id_gen: &mut IdGenerator) -> Vec<MessageData> {
                     FieldDeclaration::new("list_value", FieldTypeReference::id("ListValue"), 6)
                         .into(),
                 ],
+                options: vec![],
+                comments: Comments::default(),
             }
             .into()],
         )),
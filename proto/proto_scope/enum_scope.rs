@@ -1,6 +1,9 @@
 use std::rc::Rc;
 
-use crate::proto::package::EnumEntry;
+use crate::proto::{
+    lexems::SourceRange,
+    package::{Comments, EnumEntry},
+};
 
 use super::{traits::ChildrenScopes, ProtoScope};
 
@@ -9,6 +12,13 @@ pub(crate) struct EnumScope {
     pub id: usize,
     pub name: Rc<str>,
     pub entries: Vec<EnumEntry>,
+    pub comments: Comments,
+    /// Unknown values are dropped on decoding
+    pub closed: bool,
+    /// Several entries may share the same value
+    pub allow_alias: bool,
+    /// Range of the name, missing for built-in declarations
+    pub range: Option<SourceRange>,
 }
 
 impl std::fmt::Display for EnumScope {
@@ -39,14 +49,24 @@ mod test_format {
                 EnumEntry {
                     name: "Hello".into(),
                     value: 0,
+                    options: vec![],
+                    range: None,
+                    comments: Comments::default(),
                 }
                 .into(),
                 EnumEntry {
                     name: "World".into(),
                     value: 1,
+                    options: vec![],
+                    range: None,
+                    comments: Comments::default(),
                 }
                 .into(),
             ],
+            comments: Comments::default(),
+            closed: false,
+            allow_alias: false,
+            range: None,
         };
         let str = format!("{}", enum_scope);
         assert_eq!(
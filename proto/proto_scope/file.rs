@@ -1,11 +1,17 @@
 use std::{rc::{Rc}, fmt::Write};
 
+use crate::proto::package::Extension;
+
 use super::{traits::ChildrenScopes, ProtoScope};
 
 #[derive(Debug)]
 pub(crate) struct FileScope {
     pub name: Rc<str>,
     pub children: Vec<Rc<ProtoScope>>,
+    /// Extensions declared by top level `extend` blocks
+    pub extensions: Vec<Rc<Extension>>,
+    /// False for files loaded only to resolve imports
+    pub generated: bool,
 }
 
 impl ChildrenScopes for FileScope {
@@ -1,6 +1,9 @@
 use std::{fmt::Write, rc::Rc};
 
-use crate::proto::package::{MessageEntry, Field};
+use crate::proto::{
+    lexems::SourceRange,
+    package::{Comments, Extension, Field, MessageEntry, OneOfGroup, TagRange},
+};
 
 use super::{traits::ChildrenScopes, ProtoScope};
 
@@ -10,6 +13,12 @@ pub(crate) struct MessageScope {
     pub name: Rc<str>,
     pub children: Vec<Rc<ProtoScope>>,
     pub entries: Vec<MessageEntry>,
+    pub extension_ranges: Vec<TagRange>,
+    /// Extensions declared by `extend` blocks nested in this message
+    pub extensions: Vec<Rc<Extension>>,
+    pub comments: Comments,
+    /// Range of the name, missing for built-in declarations
+    pub range: Option<SourceRange>,
 }
 
 impl ChildrenScopes for MessageScope {
@@ -25,13 +34,30 @@ impl MessageScope {
             .iter()
             .flat_map(|f| match f {
                 MessageEntry::Field(f) => vec![f],
-                MessageEntry::OneOf(one_of) => one_of.options.iter().collect(),
+                MessageEntry::OneOf(one_of) => one_of.fields.iter().collect(),
             })
             .collect::<Vec<_>>();
 
         fields.sort_by_key(|x| x.tag);
         fields
     }
+
+    /// Whether the field is an option of some `oneof` group
+    pub fn is_one_of_field(&self, field: &Field) -> bool {
+        self.get_one_of_group(field).is_some()
+    }
+
+    /// `oneof` group which the field is an option of
+    pub fn get_one_of_group(&self, field: &Field) -> Option<&OneOfGroup> {
+        self.entries.iter().find_map(|entry| match entry {
+            MessageEntry::Field(_) => None,
+            MessageEntry::OneOf(one_of) => one_of
+                .fields
+                .iter()
+                .any(|f| std::ptr::eq(f, field))
+                .then_some(one_of),
+        })
+    }
 }
 
 impl std::fmt::Display for MessageScope {
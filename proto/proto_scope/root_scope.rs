@@ -1,6 +1,9 @@
 use std::{collections::HashMap, rc::Rc, fmt::Write};
 
-use crate::proto::protopath::ProtoPath;
+use crate::proto::{
+    compiler::ts::compile_options::CompileOptions, error::ProtoError, package::Extension,
+    protopath::ProtoPath,
+};
 
 use super::{
     traits::{ChildrenScopes, ResolveName},
@@ -11,6 +14,12 @@ use super::{
 pub(crate) struct RootScope {
     pub children: Vec<Rc<ProtoScope>>,
     pub types: HashMap<usize, Vec<Rc<str>>>,
+    /// Known extensions by id of the extended message, sorted by field number
+    pub extensions: HashMap<usize, Vec<Rc<Extension>>>,
+    /// Diagnostics which do not stop compilation, e.g. unused imports
+    pub warnings: Vec<ProtoError>,
+    /// Options of generated code
+    pub options: CompileOptions,
 }
 
 impl RootScope {
@@ -30,6 +39,38 @@ impl RootScope {
         Some(res)
     }
 
+    pub fn get_declaration(&self, decl_id: usize) -> Option<Rc<ProtoScope>> {
+        let str_path = &self.types.get(&decl_id)?[..];
+        let mut current = self.resolve_name(&str_path[0])?;
+        for name in &str_path[1..] {
+            current = current.resolve_name(name)?;
+        }
+        Some(current)
+    }
+
+    /// Path of a package, file or declaration scope found by identity
+    pub fn get_scope_path(&self, scope: &ProtoScope) -> Option<ProtoPath> {
+        fn find(children: &[Rc<ProtoScope>], scope: &ProtoScope, res: &mut ProtoPath) -> bool {
+            for child in children {
+                if let ProtoScope::Service(_) = child.as_ref() {
+                    continue;
+                }
+                res.push(child.as_path_component());
+                if std::ptr::eq(child.as_ref(), scope) || find(child.children(), scope, res) {
+                    return true;
+                }
+                res.path.pop();
+            }
+            false
+        }
+        let mut res = ProtoPath::new();
+        if find(&self.children, scope, &mut res) {
+            Some(res)
+        } else {
+            None
+        }
+    }
+
     pub fn get_declaration_name(&self, decl_id: usize) -> Option<Rc<str>> {
         let str_path = &self.types.get(&decl_id)?;
         let last_name = &str_path[str_path.len() - 1];
@@ -42,6 +83,9 @@ impl Default for RootScope {
         Self {
             children: Vec::new(),
             types: Default::default(),
+            extensions: Default::default(),
+            warnings: Vec::new(),
+            options: Default::default(),
         }
     }
 }
@@ -0,0 +1,44 @@
+use std::rc::Rc;
+
+use crate::proto::{
+    lexems::SourceRange,
+    package::{Method, ProtoOption},
+};
+
+use super::{traits::ChildrenScopes, ProtoScope};
+
+#[derive(Debug)]
+pub(crate) struct ServiceScope {
+    pub name: Rc<str>,
+    pub methods: Vec<Method>,
+    pub options: Vec<ProtoOption>,
+    /// Range of the name
+    pub range: Option<SourceRange>,
+}
+
+impl ChildrenScopes for ServiceScope {
+    fn children(&self) -> &[Rc<ProtoScope>] {
+        &[]
+    }
+}
+
+impl std::fmt::Display for ServiceScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "service {}", self.name)?;
+        for option in &self.options {
+            writeln!(f, "  option {};", option)?;
+        }
+        for method in &self.methods {
+            writeln!(
+                f,
+                "  rpc {}({}{}) returns ({}{})",
+                method.name,
+                if method.client_streaming { "stream " } else { "" },
+                method.input_type.to_string(),
+                if method.server_streaming { "stream " } else { "" },
+                method.output_type.to_string(),
+            )?;
+        }
+        Ok(())
+    }
+}
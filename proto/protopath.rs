@@ -47,6 +47,15 @@ impl ProtoPath {
     pub fn is_empty(&self) -> bool {
         self.path.is_empty()
     }
+    /// Fully qualified proto name, e.g. `google.protobuf.Timestamp`
+    pub fn full_name(&self) -> String {
+        self.path
+            .iter()
+            .filter(|c| !matches!(c, PathComponent::File(_)))
+            .map(String::from)
+            .collect::<Vec<_>>()
+            .join(".")
+    }
 }
 
 impl std::fmt::Display for ProtoPath {
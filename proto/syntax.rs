@@ -3,18 +3,21 @@ use std::{ops::Deref, rc::Rc};
 use crate::proto::package::FieldDeclaration;
 
 use super::{
-    error::{syntax_error, ProtoError},
+    error::{syntax_error, unexpected_lexem_error, validation_error, ErrorCode, ProtoError},
+    features::resolve_features,
     id_generator::IdGenerator,
-    lexems::{Lexem, LocatedLexem},
+    lexems::{Lexem, LocatedLexem, Position, SourceRange},
     package::{
-        Declaration, EnumDeclaration, EnumEntry, FieldTypeReference, ImportPath,
-        MessageDeclaration, MessageDeclarationEntry, OneOfDeclaration, ProtoFile,
+        find_option, Comments, Declaration, EnumDeclaration, EnumEntry, ExtendDeclaration,
+        Extensions, FieldLabel, FieldTypeReference, ImportKind, ImportPath, MessageDeclaration,
+        MessageDeclarationEntry, MethodDeclaration, OneOfDeclaration, OptionName, OptionNamePart,
+        OptionValue, ProtoFile, ProtoOption, ProtoVersion, Reserved, ServiceDeclaration, TagRange,
+        MAX_ENUM_VALUE, MAX_FIELD_NUMBER,
     },
 };
 
 #[derive(Debug, Clone)]
 enum Task {
-    ParseStatements,
     ParseStatement,
     ParseSyntaxStatement,
     ParseImportStatement,
@@ -27,23 +30,44 @@ enum Task {
     ParseFieldDeclaration,
     ParseMessageStatement,
     ExpectLexem(Lexem),
-    Push(StackItem),
-    ParseFieldAttributes,
     /// Takes declaration from the stack
     /// And pushes it to the package declarations
     AppendDeclarationToPackage,
-    ParseFieldAttribute,
     PushFieldDeclaration,
-    PushFieldAttribute,
+    PushEnumEntry,
     ParseMessageEntries,
     ParseMessageEntry,
-    ParseOptionalAttributes,
+    /// Parses `[name = value, ...]` if present
+    /// and places options list into stack
+    ParseCompactOptions,
+    ParseCompactOptionsEntries,
     ParseInt64,
     ParseFieldType,
-    ParseStringLiteral,
     WrapMessageEntry,
     PushMessageEntry,
-    PushMessageStatement,
+    /// String -> Message
+    StartMessage,
+    /// String -> OneOf
+    StartOneOf,
+    /// Checks fields or values of message or enum on top of the stack
+    /// against its reserved numbers and names
+    ValidateReserved,
+    /// Parses `reserved` statement into message or enum on top of the stack
+    ParseReservedStatement,
+    /// Pushes FieldLabel, which is empty when field has no label
+    ParseFieldLabel,
+    /// proto2 `group`, which declares both nested message and field
+    ParseGroup,
+    /// Creates nested message of a group
+    StartGroup,
+    PushGroup,
+    /// Parses `extensions 100 to 199;` of a message
+    ParseExtensionsStatement,
+    PushExtensions,
+    /// Parses `extend Foo { ... }` inside a file or a message
+    ParseExtendStatement,
+    StartExtend,
+    PushExtend,
     ParseIdPath,
     /// FieldType -> FieldType
     ExpectKeyTypeOnStack,
@@ -51,11 +75,35 @@ enum Task {
     WrapFieldType,
     /// [FieldType, FieldType] => Map<FieldType, FieldType>
     WrapMapType,
-    /// Input: Vec<MessageEntries> String
-    /// Output: OneOfDeclaration
-    PushOneOf,
     /// Parses identifier and places it into stack
     ParseId,
+    ParseServiceStatement,
+    /// String -> Service
+    StartService,
+    ParseServiceEntries,
+    ParseMethodStatement,
+    /// Parses optional `stream` keyword and message type
+    /// Output: Bool FieldType
+    ParseStreamType,
+    /// [String, Bool, FieldType, Bool, FieldType] -> Method
+    WrapMethod,
+    ParseMethodBody,
+    ParseMethodOptions,
+    /// Takes method from the stack
+    /// And pushes it to the service below it
+    PushMethod,
+    /// Parses `option name = value;` and places option into stack
+    ParseOptionStatement,
+    /// Parses `name = value` and places option into stack
+    ParseOption,
+    ParseOptionName,
+    ParseOptionValue,
+    /// [OptionName, OptionValue] -> Option
+    WrapOption,
+    /// Takes option from the stack
+    /// And pushes it to the closest declaration below it
+    /// or to the file if there is none
+    PushOption,
 }
 use Task::*;
 
@@ -63,15 +111,23 @@ use Task::*;
 enum StackItem {
     String(Rc<str>),
     StringList(Vec<Rc<str>>),
-    EnumEntriesList(Vec<EnumEntry>),
-    MessageEntriesList(Vec<MessageDeclarationEntry>),
     MessageEntry(MessageDeclarationEntry),
     FieldType(FieldTypeReference),
     Int64(i64),
     Message(MessageDeclaration),
-    OptionalAttributes(Option<Vec<(Rc<str>, Rc<str>)>>),
+    OptionsList(Vec<ProtoOption>),
     Enum(EnumDeclaration),
     OneOf(OneOfDeclaration),
+    Bool(bool),
+    Service(ServiceDeclaration),
+    Method(MethodDeclaration),
+    OptionName(OptionName),
+    OptionValue(OptionValue),
+    Option(ProtoOption),
+    Range(SourceRange),
+    FieldLabel(Option<FieldLabel>),
+    TagRanges(Vec<TagRange>),
+    Extend(ExtendDeclaration),
 }
 
 impl From<Rc<str>> for StackItem {
@@ -110,34 +166,72 @@ impl From<FieldTypeReference> for StackItem {
     }
 }
 
+/// Parses all top level statements of the file.
+/// A statement with syntax error is skipped, so that all errors of the file
+/// are reported at once
 pub(super) fn parse_package(
     id_gen: &mut IdGenerator,
     located_lexems: &[LocatedLexem],
     res: &mut ProtoFile,
 ) -> Result<(), ProtoError> {
     let mut ind = 0;
-    let mut tasks: Vec<Task> = vec![ParseStatements];
+    let mut errors = Vec::new();
+    while located_lexems[ind].lexem != Lexem::EOF {
+        match parse_statement(id_gen, located_lexems, ind, res) {
+            Ok(statement_end) => ind = statement_end,
+            Err(error) => {
+                errors.push(error);
+                ind = skip_statement(located_lexems, ind);
+            }
+        }
+    }
+    match ProtoError::from_errors(errors) {
+        Some(error) => Err(error),
+        None => resolve_features(res),
+    }
+}
+
+/// Returns index of the lexem after the statement starting at `start`:
+/// after its `;` or after `}` of its body
+fn skip_statement(located_lexems: &[LocatedLexem], start: usize) -> usize {
+    let mut depth = 0;
+    for (ind, located_lexem) in located_lexems.iter().enumerate().skip(start) {
+        match located_lexem.lexem {
+            Lexem::OpenCurly => depth += 1,
+            Lexem::CloseCurly if depth <= 1 => return ind + 1,
+            Lexem::CloseCurly => depth -= 1,
+            Lexem::SemiColon if depth == 0 => return ind + 1,
+            Lexem::EOF => return ind,
+            _ => {}
+        }
+    }
+    located_lexems.len() - 1
+}
+
+/// Parses single top level statement starting at `start`
+/// and returns index of the lexem after it
+fn parse_statement(
+    id_gen: &mut IdGenerator,
+    located_lexems: &[LocatedLexem],
+    start: usize,
+    res: &mut ProtoFile,
+) -> Result<usize, ProtoError> {
+    let mut ind = start;
+    let mut tasks: Vec<Task> = vec![ParseStatement];
     let mut stack: Vec<StackItem> = Vec::new();
     while let Some(task) = tasks.pop() {
+        if ind >= located_lexems.len() {
+            return Err(syntax_error(
+                "Unexpected end of file",
+                located_lexems.last().unwrap(),
+            ));
+        }
         match task {
-            ParseStatements => {
-                let located_lexem = &located_lexems[ind];
-                let lexem = &located_lexem.lexem;
-                match lexem {
-                    Lexem::EOF => {
-                        break;
-                    }
-                    _ => {
-                        tasks.push(ParseStatements);
-                        tasks.push(ParseStatement)
-                    }
-                }
-            }
             ParseStatement => {
                 let located_lexem = &located_lexems[ind];
                 let lexem = &located_lexem.lexem;
                 match lexem {
-                    Lexem::Id(id) if id.deref() == "syntax" => {
+                    Lexem::Id(id) if matches!(id.deref(), "syntax" | "edition") => {
                         tasks.push(ParseSyntaxStatement);
                         continue;
                     }
@@ -159,22 +253,26 @@ pub(super) fn parse_package(
                         tasks.push(ParseEnumDeclaration);
                         continue;
                     }
-                    Lexem::Id(id) => {
-                        return Err(syntax_error(
-                            format!("Unexpected identifier: {}", id),
-                            located_lexem,
-                        ));
+                    Lexem::Id(id) if id.deref() == "service" => {
+                        tasks.push(AppendDeclarationToPackage);
+                        tasks.push(ParseServiceStatement);
+                        continue;
+                    }
+                    Lexem::Id(id) if id.deref() == "extend" => {
+                        tasks.push(ParseExtendStatement);
+                        continue;
+                    }
+                    Lexem::Id(id) if id.deref() == "option" => {
+                        tasks.push(PushOption);
+                        tasks.push(ParseOptionStatement);
+                        continue;
                     }
                     Lexem::SemiColon => {
                         ind += 1;
                         continue;
                     }
                     _ => {
-                        print_state(stack, tasks, task, &located_lexems[ind..]);
-                        return Err(syntax_error(
-                            format!("Unexpected lexem {:?}", lexem),
-                            located_lexem,
-                        ));
+                        return Err(unexpected_lexem_error("statement", located_lexem));
                     }
                 }
             }
@@ -182,6 +280,7 @@ pub(super) fn parse_package(
                 let declaration = match stack.pop() {
                     Some(StackItem::Message(message)) => Declaration::Message(message),
                     Some(StackItem::Enum(enum_decl)) => Declaration::Enum(enum_decl),
+                    Some(StackItem::Service(service)) => Declaration::Service(service),
                     _ => unreachable!(),
                 };
                 res.declarations.push(declaration);
@@ -227,18 +326,34 @@ pub(super) fn parse_package(
                 let semi_colon = &located_lexems[ind + 3].lexem;
                 match (syntax, equals, version, semi_colon) {
                     (Lexem::Id(id), Lexem::Equal, Lexem::StringLiteral(s), Lexem::SemiColon)
-                        if id.deref() == "syntax" && s.deref() == "proto2" =>
+                        if id.deref() == "syntax" && s.deref() == b"proto2" =>
                     {
                         ind += 4;
+                        res.version = super::package::ProtoVersion::Proto2;
                         continue;
                     }
                     (Lexem::Id(id), Lexem::Equal, Lexem::StringLiteral(s), Lexem::SemiColon)
-                        if id.deref() == "syntax" && s.deref() == "proto3" =>
+                        if id.deref() == "syntax" && s.deref() == b"proto3" =>
                     {
                         ind += 4;
                         res.version = super::package::ProtoVersion::Proto3;
                         continue;
                     }
+                    (Lexem::Id(id), Lexem::Equal, Lexem::StringLiteral(s), Lexem::SemiColon)
+                        if id.deref() == "edition" && s.deref() == b"2023" =>
+                    {
+                        ind += 4;
+                        res.version = ProtoVersion::Edition2023;
+                        continue;
+                    }
+                    (Lexem::Id(id), Lexem::Equal, Lexem::StringLiteral(s), Lexem::SemiColon)
+                        if id.deref() == "edition" =>
+                    {
+                        return Err(syntax_error(
+                            format!("Unsupported edition {}", String::from_utf8_lossy(s)),
+                            &located_lexems[ind + 2],
+                        ));
+                    }
                     _ => {
                         return Err(syntax_error(
                             "Invalid syntax statement",
@@ -254,20 +369,39 @@ pub(super) fn parse_package(
                     3,
                     "Not enough lexems for import statement",
                 )?;
-                let import = &located_lexems[ind].lexem;
-                let str = &located_lexems[ind + 1].lexem;
-                let semi_colon = &located_lexems[ind + 2].lexem;
-                match (import, str, semi_colon) {
-                    (Lexem::Id(id), Lexem::StringLiteral(s), Lexem::SemiColon)
-                        if id.deref().eq("import") =>
-                    {
-                        ind += 3;
-                        let imports_components: ImportPath = parse_import_path(s);
+                let import_start = ind;
+                ind += 1;
+                let kind = match &located_lexems[ind].lexem {
+                    Lexem::Id(id) if id.deref() == "public" => ImportKind::Public,
+                    Lexem::Id(id) if id.deref() == "weak" => ImportKind::Weak,
+                    _ => ImportKind::Default,
+                };
+                if kind != ImportKind::Default {
+                    ind += 1;
+                    assert_enough_length(
+                        located_lexems,
+                        ind,
+                        2,
+                        "Not enough lexems for import statement",
+                    )?;
+                }
+                let str = &located_lexems[ind].lexem;
+                let semi_colon = &located_lexems[ind + 1].lexem;
+                match (str, semi_colon) {
+                    (Lexem::StringLiteral(s), Lexem::SemiColon) => {
+                        ind += 2;
+                        let import_path = utf8_literal(s, &located_lexems[ind])?;
+                        let mut imports_components: ImportPath = parse_import_path(&import_path);
+                        imports_components.kind = kind;
+                        imports_components.range = Some(SourceRange {
+                            start: located_lexems[import_start].range.start.clone(),
+                            end: located_lexems[ind - 1].range.end.clone(),
+                        });
                         res.imports.push(imports_components);
                         continue;
                     }
-                    (Lexem::Id(_), Lexem::StringLiteral(_), _) => {
-                        return Err(syntax_error("expected semicolon", &located_lexems[ind + 2]))
+                    (Lexem::StringLiteral(_), _) => {
+                        return Err(unexpected_lexem_error(";", &located_lexems[ind + 1]))
                     }
                     _ => {
                         return Err(syntax_error(
@@ -282,80 +416,49 @@ pub(super) fn parse_package(
                 let lexem = &located_lexem.lexem;
                 match lexem {
                     Lexem::IntLiteral(i) => {
-                        stack.push((*i).into());
+                        stack.push(int64_literal(*i, located_lexem)?.into());
                         ind += 1;
                         continue;
                     }
                     _ => {
-                        return Err(syntax_error("Expected int literal", located_lexem));
+                        return Err(unexpected_lexem_error("integer", located_lexem));
                     }
                 }
             }
-            ParseOptionalAttributes => {
+            ParseCompactOptions => {
+                stack.push(StackItem::OptionsList(Vec::new()));
+                if let Lexem::OpenBracket = located_lexems[ind].lexem {
+                    ind += 1;
+                    tasks.push(ParseCompactOptionsEntries);
+                    tasks.push(PushOption);
+                    tasks.push(ParseOption);
+                }
+                continue;
+            }
+            ParseCompactOptionsEntries => {
                 let located_lexem = &located_lexems[ind];
-                let lexem = &located_lexem.lexem;
-                match lexem {
-                    Lexem::OpenBracket => {
-                        stack.push(StackItem::OptionalAttributes(Some(Vec::new())));
-                        tasks.push(ParseFieldAttributes);
-                        tasks.push(ExpectLexem(Lexem::OpenBracket));
+                match &located_lexem.lexem {
+                    Lexem::CloseBracket => {
+                        ind += 1;
                         continue;
                     }
-                    _ => {
-                        stack.push(StackItem::OptionalAttributes(None));
+                    Lexem::Comma => {
+                        ind += 1;
+                        tasks.push(ParseCompactOptionsEntries);
+                        tasks.push(PushOption);
+                        tasks.push(ParseOption);
                         continue;
                     }
-                }
-            }
-            ParseFieldAttributes => {
-                let located_lexem = &located_lexems[ind];
-                let lexem = &located_lexem.lexem;
-                if let Lexem::CloseBracket = lexem {
-                    ind += 1;
-                    continue;
-                }
-                tasks.push(ParseFieldAttributes);
-                tasks.push(ParseFieldAttribute);
-
-                continue;
-            }
-            ParseFieldAttribute => {
-                tasks.push(PushFieldAttribute);
-                tasks.push(ParseStringLiteral);
-                tasks.push(ExpectLexem(Lexem::Equal));
-                tasks.push(ParseId);
-                continue;
-            }
-            PushFieldAttribute => {
-                let value = match stack.pop() {
-                    Some(StackItem::String(s)) => s,
-                    _ => unreachable!(),
-                };
-                let key = match stack.pop() {
-                    Some(StackItem::String(s)) => s,
-                    invalid_item => {
-                        println!("invalid item = {:?}", invalid_item);
-                        println!("value = {:?}", value);
-                        println!("location = {:?}", located_lexems[ind].range.start);
-                        print_state(stack, tasks, task, &located_lexems[ind - 1..]);
-                        unreachable!();
+                    _ => {
+                        return Err(unexpected_lexem_error(", or ]", located_lexem));
                     }
-                };
-                let optional_list_item = stack.pop().unwrap();
-                let mut optional_list = match optional_list_item {
-                    StackItem::OptionalAttributes(Some(optional_list)) => optional_list,
-                    _ => unreachable!(),
-                };
-                optional_list.push((key, value));
-                stack.push(StackItem::OptionalAttributes(Some(optional_list)));
-                continue;
+                }
             }
             PushFieldDeclaration => {
-                let attributes = match stack.pop() {
-                    Some(StackItem::OptionalAttributes(optional_attributes)) => optional_attributes,
+                let options = match stack.pop() {
+                    Some(StackItem::OptionsList(options)) => options,
                     _ => unreachable!(),
-                }
-                .unwrap_or_default();
+                };
                 let tag = match stack.pop() {
                     Some(StackItem::Int64(tag)) => tag,
                     _ => unreachable!(),
@@ -368,18 +471,80 @@ pub(super) fn parse_package(
                     Some(StackItem::FieldType(field_type)) => field_type,
                     _ => unreachable!(),
                 };
+                let label = match stack.pop() {
+                    Some(StackItem::FieldLabel(label)) => label,
+                    _ => unreachable!(),
+                };
+                let range = match stack.pop() {
+                    Some(StackItem::Range(start)) => SourceRange {
+                        start: start.start,
+                        end: located_lexems[ind - 1].range.end.clone(),
+                    },
+                    _ => unreachable!(),
+                };
+                let comments = read_comments(
+                    located_lexems,
+                    lexem_index(located_lexems, &range.start),
+                    ind - 1,
+                );
+                if res.version == ProtoVersion::Proto3 && find_option(&options, "default").is_some()
+                {
+                    return Err(validation_error(
+                        format!("Default value of field {} is not allowed in proto3", name),
+                        &range,
+                    )
+                    .with_code(ErrorCode::InvalidDefault));
+                }
                 let field_declaration = FieldDeclaration {
                     name,
+                    label,
                     tag,
                     field_type_ref: field_type,
-                    attributes,
+                    options,
+                    range: Some(range),
+                    delimited: false,
+                    packed: false,
+                    comments,
                 };
-                let mut message_entries = match stack.pop() {
-                    Some(StackItem::MessageEntriesList(list)) => list,
+                match stack.last_mut() {
+                    Some(StackItem::Message(message)) => message
+                        .entries
+                        .push(MessageDeclarationEntry::Field(field_declaration)),
+                    Some(StackItem::OneOf(one_of)) => {
+                        if let Some(label) = field_declaration.label {
+                            return Err(validation_error(
+                                format!(
+                                    "Field {} in oneof {} cannot be {}",
+                                    field_declaration.name, one_of.name, label
+                                ),
+                                field_declaration.range.as_ref().unwrap(),
+                            ));
+                        }
+                        one_of.fields.push(field_declaration)
+                    }
+                    Some(StackItem::Extend(extend)) => {
+                        if field_declaration.label == Some(FieldLabel::Required) {
+                            return Err(validation_error(
+                                format!(
+                                    "Extension {} of {} cannot be required",
+                                    field_declaration.name, extend.extendee
+                                ),
+                                field_declaration.range.as_ref().unwrap(),
+                            ));
+                        }
+                        if let FieldTypeReference::Map(_, _) = field_declaration.field_type_ref {
+                            return Err(validation_error(
+                                format!(
+                                    "Extension {} of {} cannot be a map",
+                                    field_declaration.name, extend.extendee
+                                ),
+                                field_declaration.range.as_ref().unwrap(),
+                            ));
+                        }
+                        extend.fields.push(field_declaration)
+                    }
                     _ => unreachable!(),
-                };
-                message_entries.push(MessageDeclarationEntry::Field(field_declaration));
-                stack.push(StackItem::MessageEntriesList(message_entries));
+                }
                 continue;
             }
             ParsePackageStatement => {
@@ -410,7 +575,7 @@ pub(super) fn parse_package(
                             res.path.push(Rc::clone(id));
                         }
                         _ => {
-                            return Err(syntax_error("Expected identifier", id_loc_lexem));
+                            return Err(unexpected_lexem_error("identifier", id_loc_lexem));
                         }
                     }
                     let punct_loc_lexem = &located_lexems[ind];
@@ -424,7 +589,7 @@ pub(super) fn parse_package(
                             break 'listLoop;
                         }
                         _ => {
-                            return Err(syntax_error("Expected dot or semicolon", punct_loc_lexem));
+                            return Err(unexpected_lexem_error(". or ;", punct_loc_lexem));
                         }
                     }
                 }
@@ -438,24 +603,28 @@ pub(super) fn parse_package(
                     "Not enough lexems for enum statement",
                 )?;
 
+                let start = ind;
                 ind += 1;
                 let name_loc_lexem = &located_lexems[ind];
                 let name = &name_loc_lexem.lexem;
-                match name {
-                    Lexem::Id(id) => stack.push(Rc::clone(id).into()),
-                    _ => return Err(syntax_error("Expacted enum name", name_loc_lexem)),
-                }
+                let mut enum_declaration: EnumDeclaration = match name {
+                    Lexem::Id(id) => id_gen.create((Rc::clone(id), Vec::new())),
+                    _ => return Err(unexpected_lexem_error("enum name", name_loc_lexem)),
+                };
                 ind += 1;
                 let curly_open_loc = &located_lexems[ind];
                 let curly_open = &curly_open_loc.lexem;
                 match curly_open {
                     Lexem::OpenCurly => {}
                     _ => {
-                        return Err(syntax_error("Expected curly open", curly_open_loc));
+                        return Err(unexpected_lexem_error("{", curly_open_loc));
                     }
                 }
+                enum_declaration.comments = read_comments(located_lexems, start, ind);
+                enum_declaration.range = Some(name_loc_lexem.range.clone());
                 ind += 1;
-                stack.push(StackItem::EnumEntriesList(Vec::new()));
+                stack.push(enum_declaration.into());
+                tasks.push(ValidateReserved);
                 tasks.push(ParseEnumEntries);
                 continue;
             }
@@ -465,100 +634,129 @@ pub(super) fn parse_package(
                 match separator {
                     Lexem::CloseCurly => {
                         ind += 1;
-                        let list_item = stack.pop().unwrap();
-                        let enum_name_item = stack.pop().unwrap();
-                        match (list_item, enum_name_item) {
-                            (StackItem::EnumEntriesList(entries), StackItem::String(name)) => {
-                                let enum_declaration: EnumDeclaration =
-                                    id_gen.create((name, entries));
-                                stack.push(enum_declaration.into());
-                            }
-                            (a, b) => {
-                                println!("Invalid stack items for enum declaration finishing: {:?} and {:?}", a, b);
-                                print_state(stack, tasks, task, &located_lexems[ind..]);
-                                todo!("Cannot handle separator {:?}", separator);
-                            }
-                        }
+                        continue;
+                    }
+                    Lexem::Id(id) if id.deref() == "option" => {
+                        tasks.push(ParseEnumEntries);
+                        tasks.push(PushOption);
+                        tasks.push(ParseOptionStatement);
+                        continue;
+                    }
+                    Lexem::Id(id) if id.deref() == "reserved" => {
+                        tasks.push(ParseEnumEntries);
+                        tasks.push(ParseReservedStatement);
+                        continue;
                     }
                     Lexem::Id(_) => {
                         tasks.push(ParseEnumEntries);
                         tasks.push(ParseEnumEntry);
                         continue;
                     }
+                    Lexem::SemiColon => {
+                        ind += 1;
+                        tasks.push(ParseEnumEntries);
+                        continue;
+                    }
                     _ => {
-                        print_state(stack, tasks, task, &located_lexems[ind..]);
-                        todo!("Cannot handle separator {:?}", separator);
+                        return Err(unexpected_lexem_error("enum entry or }", loc_separator));
                     }
                 }
             }
             ParseEnumEntry => {
-                assert_enough_length(located_lexems, ind, 4, "Not enough lexems for enum entry")?;
-                let id_loc = &located_lexems[ind];
-                ind += 1;
-                let eq_loc = &located_lexems[ind];
-                ind += 1;
-                let value_loc = &located_lexems[ind];
-                ind += 1;
-                let semi_loc = &located_lexems[ind];
-                ind += 1;
-                match (
-                    &id_loc.lexem,
-                    &eq_loc.lexem,
-                    &value_loc.lexem,
-                    &semi_loc.lexem,
-                ) {
-                    (Lexem::Id(id), Lexem::Equal, Lexem::IntLiteral(value), Lexem::SemiColon) => {
-                        let entries = stack.pop().unwrap();
-                        match entries {
-                            StackItem::EnumEntriesList(mut list) => {
-                                list.push(super::package::EnumEntry {
-                                    name: Rc::clone(id),
-                                    value: *value,
-                                });
-                                stack.push(StackItem::EnumEntriesList(list));
-                            }
-                            _ => {
-                                unreachable!()
-                            }
-                        }
-                        continue;
-                    }
-                    _ => {
-                        print_state(stack, tasks, task, &located_lexems[ind..]);
-                        todo!("Cannot parse enum entry")
+                stack.push(StackItem::Range(located_lexems[ind].range.clone()));
+                tasks.push(PushEnumEntry);
+                tasks.push(ExpectLexem(Lexem::SemiColon));
+                tasks.push(ParseCompactOptions);
+                tasks.push(ParseInt64);
+                tasks.push(ExpectLexem(Lexem::Equal));
+                tasks.push(ParseId);
+                continue;
+            }
+            PushEnumEntry => {
+                let options = match stack.pop() {
+                    Some(StackItem::OptionsList(options)) => options,
+                    _ => unreachable!(),
+                };
+                let value = match stack.pop() {
+                    Some(StackItem::Int64(value)) => value,
+                    _ => unreachable!(),
+                };
+                let name = match stack.pop() {
+                    Some(StackItem::String(name)) => name,
+                    _ => unreachable!(),
+                };
+                let range = match stack.pop() {
+                    Some(StackItem::Range(start)) => SourceRange {
+                        start: start.start,
+                        end: located_lexems[ind - 1].range.end.clone(),
+                    },
+                    _ => unreachable!(),
+                };
+                let comments = read_comments(
+                    located_lexems,
+                    lexem_index(located_lexems, &range.start),
+                    ind - 1,
+                );
+                match stack.last_mut() {
+                    Some(StackItem::Enum(enum_declaration)) => {
+                        enum_declaration.entries.push(EnumEntry {
+                            name,
+                            value,
+                            options,
+                            range: Some(range),
+                            comments,
+                        })
                     }
+                    _ => unreachable!(),
                 }
+                continue;
             }
             ParseMessageStatement => {
-                tasks.push(PushMessageStatement);
+                tasks.push(ValidateReserved);
                 tasks.push(ExpectLexem(Lexem::CloseCurly));
                 tasks.push(ParseMessageEntries);
-                tasks.push(Push(StackItem::MessageEntriesList(Vec::new())));
                 tasks.push(ExpectLexem(Lexem::OpenCurly));
+                tasks.push(StartMessage);
                 tasks.push(ParseId);
                 tasks.push(ExpectLexem(Lexem::Id("message".into())));
                 continue;
             }
-            Push(stack_item) => {
-                stack.push(stack_item);
-                continue;
-            }
-            PushMessageStatement => {
-                let entries = match stack.pop() {
-                    Some(StackItem::MessageEntriesList(entries)) => entries,
-                    _ => unreachable!(),
-                };
+            StartMessage => {
                 let message_name = match stack.pop() {
                     Some(StackItem::String(name)) => name,
                     _ => unreachable!(),
                 };
-                let message_declaration: MessageDeclaration = id_gen.create((
-                    message_name,
-                    entries
-                ));
+                let mut message_declaration: MessageDeclaration =
+                    id_gen.create((message_name, Vec::new()));
+                // `message` keyword and name are already parsed, `{` is the next lexem
+                message_declaration.comments = read_comments(located_lexems, ind - 2, ind);
+                message_declaration.range = Some(located_lexems[ind - 1].range.clone());
                 stack.push(message_declaration.into());
                 continue;
             }
+            StartOneOf => {
+                let one_of_name = match stack.pop() {
+                    Some(StackItem::String(name)) => name,
+                    _ => unreachable!(),
+                };
+                stack.push(StackItem::OneOf(OneOfDeclaration {
+                    name: one_of_name,
+                    fields: Vec::new(),
+                    options: Vec::new(),
+                    comments: read_comments(located_lexems, ind - 2, ind),
+                }));
+                continue;
+            }
+            ValidateReserved => {
+                match stack.last() {
+                    Some(StackItem::Message(message)) => validate_message_reserved(message)?,
+                    Some(StackItem::Enum(enum_declaration)) => {
+                        validate_enum_reserved(enum_declaration)?
+                    }
+                    _ => unreachable!(),
+                }
+                continue;
+            }
             WrapMessageEntry => {
                 let entry = match stack.pop() {
                     Some(StackItem::Message(message_declaration)) => {
@@ -582,19 +780,24 @@ pub(super) fn parse_package(
                     Some(StackItem::MessageEntry(entry)) => entry,
                     _ => unreachable!(),
                 };
-                let mut entries = match stack.pop() {
-                    Some(StackItem::MessageEntriesList(entries)) => entries,
+                match stack.last_mut() {
+                    Some(StackItem::Message(message)) => message.entries.push(message_entry),
+                    Some(StackItem::OneOf(_)) => {
+                        return Err(syntax_error(
+                            "oneof can contain only field declarations",
+                            &located_lexems[ind - 1],
+                        ));
+                    }
                     _ => unreachable!(),
-                };
-                entries.push(message_entry);
-                stack.push(StackItem::MessageEntriesList(entries));
+                }
                 continue;
             }
             ParseMessageEntries => {
                 let loc_separator = &located_lexems[ind];
                 let separator = &loc_separator.lexem;
                 match separator {
-                    Lexem::Id(_) => {
+                    // Field type may be fully qualified name starting with `.`
+                    Lexem::Id(_) | Lexem::Dot => {
                         tasks.push(ParseMessageEntries);
                         tasks.push(ParseMessageEntry);
                         continue;
@@ -602,15 +805,38 @@ pub(super) fn parse_package(
                     Lexem::CloseCurly => {
                         continue;
                     }
+                    Lexem::SemiColon => {
+                        ind += 1;
+                        tasks.push(ParseMessageEntries);
+                        continue;
+                    }
                     _ => {
-                        print_state(stack, tasks, task, &located_lexems[ind..]);
-                        todo!("Cannot handle separator {:?}", separator);
+                        return Err(unexpected_lexem_error("message entry or }", loc_separator));
                     }
                 }
             }
             ParseMessageEntry => {
                 let start_loc = &located_lexems[ind];
                 let start = &start_loc.lexem;
+                if let Some(StackItem::Extend(_)) = stack.last() {
+                    if let Lexem::Id(id) = start {
+                        let keywords = [
+                            "message",
+                            "enum",
+                            "oneof",
+                            "option",
+                            "reserved",
+                            "extensions",
+                            "extend",
+                        ];
+                        if keywords.contains(&id.deref()) {
+                            return Err(syntax_error(
+                                "extend can contain only field declarations",
+                                start_loc,
+                            ));
+                        }
+                    }
+                }
                 match start {
                     Lexem::Id(id) if id.deref() == "message" => {
                         tasks.push(PushMessageEntry);
@@ -627,67 +853,314 @@ pub(super) fn parse_package(
                     Lexem::Id(id) if id.deref() == "oneof" => {
                         tasks.push(PushMessageEntry);
                         tasks.push(WrapMessageEntry);
-                        tasks.push(PushOneOf);
                         tasks.push(ExpectLexem(Lexem::CloseCurly));
                         tasks.push(ParseMessageEntries);
                         tasks.push(ExpectLexem(Lexem::OpenCurly));
-                        tasks.push(Push(StackItem::MessageEntriesList(Vec::new())));
+                        tasks.push(StartOneOf);
                         tasks.push(ParseId);
                         tasks.push(ExpectLexem(Lexem::Id("oneof".into())));
                         continue;
                     }
-                    Lexem::Id(id) if id.deref() == "enum" => {
-                        print_state(stack, tasks, task, &located_lexems[ind..]);
-                        todo!("Cannot handle start message entry {:?}", start)
+                    Lexem::Id(id) if id.deref() == "option" => {
+                        tasks.push(PushOption);
+                        tasks.push(ParseOptionStatement);
+                        continue;
                     }
-                    Lexem::Id(_) => {
+                    Lexem::Id(id) if id.deref() == "reserved" => {
+                        tasks.push(ParseReservedStatement);
+                        continue;
+                    }
+                    Lexem::Id(id) if id.deref() == "extensions" => {
+                        tasks.push(ParseExtensionsStatement);
+                        continue;
+                    }
+                    Lexem::Id(id) if id.deref() == "extend" => {
+                        tasks.push(ParseExtendStatement);
+                        continue;
+                    }
+                    Lexem::Id(_) if is_group_start(&located_lexems[ind..]) => {
+                        tasks.push(ParseGroup);
+                        continue;
+                    }
+                    Lexem::Id(_) | Lexem::Dot => {
                         tasks.push(ParseFieldDeclaration);
                         continue;
                     }
                     _ => {
-                        print_state(stack, tasks, task, &located_lexems[ind..]);
-                        todo!("Cannot handle start message entry {:?}", start)
+                        return Err(unexpected_lexem_error("message entry", start_loc));
+                    }
+                }
+            }
+            ParseGroup => {
+                let start_loc = &located_lexems[ind];
+                if res.version == ProtoVersion::Proto3 {
+                    return Err(syntax_error("Groups are not allowed in proto3", start_loc));
+                }
+                if res.version == ProtoVersion::Edition2023 {
+                    return Err(syntax_error(
+                        "Groups are not allowed in editions, use features.message_encoding",
+                        start_loc,
+                    ));
+                }
+                let (label, repeated) = match &start_loc.lexem {
+                    Lexem::Id(id) if id.deref() == "optional" => {
+                        (Some(FieldLabel::Optional), false)
+                    }
+                    Lexem::Id(id) if id.deref() == "required" => {
+                        (Some(FieldLabel::Required), false)
                     }
+                    Lexem::Id(id) if id.deref() == "repeated" => (None, true),
+                    _ => (None, false),
+                };
+                if label.is_some() || repeated {
+                    ind += 1;
                 }
+                stack.push(StackItem::Range(start_loc.range.clone()));
+                stack.push(StackItem::FieldLabel(label));
+                stack.push(StackItem::Bool(repeated));
+                tasks.push(PushGroup);
+                tasks.push(ValidateReserved);
+                tasks.push(ExpectLexem(Lexem::CloseCurly));
+                tasks.push(ParseMessageEntries);
+                tasks.push(ExpectLexem(Lexem::OpenCurly));
+                tasks.push(StartGroup);
+                tasks.push(ParseCompactOptions);
+                tasks.push(ParseInt64);
+                tasks.push(ExpectLexem(Lexem::Equal));
+                tasks.push(ParseId);
+                tasks.push(ExpectLexem(Lexem::Id("group".into())));
+                continue;
+            }
+            StartGroup => {
+                let group_name = stack
+                    .iter()
+                    .rev()
+                    .find_map(|item| match item {
+                        StackItem::String(name) => Some(Rc::clone(name)),
+                        _ => None,
+                    })
+                    .unwrap();
+                let group_start = stack
+                    .iter()
+                    .rev()
+                    .find_map(|item| match item {
+                        StackItem::Range(range) => Some(lexem_index(located_lexems, &range.start)),
+                        _ => None,
+                    })
+                    .unwrap();
+                let mut message_declaration: MessageDeclaration =
+                    id_gen.create((group_name, Vec::new()));
+                message_declaration.comments = read_comments(located_lexems, group_start, ind);
+                message_declaration.range = Some(located_lexems[group_start].range.clone());
+                stack.push(message_declaration.into());
+                continue;
             }
-            PushOneOf => {
-                let message_entries = match stack.pop() {
-                    Some(StackItem::MessageEntriesList(entries)) => entries,
+            PushGroup => {
+                let message_declaration = match stack.pop() {
+                    Some(StackItem::Message(message_declaration)) => message_declaration,
                     _ => unreachable!(),
                 };
-                let one_of_name = match stack.pop() {
+                let options = match stack.pop() {
+                    Some(StackItem::OptionsList(options)) => options,
+                    _ => unreachable!(),
+                };
+                let tag = match stack.pop() {
+                    Some(StackItem::Int64(tag)) => tag,
+                    _ => unreachable!(),
+                };
+                let group_name = match stack.pop() {
                     Some(StackItem::String(name)) => name,
                     _ => unreachable!(),
                 };
-                if message_entries.iter().any(|entry| match entry {
-                    MessageDeclarationEntry::Field(_) => false,
-                    _ => true,
-                }) {
+                let repeated = match stack.pop() {
+                    Some(StackItem::Bool(repeated)) => repeated,
+                    _ => unreachable!(),
+                };
+                let label = match stack.pop() {
+                    Some(StackItem::FieldLabel(label)) => label,
+                    _ => unreachable!(),
+                };
+                let range = match stack.pop() {
+                    Some(StackItem::Range(start)) => SourceRange {
+                        start: start.start,
+                        end: located_lexems[ind - 1].range.end.clone(),
+                    },
+                    _ => unreachable!(),
+                };
+                let group_type = FieldTypeReference::id(&group_name);
+                let field_declaration = FieldDeclaration {
+                    name: group_name.to_lowercase().into(),
+                    label,
+                    field_type_ref: if repeated {
+                        FieldTypeReference::repeated(group_type)
+                    } else {
+                        group_type
+                    },
+                    tag,
+                    options,
+                    range: Some(range),
+                    delimited: true,
+                    packed: false,
+                    comments: message_declaration.comments.clone(),
+                };
+                match stack.last_mut() {
+                    Some(StackItem::Message(message)) => message
+                        .entries
+                        .push(MessageDeclarationEntry::Field(field_declaration)),
+                    Some(StackItem::OneOf(one_of)) => {
+                        if label.is_some() || repeated {
+                            return Err(validation_error(
+                                format!(
+                                    "Group {} in oneof {} cannot have label",
+                                    group_name, one_of.name
+                                ),
+                                field_declaration.range.as_ref().unwrap(),
+                            ));
+                        }
+                        one_of.fields.push(field_declaration)
+                    }
+                    Some(StackItem::Extend(extend)) => extend.fields.push(field_declaration),
+                    _ => unreachable!(),
+                }
+                // Nested message of a group inside oneof or extend belongs to the enclosing
+                // message or to the file for top level extend
+                let parent_message = stack.iter_mut().rev().find_map(|item| match item {
+                    StackItem::Message(message) => Some(message),
+                    _ => None,
+                });
+                let declaration: Declaration = message_declaration.into();
+                match parent_message {
+                    Some(message) => message.entries.push(declaration.into()),
+                    None => res.declarations.push(declaration),
+                }
+                continue;
+            }
+            ParseExtensionsStatement => {
+                let start_loc = &located_lexems[ind];
+                if res.version == ProtoVersion::Proto3 {
                     return Err(syntax_error(
-                        "oneof can contain only field declarations",
-                        &located_lexems[ind],
+                        "Extension ranges are not allowed in proto3",
+                        start_loc,
                     ));
                 }
-                let one_of_declaration = OneOfDeclaration {
-                    name: one_of_name,
-                    options: message_entries
-                        .iter()
-                        .filter_map(|entry| match entry {
-                            MessageDeclarationEntry::Field(field_decl) => {
-                                Some(field_decl.to_owned())
-                            }
-                            _ => None,
-                        })
-                        .collect::<Vec<FieldDeclaration>>(),
+                ind += 1;
+                let mut ranges = vec![parse_tag_range(located_lexems, &mut ind, MAX_FIELD_NUMBER)?];
+                while located_lexems[ind].lexem == Lexem::Comma {
+                    ind += 1;
+                    ranges.push(parse_tag_range(located_lexems, &mut ind, MAX_FIELD_NUMBER)?);
+                }
+                stack.push(StackItem::Range(start_loc.range.clone()));
+                stack.push(StackItem::TagRanges(ranges));
+                tasks.push(PushExtensions);
+                tasks.push(ExpectLexem(Lexem::SemiColon));
+                tasks.push(ParseCompactOptions);
+                continue;
+            }
+            PushExtensions => {
+                let options = match stack.pop() {
+                    Some(StackItem::OptionsList(options)) => options,
+                    _ => unreachable!(),
+                };
+                let ranges = match stack.pop() {
+                    Some(StackItem::TagRanges(ranges)) => ranges,
+                    _ => unreachable!(),
                 };
-                stack.push(one_of_declaration.into());
+                let range = match stack.pop() {
+                    Some(StackItem::Range(start)) => SourceRange {
+                        start: start.start,
+                        end: located_lexems[ind - 1].range.end.clone(),
+                    },
+                    _ => unreachable!(),
+                };
+                let extensions = Extensions {
+                    ranges,
+                    options,
+                    range,
+                };
+                match stack.last_mut() {
+                    Some(StackItem::Message(message)) => message.extensions.push(extensions),
+                    _ => {
+                        return Err(validation_error(
+                            "extensions are allowed only in messages",
+                            &extensions.range,
+                        ));
+                    }
+                }
                 continue;
             }
-            ParseFieldType => {
-                let start_loc = &located_lexems[ind];
-                let start = &start_loc.lexem;
-                if let Lexem::Id(id) = start {
-                    if id.deref() == "repeated" {
+            ParseExtendStatement => {
+                stack.push(StackItem::Range(located_lexems[ind].range.clone()));
+                tasks.push(PushExtend);
+                tasks.push(ExpectLexem(Lexem::CloseCurly));
+                tasks.push(ParseMessageEntries);
+                tasks.push(ExpectLexem(Lexem::OpenCurly));
+                tasks.push(StartExtend);
+                tasks.push(ParseIdPath);
+                tasks.push(ExpectLexem(Lexem::Id("extend".into())));
+                continue;
+            }
+            StartExtend => {
+                let extendee = match stack.pop() {
+                    Some(StackItem::StringList(ids)) => FieldTypeReference::from(ids),
+                    _ => unreachable!(),
+                };
+                let range = match stack.pop() {
+                    Some(StackItem::Range(range)) => range,
+                    _ => unreachable!(),
+                };
+                stack.push(StackItem::Extend(ExtendDeclaration {
+                    extendee,
+                    fields: Vec::new(),
+                    range,
+                }));
+                continue;
+            }
+            PushExtend => {
+                let mut extend = match stack.pop() {
+                    Some(StackItem::Extend(extend)) => extend,
+                    _ => unreachable!(),
+                };
+                extend.range.end = located_lexems[ind - 1].range.end.clone();
+                let range = extend.range.clone();
+                let declaration: Declaration = extend.into();
+                match stack.last_mut() {
+                    Some(StackItem::Message(message)) => message.entries.push(declaration.into()),
+                    None => res.declarations.push(declaration),
+                    _ => {
+                        return Err(validation_error(
+                            "extend is allowed only in files and messages",
+                            &range,
+                        ));
+                    }
+                }
+                continue;
+            }
+            ParseReservedStatement => {
+                let max = match stack.last() {
+                    Some(StackItem::Enum(_)) => MAX_ENUM_VALUE,
+                    _ => MAX_FIELD_NUMBER,
+                };
+                let editions = res.version == ProtoVersion::Edition2023;
+                let reserved = parse_reserved(located_lexems, &mut ind, max, editions)?;
+                match stack.last_mut() {
+                    Some(StackItem::Message(message)) => message.reserved.push(reserved),
+                    Some(StackItem::Enum(enum_declaration)) => {
+                        enum_declaration.reserved.push(reserved)
+                    }
+                    _ => {
+                        return Err(validation_error(
+                            "reserved is allowed only in messages and enums",
+                            &reserved.range,
+                        ));
+                    }
+                }
+                continue;
+            }
+            ParseFieldType => {
+                let start_loc = &located_lexems[ind];
+                let start = &start_loc.lexem;
+                if let Lexem::Id(id) = start {
+                    if id.deref() == "repeated" {
                         tasks.push(WrapRepeated);
                         tasks.push(ParseFieldType);
                         ind += 1;
@@ -708,7 +1181,12 @@ pub(super) fn parse_package(
                     tasks.push(ParseIdPath);
                     continue;
                 }
-                return Err(syntax_error("Expected lexem", start_loc));
+                if start == &Lexem::Dot {
+                    tasks.push(WrapFieldType);
+                    tasks.push(ParseIdPath);
+                    continue;
+                }
+                return Err(unexpected_lexem_error("field type", start_loc));
             }
             WrapFieldType => {
                 let field_type = match stack.pop() {
@@ -720,6 +1198,11 @@ pub(super) fn parse_package(
             }
             ParseIdPath => {
                 let mut id_path = Vec::new();
+                // Leading dot of fully qualified name is kept as empty first part
+                if located_lexems[ind].lexem == Lexem::Dot {
+                    id_path.push(Rc::from(""));
+                    ind += 1;
+                }
                 loop {
                     let id_loc_lexem = &located_lexems[ind];
                     ind += 1;
@@ -729,7 +1212,7 @@ pub(super) fn parse_package(
                             id_path.push(id.clone());
                         }
                         _ => {
-                            return Err(syntax_error("Expected identifier", id_loc_lexem));
+                            return Err(unexpected_lexem_error("identifier", id_loc_lexem));
                         }
                     }
                     let punct_loc_lexem = &located_lexems[ind];
@@ -754,20 +1237,58 @@ pub(super) fn parse_package(
                         stack.push(FieldTypeReference::repeated(field_type).into());
                         continue;
                     }
-                    _ => {
-                        print_state(stack, tasks, task, &located_lexems[ind..]);
-                        todo!("Cannot handle repeated field type")
-                    }
+                    _ => unreachable!(),
                 }
             }
             ParseFieldDeclaration => {
+                stack.push(StackItem::Range(located_lexems[ind].range.clone()));
                 tasks.push(PushFieldDeclaration);
                 tasks.push(ExpectLexem(Lexem::SemiColon));
-                tasks.push(ParseOptionalAttributes);
+                tasks.push(ParseCompactOptions);
                 tasks.push(ParseInt64);
                 tasks.push(ExpectLexem(Lexem::Equal));
                 tasks.push(ParseId);
                 tasks.push(ParseFieldType);
+                tasks.push(ParseFieldLabel);
+                continue;
+            }
+            ParseFieldLabel => {
+                let label = match &located_lexems[ind].lexem {
+                    Lexem::Id(id) if id.deref() == "optional" => Some(FieldLabel::Optional),
+                    Lexem::Id(id) if id.deref() == "required" => {
+                        if res.version == ProtoVersion::Proto3 {
+                            return Err(syntax_error(
+                                "Required fields are not allowed in proto3",
+                                &located_lexems[ind],
+                            ));
+                        }
+                        Some(FieldLabel::Required)
+                    }
+                    _ => None,
+                };
+                if let Some(label) = label {
+                    if res.version == ProtoVersion::Edition2023 {
+                        return Err(syntax_error(
+                            format!(
+                                "Label {} is not allowed in editions, use features.field_presence",
+                                label
+                            ),
+                            &located_lexems[ind],
+                        ));
+                    }
+                    ind += 1;
+                    let type_loc_lexem = &located_lexems[ind];
+                    match &type_loc_lexem.lexem {
+                        Lexem::Id(id) if id.deref() == "repeated" || id.deref() == "map" => {
+                            return Err(unexpected_lexem_error(
+                                format!("field type after {}", label),
+                                type_loc_lexem,
+                            ));
+                        }
+                        _ => {}
+                    }
+                }
+                stack.push(StackItem::FieldLabel(label));
                 continue;
             }
             ExpectLexem(expected_lexem) => {
@@ -775,7 +1296,7 @@ pub(super) fn parse_package(
                     located_lexems,
                     ind,
                     1,
-                    format!("Expected lexem: {:?}", expected_lexem),
+                    format!("Expected {}", expected_lexem),
                 )?;
                 let loc_lexem = &located_lexems[ind];
                 let lexem = &loc_lexem.lexem;
@@ -783,8 +1304,8 @@ pub(super) fn parse_package(
                     ind += 1;
                     continue;
                 }
-                return Err(syntax_error(
-                    format!("Expected lexem: {:?}", expected_lexem),
+                return Err(unexpected_lexem_error(
+                    expected_lexem.to_string(),
                     loc_lexem,
                 ));
             }
@@ -799,28 +1320,291 @@ pub(super) fn parse_package(
                         continue;
                     }
                     _ => {
-                        return Err(syntax_error("expected identifier", loc_lexem));
+                        return Err(unexpected_lexem_error("identifier", loc_lexem));
                     }
                 }
             }
-            ParseStringLiteral => {
-                assert_enough_length(located_lexems, ind, 1, "Expected identifier")?;
+            ParseServiceStatement => {
+                tasks.push(ExpectLexem(Lexem::CloseCurly));
+                tasks.push(ParseServiceEntries);
+                tasks.push(ExpectLexem(Lexem::OpenCurly));
+                tasks.push(StartService);
+                tasks.push(ParseId);
+                tasks.push(ExpectLexem(Lexem::Id("service".into())));
+                continue;
+            }
+            StartService => {
+                let service_name = match stack.pop() {
+                    Some(StackItem::String(name)) => name,
+                    _ => unreachable!(),
+                };
+                let mut service_declaration = ServiceDeclaration::new(service_name);
+                service_declaration.range = Some(located_lexems[ind - 1].range.clone());
+                stack.push(StackItem::Service(service_declaration));
+                continue;
+            }
+            ParseServiceEntries => {
                 let loc_lexem = &located_lexems[ind];
-                let lexem = &loc_lexem.lexem;
-                match lexem {
-                    Lexem::StringLiteral(found_literal) => {
+                match &loc_lexem.lexem {
+                    Lexem::CloseCurly => {
+                        continue;
+                    }
+                    Lexem::SemiColon => {
+                        ind += 1;
+                        tasks.push(ParseServiceEntries);
+                        continue;
+                    }
+                    Lexem::Id(id) if id.deref() == "rpc" => {
+                        // Method is located by its name
+                        stack.push(StackItem::Range(located_lexems[ind + 1].range.clone()));
+                        tasks.push(ParseServiceEntries);
+                        tasks.push(PushMethod);
+                        tasks.push(ParseMethodStatement);
+                        continue;
+                    }
+                    Lexem::Id(id) if id.deref() == "option" => {
+                        tasks.push(ParseServiceEntries);
+                        tasks.push(PushOption);
+                        tasks.push(ParseOptionStatement);
+                        continue;
+                    }
+                    _ => {
+                        return Err(unexpected_lexem_error("rpc or option", loc_lexem));
+                    }
+                }
+            }
+            ParseMethodStatement => {
+                tasks.push(ParseMethodBody);
+                tasks.push(WrapMethod);
+                tasks.push(ExpectLexem(Lexem::CloseParen));
+                tasks.push(ParseStreamType);
+                tasks.push(ExpectLexem(Lexem::OpenParen));
+                tasks.push(ExpectLexem(Lexem::Id("returns".into())));
+                tasks.push(ExpectLexem(Lexem::CloseParen));
+                tasks.push(ParseStreamType);
+                tasks.push(ExpectLexem(Lexem::OpenParen));
+                tasks.push(ParseId);
+                tasks.push(ExpectLexem(Lexem::Id("rpc".into())));
+                continue;
+            }
+            ParseStreamType => {
+                let is_stream = match (&located_lexems[ind].lexem, located_lexems.get(ind + 1)) {
+                    (Lexem::Id(id), Some(next)) => {
+                        // `stream .pkg.Msg` streams fully qualified type
+                        id.deref() == "stream" && matches!(next.lexem, Lexem::Id(_) | Lexem::Dot)
+                    }
+                    _ => false,
+                };
+                if is_stream {
+                    ind += 1;
+                }
+                stack.push(StackItem::Bool(is_stream));
+                tasks.push(WrapFieldType);
+                tasks.push(ParseIdPath);
+                continue;
+            }
+            WrapMethod => {
+                let output_type_ref = match stack.pop() {
+                    Some(StackItem::FieldType(field_type)) => field_type,
+                    _ => unreachable!(),
+                };
+                let server_streaming = match stack.pop() {
+                    Some(StackItem::Bool(b)) => b,
+                    _ => unreachable!(),
+                };
+                let input_type_ref = match stack.pop() {
+                    Some(StackItem::FieldType(field_type)) => field_type,
+                    _ => unreachable!(),
+                };
+                let client_streaming = match stack.pop() {
+                    Some(StackItem::Bool(b)) => b,
+                    _ => unreachable!(),
+                };
+                let name = match stack.pop() {
+                    Some(StackItem::String(name)) => name,
+                    _ => unreachable!(),
+                };
+                let range = match stack.pop() {
+                    Some(StackItem::Range(range)) => range,
+                    _ => unreachable!(),
+                };
+                stack.push(StackItem::Method(MethodDeclaration {
+                    name,
+                    input_type_ref,
+                    output_type_ref,
+                    client_streaming,
+                    server_streaming,
+                    options: Vec::new(),
+                    range: Some(range),
+                }));
+                continue;
+            }
+            ParseMethodBody => {
+                let loc_lexem = &located_lexems[ind];
+                match &loc_lexem.lexem {
+                    Lexem::SemiColon => {
+                        ind += 1;
+                        continue;
+                    }
+                    Lexem::OpenCurly => {
+                        ind += 1;
+                        tasks.push(ParseMethodOptions);
+                        continue;
+                    }
+                    _ => {
+                        return Err(unexpected_lexem_error("; or method body", loc_lexem));
+                    }
+                }
+            }
+            ParseMethodOptions => {
+                let loc_lexem = &located_lexems[ind];
+                match &loc_lexem.lexem {
+                    Lexem::CloseCurly => {
+                        ind += 1;
+                        continue;
+                    }
+                    Lexem::SemiColon => {
                         ind += 1;
-                        stack.push(found_literal.clone().into());
+                        tasks.push(ParseMethodOptions);
+                        continue;
+                    }
+                    Lexem::Id(id) if id.deref() == "option" => {
+                        tasks.push(ParseMethodOptions);
+                        tasks.push(PushOption);
+                        tasks.push(ParseOptionStatement);
                         continue;
                     }
                     _ => {
-                        return Err(syntax_error("expected identifier", loc_lexem));
+                        return Err(unexpected_lexem_error("option", loc_lexem));
                     }
                 }
             }
+            PushMethod => {
+                let method = match stack.pop() {
+                    Some(StackItem::Method(method)) => method,
+                    _ => unreachable!(),
+                };
+                match stack.last_mut() {
+                    Some(StackItem::Service(service)) => service.methods.push(method),
+                    _ => unreachable!(),
+                }
+                continue;
+            }
+            ParseOptionStatement => {
+                tasks.push(ExpectLexem(Lexem::SemiColon));
+                tasks.push(ParseOption);
+                tasks.push(ExpectLexem(Lexem::Id("option".into())));
+                continue;
+            }
+            ParseOption => {
+                tasks.push(WrapOption);
+                tasks.push(ParseOptionValue);
+                tasks.push(ExpectLexem(Lexem::Equal));
+                tasks.push(ParseOptionName);
+                continue;
+            }
+            ParseOptionName => {
+                let option_name = parse_option_name(located_lexems, &mut ind)?;
+                stack.push(StackItem::OptionName(option_name));
+                continue;
+            }
+            ParseOptionValue => {
+                let option_value = parse_option_value(located_lexems, &mut ind)?;
+                stack.push(StackItem::OptionValue(option_value));
+                continue;
+            }
+            WrapOption => {
+                let value = match stack.pop() {
+                    Some(StackItem::OptionValue(value)) => value,
+                    _ => unreachable!(),
+                };
+                let name = match stack.pop() {
+                    Some(StackItem::OptionName(name)) => name,
+                    _ => unreachable!(),
+                };
+                stack.push(StackItem::Option(ProtoOption { name, value }));
+                continue;
+            }
+            PushOption => {
+                let option = match stack.pop() {
+                    Some(StackItem::Option(option)) => option,
+                    _ => unreachable!(),
+                };
+                let declaration_options = stack.iter_mut().rev().find_map(|item| match item {
+                    StackItem::OptionsList(options) => Some(options),
+                    StackItem::Message(message) => Some(&mut message.options),
+                    StackItem::Enum(enum_declaration) => Some(&mut enum_declaration.options),
+                    StackItem::OneOf(one_of) => Some(&mut one_of.options),
+                    StackItem::Service(service) => Some(&mut service.options),
+                    StackItem::Method(method) => Some(&mut method.options),
+                    _ => None,
+                });
+                match declaration_options {
+                    Some(options) => options.push(option),
+                    None => res.options.push(option),
+                }
+                continue;
+            }
+        }
+    }
+    Ok(ind)
+}
+
+/// Collects comments of declaration spanning lexems from `start` to `end`
+fn read_comments(located_lexems: &[LocatedLexem], start: usize, end: usize) -> Comments {
+    let start_lexem = &located_lexems[start];
+    // Trailing comments before the declaration belong to the previous one
+    let mut comments = start_lexem
+        .comments
+        .iter()
+        .filter(|comment| !comment.trailing)
+        .collect::<Vec<_>>();
+    let leading = match comments.last() {
+        Some(comment) if comment.range.end.line + 1 >= start_lexem.range.start.line => {
+            comments.pop().map(|comment| comment.text.as_str().into())
         }
+        _ => None,
+    };
+    let trailing = located_lexems
+        .get(end + 1)
+        .and_then(|next_lexem| next_lexem.comments.first())
+        .filter(|comment| comment.trailing)
+        .map(|comment| comment.text.as_str().into());
+    Comments {
+        leading,
+        trailing,
+        detached: comments
+            .iter()
+            .map(|comment| comment.text.as_str().into())
+            .collect(),
+    }
+}
+
+/// Finds index of the lexem which starts at `position`
+fn lexem_index(located_lexems: &[LocatedLexem], position: &Position) -> usize {
+    located_lexems.partition_point(|located_lexem| {
+        let start = &located_lexem.range.start;
+        (start.line, start.column) < (position.line, position.column)
+    })
+}
+
+/// Text of string literal, which must be valid UTF-8 everywhere except `bytes` values
+fn utf8_literal(bytes: &[u8], located_lexem: &LocatedLexem) -> Result<Rc<str>, ProtoError> {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => Ok(s.into()),
+        Err(_) => Err(ProtoError::InvalidStringLiteral {
+            message: "String literal is not valid UTF-8".into(),
+            range: located_lexem.range.clone(),
+        }),
     }
-    Ok(())
+}
+
+/// Value of integer literal, which must fit `int64` everywhere except `uint64` values
+fn int64_literal(value: i128, located_lexem: &LocatedLexem) -> Result<i64, ProtoError> {
+    i64::try_from(value).map_err(|_| ProtoError::InvalidIntLiteral {
+        literal: value.to_string(),
+        range: located_lexem.range.clone(),
+    })
 }
 
 fn parse_import_path(s: &str) -> ImportPath {
@@ -834,9 +1618,428 @@ fn parse_import_path(s: &str) -> ImportPath {
     return ImportPath {
         packages,
         file_name,
+        kind: ImportKind::Default,
+        range: None,
     };
 }
 
+/// Checks for `[label] group Name = 1 {` or `[label] group Name = 1 [`,
+/// as `group` alone may be a name of message type
+fn is_group_start(located_lexems: &[LocatedLexem]) -> bool {
+    let lexems = located_lexems
+        .iter()
+        .take(6)
+        .map(|l| &l.lexem)
+        .collect::<Vec<_>>();
+    let lexems = match &lexems[..] {
+        [Lexem::Id(label), rest @ ..]
+            if matches!(label.deref(), "optional" | "required" | "repeated") =>
+        {
+            rest
+        }
+        all => all,
+    };
+    matches!(
+        lexems,
+        [Lexem::Id(group), Lexem::Id(_), Lexem::Equal, Lexem::IntLiteral(_), Lexem::OpenCurly | Lexem::OpenBracket, ..]
+            if group.deref() == "group"
+    )
+}
+
+/// Parses `reserved 2, 9 to 11, 40 to max;` or `reserved "foo", "bar";`,
+/// editions write names as identifiers, e.g. `reserved foo, bar;`
+fn parse_reserved(
+    located_lexems: &[LocatedLexem],
+    ind: &mut usize,
+    max: i64,
+    editions: bool,
+) -> Result<Reserved, ProtoError> {
+    let start = located_lexems[*ind].range.start.clone();
+    *ind += 1;
+    let mut ranges = Vec::new();
+    let mut names = Vec::new();
+    loop {
+        let loc_lexem = &located_lexems[*ind];
+        match &loc_lexem.lexem {
+            Lexem::StringLiteral(_) if editions => {
+                return Err(syntax_error(
+                    "Reserved names must be identifiers in editions, not string literals",
+                    loc_lexem,
+                ));
+            }
+            Lexem::StringLiteral(name) => {
+                *ind += 1;
+                names.push(utf8_literal(name, loc_lexem)?);
+            }
+            Lexem::Id(name) if editions => {
+                *ind += 1;
+                names.push(Rc::clone(name));
+            }
+            Lexem::IntLiteral(_) => {
+                ranges.push(parse_tag_range(located_lexems, ind, max)?);
+            }
+            _ => {
+                return Err(unexpected_lexem_error("field number or name", loc_lexem));
+            }
+        }
+        let separator = &located_lexems[*ind];
+        *ind += 1;
+        match separator.lexem {
+            Lexem::Comma => {}
+            Lexem::SemiColon => {
+                return Ok(Reserved {
+                    ranges,
+                    names,
+                    range: SourceRange {
+                        start,
+                        end: separator.range.end.clone(),
+                    },
+                });
+            }
+            _ => {
+                return Err(unexpected_lexem_error(", or ;", separator));
+            }
+        }
+    }
+}
+
+/// Parses `9`, `9 to 11` or `9 to max`
+fn parse_tag_range(
+    located_lexems: &[LocatedLexem],
+    ind: &mut usize,
+    max: i64,
+) -> Result<TagRange, ProtoError> {
+    let loc_lexem = &located_lexems[*ind];
+    let range_start = match &loc_lexem.lexem {
+        Lexem::IntLiteral(start) => int64_literal(*start, loc_lexem)?,
+        _ => return Err(unexpected_lexem_error("integer", loc_lexem)),
+    };
+    *ind += 1;
+    let range_end = match &located_lexems[*ind].lexem {
+        Lexem::Id(id) if id.deref() == "to" => {
+            *ind += 1;
+            let end_loc_lexem = &located_lexems[*ind];
+            *ind += 1;
+            match &end_loc_lexem.lexem {
+                Lexem::IntLiteral(end) => int64_literal(*end, end_loc_lexem)?,
+                Lexem::Id(id) if id.deref() == "max" => max,
+                _ => {
+                    return Err(unexpected_lexem_error("integer or max", end_loc_lexem));
+                }
+            }
+        }
+        _ => range_start,
+    };
+    if range_start > range_end {
+        return Err(validation_error(
+            format!("Invalid range {} to {}", range_start, range_end),
+            &loc_lexem.range,
+        ));
+    }
+    Ok(TagRange {
+        start: range_start,
+        end: range_end,
+    })
+}
+
+fn validate_message_reserved(message: &MessageDeclaration) -> Result<(), ProtoError> {
+    let fields = message.entries.iter().flat_map(|entry| match entry {
+        MessageDeclarationEntry::Field(field) => vec![field],
+        MessageDeclarationEntry::OneOf(one_of) => one_of.fields.iter().collect(),
+        MessageDeclarationEntry::Declaration(_) => vec![],
+    });
+    let mut errors = Vec::new();
+    for field in fields {
+        for reserved in &message.reserved {
+            let range = field.range.as_ref().unwrap_or(&reserved.range);
+            if reserved.contains_tag(field.tag) {
+                errors.push(
+                    validation_error(
+                        format!(
+                            "Field {} in message {} uses reserved number {}",
+                            field.name, message.name, field.tag
+                        ),
+                        range,
+                    )
+                    .with_code(ErrorCode::ReservedNumber)
+                    .with_label(&reserved.range, "reserved here"),
+                );
+            }
+            if reserved.contains_name(&field.name) {
+                errors.push(
+                    validation_error(
+                        format!(
+                            "Field name {} in message {} is reserved",
+                            field.name, message.name
+                        ),
+                        range,
+                    )
+                    .with_code(ErrorCode::ReservedName)
+                    .with_label(&reserved.range, "reserved here"),
+                );
+            }
+        }
+        for extensions in &message.extensions {
+            if extensions.contains_tag(field.tag) {
+                errors.push(
+                    validation_error(
+                        format!(
+                            "Field {} in message {} uses number {} of extension range",
+                            field.name, message.name, field.tag
+                        ),
+                        field.range.as_ref().unwrap_or(&extensions.range),
+                    )
+                    .with_code(ErrorCode::ExtensionRangeConflict)
+                    .with_label(&extensions.range, "extension range declared here"),
+                );
+            }
+        }
+    }
+    match ProtoError::from_errors(errors) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+fn validate_enum_reserved(enum_declaration: &EnumDeclaration) -> Result<(), ProtoError> {
+    let mut errors = Vec::new();
+    for entry in &enum_declaration.entries {
+        for reserved in &enum_declaration.reserved {
+            let range = entry.range.as_ref().unwrap_or(&reserved.range);
+            if reserved.contains_tag(entry.value) {
+                errors.push(
+                    validation_error(
+                        format!(
+                            "Value {} in enum {} uses reserved number {}",
+                            entry.name, enum_declaration.name, entry.value
+                        ),
+                        range,
+                    )
+                    .with_code(ErrorCode::ReservedNumber)
+                    .with_label(&reserved.range, "reserved here"),
+                );
+            }
+            if reserved.contains_name(&entry.name) {
+                errors.push(
+                    validation_error(
+                        format!(
+                            "Value name {} in enum {} is reserved",
+                            entry.name, enum_declaration.name
+                        ),
+                        range,
+                    )
+                    .with_code(ErrorCode::ReservedName)
+                    .with_label(&reserved.range, "reserved here"),
+                );
+            }
+        }
+    }
+    match ProtoError::from_errors(errors) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Parses `ident { "." ident }`
+fn parse_full_ident(
+    located_lexems: &[LocatedLexem],
+    ind: &mut usize,
+) -> Result<Vec<Rc<str>>, ProtoError> {
+    let mut parts = Vec::new();
+    loop {
+        let loc_lexem = &located_lexems[*ind];
+        match &loc_lexem.lexem {
+            Lexem::Id(id) => {
+                parts.push(id.clone());
+                *ind += 1;
+            }
+            _ => {
+                return Err(unexpected_lexem_error("identifier", loc_lexem));
+            }
+        }
+        match &located_lexems[*ind].lexem {
+            Lexem::Dot => {
+                *ind += 1;
+            }
+            _ => {
+                return Ok(parts);
+            }
+        }
+    }
+}
+
+/// Parses option name, e.g. `java_package`, `(my.ext).field`
+fn parse_option_name(
+    located_lexems: &[LocatedLexem],
+    ind: &mut usize,
+) -> Result<OptionName, ProtoError> {
+    let mut parts = Vec::new();
+    loop {
+        let loc_lexem = &located_lexems[*ind];
+        match &loc_lexem.lexem {
+            Lexem::Id(id) => {
+                parts.push(OptionNamePart {
+                    name: id.clone(),
+                    is_extension: false,
+                });
+                *ind += 1;
+            }
+            Lexem::OpenParen => {
+                *ind += 1;
+                let mut name = String::new();
+                if let Lexem::Dot = located_lexems[*ind].lexem {
+                    name.push('.');
+                    *ind += 1;
+                }
+                name.push_str(&parse_full_ident(located_lexems, ind)?.join("."));
+                let close_lexem = &located_lexems[*ind];
+                if close_lexem.lexem != Lexem::CloseParen {
+                    return Err(unexpected_lexem_error(")", close_lexem));
+                }
+                *ind += 1;
+                parts.push(OptionNamePart {
+                    name: name.into(),
+                    is_extension: true,
+                });
+            }
+            _ => {
+                return Err(unexpected_lexem_error("option name", loc_lexem));
+            }
+        }
+        match &located_lexems[*ind].lexem {
+            Lexem::Dot => {
+                *ind += 1;
+            }
+            _ => {
+                return Ok(OptionName { parts });
+            }
+        }
+    }
+}
+
+/// Parses option constant: literal, identifier or aggregate in text format
+fn parse_option_value(
+    located_lexems: &[LocatedLexem],
+    ind: &mut usize,
+) -> Result<OptionValue, ProtoError> {
+    let loc_lexem = &located_lexems[*ind];
+    let value = match &loc_lexem.lexem {
+        Lexem::Id(id) if id.deref() == "true" => OptionValue::Bool(true),
+        Lexem::Id(id) if id.deref() == "false" => OptionValue::Bool(false),
+        Lexem::Id(id) if id.deref() == "inf" => OptionValue::Float(f64::INFINITY),
+        Lexem::Id(id) if id.deref() == "nan" => OptionValue::Float(f64::NAN),
+        Lexem::Id(_) => {
+            return Ok(OptionValue::Identifier(
+                parse_full_ident(located_lexems, ind)?.join(".").into(),
+            ));
+        }
+        Lexem::IntLiteral(i) => OptionValue::Int(*i),
+        Lexem::FloatLiteral(x) => OptionValue::Float(*x),
+        Lexem::StringLiteral(s) => OptionValue::String(s.clone()),
+        Lexem::OpenCurly => {
+            return parse_aggregate(located_lexems, ind);
+        }
+        _ => {
+            return Err(unexpected_lexem_error("option value", loc_lexem));
+        }
+    };
+    *ind += 1;
+    Ok(value)
+}
+
+/// Parses message value in text format enclosed in `{}` or `<>`
+fn parse_aggregate(
+    located_lexems: &[LocatedLexem],
+    ind: &mut usize,
+) -> Result<OptionValue, ProtoError> {
+    let open_lexem = &located_lexems[*ind];
+    let close = match open_lexem.lexem {
+        Lexem::OpenCurly => Lexem::CloseCurly,
+        Lexem::Less => Lexem::Greater,
+        _ => {
+            return Err(unexpected_lexem_error("{ or <", open_lexem));
+        }
+    };
+    *ind += 1;
+    let mut entries = Vec::new();
+    loop {
+        let loc_lexem = &located_lexems[*ind];
+        let name: Rc<str> = match &loc_lexem.lexem {
+            lexem if *lexem == close => {
+                *ind += 1;
+                return Ok(OptionValue::Aggregate(entries));
+            }
+            Lexem::Comma | Lexem::SemiColon => {
+                *ind += 1;
+                continue;
+            }
+            Lexem::Id(id) => {
+                *ind += 1;
+                id.clone()
+            }
+            Lexem::OpenBracket => {
+                *ind += 1;
+                let name = parse_full_ident(located_lexems, ind)?.join(".");
+                let close_lexem = &located_lexems[*ind];
+                if close_lexem.lexem != Lexem::CloseBracket {
+                    return Err(unexpected_lexem_error("]", close_lexem));
+                }
+                *ind += 1;
+                format!("[{}]", name).into()
+            }
+            _ => {
+                return Err(unexpected_lexem_error("field name", loc_lexem));
+            }
+        };
+        let loc_lexem = &located_lexems[*ind];
+        match &loc_lexem.lexem {
+            Lexem::Colon => {
+                *ind += 1;
+                if let Lexem::OpenBracket = located_lexems[*ind].lexem {
+                    *ind += 1;
+                    loop {
+                        let loc_lexem = &located_lexems[*ind];
+                        match &loc_lexem.lexem {
+                            Lexem::CloseBracket => {
+                                *ind += 1;
+                                break;
+                            }
+                            Lexem::Comma => {
+                                *ind += 1;
+                            }
+                            _ => {
+                                let value = parse_text_value(located_lexems, ind)?;
+                                entries.push((name.clone(), value));
+                            }
+                        }
+                    }
+                } else {
+                    let value = parse_text_value(located_lexems, ind)?;
+                    entries.push((name, value));
+                }
+            }
+            Lexem::OpenCurly | Lexem::Less => {
+                let value = parse_aggregate(located_lexems, ind)?;
+                entries.push((name, value));
+            }
+            _ => {
+                return Err(unexpected_lexem_error(": or {", loc_lexem));
+            }
+        }
+    }
+}
+
+/// Parses field value inside of aggregate
+fn parse_text_value(
+    located_lexems: &[LocatedLexem],
+    ind: &mut usize,
+) -> Result<OptionValue, ProtoError> {
+    match &located_lexems[*ind].lexem {
+        Lexem::Less => parse_aggregate(located_lexems, ind),
+        _ => parse_option_value(located_lexems, ind),
+    }
+}
+
 #[cfg(test)]
 mod test {
     #[test]
@@ -847,90 +2050,810 @@ mod test {
             res,
             super::ImportPath {
                 packages: vec!["google".into(), "protobuf".into()],
-                file_name: "timestamp.proto".into()
+                file_name: "timestamp.proto".into(),
+                kind: super::ImportKind::Default,
+                range: None,
             }
         );
     }
-}
 
-fn print_stack(stack: &[StackItem]) {
-    println!("\nStack: ");
-    println!(
-        "{}",
-        stack
+    #[test]
+    fn parses_import_modifiers() {
+        use crate::proto::package::ImportKind;
+        let file = parse(
+            r#"
+            syntax = "proto3";
+            import "a.proto";
+            import public "b/c.proto";
+            import weak "d.proto";
+        "#,
+        );
+        let imports = file
+            .imports
+            .iter()
+            .map(|imprt| (imprt.to_string(), imprt.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            imports,
+            vec![
+                ("a.proto".to_string(), ImportKind::Default),
+                ("b/c.proto".to_string(), ImportKind::Public),
+                ("d.proto".to_string(), ImportKind::Weak),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_edition_features() {
+        use crate::proto::package::{Declaration, FieldLabel, MessageDeclarationEntry};
+        let file = parse(
+            r#"
+            edition = "2023";
+            option features.repeated_field_encoding = EXPANDED;
+            enum Kind {
+                option features.enum_type = CLOSED;
+                A = 0;
+            }
+            message Hello {
+                option features.field_presence = IMPLICIT;
+                int32 a = 1;
+                int32 b = 2 [features.field_presence = EXPLICIT];
+                int32 c = 3 [features.field_presence = LEGACY_REQUIRED];
+                repeated int32 d = 4;
+                repeated int32 e = 5 [features.repeated_field_encoding = PACKED];
+                Hello f = 6 [features.message_encoding = DELIMITED];
+            }
+        "#,
+        );
+        let (enum_declaration, message) = match &file.declarations[..] {
+            [Declaration::Enum(e), Declaration::Message(m)] => (e, m),
+            _ => panic!("Expected enum and message"),
+        };
+        assert!(enum_declaration.closed);
+        let fields = message
+            .entries
             .iter()
-            .rev()
-            .map(|x| match x {
-                StackItem::String(_) => "string",
-                StackItem::StringList(_) => "string[]",
-                StackItem::FieldType(_) => "type",
-                StackItem::EnumEntriesList(_) => "EnumEntry[]",
-                StackItem::MessageEntriesList(_) => "MessageEntry[]",
-                StackItem::MessageEntry(_) => "MessageEntry",
-                StackItem::Int64(_) => "i64",
-                StackItem::Message(_) => "message",
-                StackItem::OptionalAttributes(_) => "attributes[]?",
-                StackItem::Enum(_) => "enum",
-                StackItem::OneOf(_) => "oneof",
+            .map(|entry| match entry {
+                MessageDeclarationEntry::Field(field) => {
+                    (field.label, field.packed, field.delimited)
+                }
+                _ => panic!("Expected field"),
             })
-            .collect::<Vec<_>>()
-            .join("\n")
-    );
-}
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                (None, false, false),
+                (Some(FieldLabel::Optional), false, false),
+                (Some(FieldLabel::Required), false, false),
+                (None, false, false),
+                (None, true, false),
+                (None, false, true),
+            ]
+        );
+    }
 
-fn print_state(
-    stack: Vec<StackItem>,
-    tasks: Vec<Task>,
-    task: Task,
-    located_lexems: &[LocatedLexem],
-) {
-    if stack.len() > 0 {
-        println!("Stack:");
-        for item in stack.iter().rev() {
-            println!("{:#?}", item);
+    #[test]
+    fn rejects_features_outside_editions() {
+        assert!(try_parse(
+            r#"
+            syntax = "proto3";
+            message Hello {
+                int32 a = 1 [features.field_presence = EXPLICIT];
+            }
+        "#,
+        )
+        .is_err());
+        assert!(try_parse(
+            r#"
+            edition = "2023";
+            message Hello {
+                optional int32 a = 1;
+            }
+        "#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn reserves_identifiers_in_editions() {
+        use crate::proto::package::Declaration;
+        let file = parse(
+            r#"
+            edition = "2023";
+            message Hello {
+                reserved 2, foo, bar;
+                int32 a = 1;
+            }
+            enum Kind { reserved UNKNOWN; A = 0; }
+        "#,
+        );
+        match &file.declarations[..] {
+            [Declaration::Message(message), Declaration::Enum(enum_declaration)] => {
+                assert_eq!(message.reserved[0].names, vec!["foo".into(), "bar".into()]);
+                assert_eq!(enum_declaration.reserved[0].names, vec!["UNKNOWN".into()]);
+            }
+            _ => panic!("Expected message and enum"),
         }
-        println!();
-        print_stack(&stack);
-        println!();
-    } else {
-        println!("Stack: empty");
-        println!();
-    }
-    if tasks.len() > 0 {
-        println!("Tasks:");
-        for task in tasks {
-            println!("{:?}", task);
+
+        let message = |content: &str| try_parse(content).unwrap_err().message();
+        assert_eq!(
+            message("edition = \"2023\";\nmessage Hello { reserved \"foo\"; }"),
+            "Reserved names must be identifiers in editions, not string literals"
+        );
+        assert_eq!(
+            message("edition = \"2023\";\nmessage Hello { reserved foo; int32 foo = 1; }"),
+            "Field name foo in message Hello is reserved"
+        );
+        assert_eq!(
+            message("syntax = \"proto3\";\nmessage Hello { reserved foo; }"),
+            "Expected field number or name, but foo occurred"
+        );
+    }
+
+    #[test]
+    fn describes_unexpected_lexems_only_where_expected() {
+        let message = |content: &str| try_parse(content).unwrap_err().message();
+        assert_eq!(message("edition = \"2024\";"), "Unsupported edition 2024");
+        assert_eq!(
+            message("edition = \"2023\";\nmessage Hello { optional int32 a = 1; }"),
+            "Label optional is not allowed in editions, use features.field_presence"
+        );
+        assert_eq!(
+            message("message Hello { int32 a 1; }"),
+            "Expected =, but 1 occurred"
+        );
+    }
+
+    #[test]
+    fn parses_files_without_declarations() {
+        for content in ["", "  \n\t", "// comment\n/* block */\n"] {
+            let file = parse(content);
+            assert!(file.declarations.is_empty());
         }
-        println!("{:?} - current", task);
-        println!();
-    } else {
-        println!("Tasks: empty");
     }
 
-    if located_lexems.is_empty() {
-        return;
+    #[test]
+    fn prints_expected_lexems() {
+        let message = |content: &str| try_parse(content).unwrap_err().message();
+        assert_eq!(
+            message("import \"a.proto\""),
+            "Expected ;, but EOF occurred"
+        );
+        assert_eq!(message("enum 1 {}"), "Expected enum name, but 1 occurred");
+        assert_eq!(
+            message("message Hello { int32 a = 1 }"),
+            "Expected ;, but } occurred"
+        );
+    }
+
+    #[test]
+    fn keeps_literals_of_uint64_and_bytes_values() {
+        use crate::proto::package::OptionValue;
+        let file = parse(
+            r#"
+            syntax = "proto2";
+            option (max) = 18446744073709551615;
+            option (raw) = "\xff";
+        "#,
+        );
+        assert_eq!(
+            file.options.iter().map(|o| &o.value).collect::<Vec<_>>(),
+            vec![
+                &OptionValue::Int(u64::MAX as i128),
+                &OptionValue::String([0xff].as_slice().into()),
+            ]
+        );
+        let message = |content: &str| try_parse(content).unwrap_err().message();
+        assert_eq!(
+            crate::proto::lexems::read_lexems("test.proto", "18446744073709551616")
+                .unwrap_err()
+                .message(),
+            "Invalid integer literal: \"18446744073709551616\""
+        );
+        assert_eq!(
+            message("message Hello { int32 a = 18446744073709551615; }"),
+            "Invalid integer literal: \"18446744073709551615\""
+        );
+        assert_eq!(
+            message("import \"\\xff.proto\";"),
+            "String literal is not valid UTF-8"
+        );
+    }
+
+    #[test]
+    fn reports_unknown_features() {
+        let error = try_parse(
+            r#"
+            edition = "2023";
+            message Hello {
+                option features.bogus = 1;
+            }
+        "#,
+        )
+        .unwrap_err();
+        assert_eq!(error.message(), "Unknown feature bogus");
+        assert_eq!(
+            error.code(),
+            Some(crate::proto::error::ErrorCode::InvalidFeature)
+        );
+    }
+
+    fn parse(content: &str) -> crate::proto::package::ProtoFile {
+        try_parse(content).unwrap()
+    }
+
+    fn try_parse(
+        content: &str,
+    ) -> Result<crate::proto::package::ProtoFile, crate::proto::error::ProtoError> {
+        use crate::proto::{
+            id_generator::IdGenerator,
+            lexems::read_lexems,
+            package::{ProtoFile, ProtoVersion},
+        };
+        let lexems = read_lexems("test.proto", content).unwrap();
+        let mut file = ProtoFile {
+            version: ProtoVersion::Proto3,
+            declarations: vec![],
+            imports: vec![],
+            path: vec![],
+            name: "test.proto".into(),
+            import_path: "test.proto".into(),
+            options: vec![],
+        };
+        super::parse_package(&mut IdGenerator::new(), &lexems, &mut file)?;
+        Ok(file)
     }
-    println!("Next lexems:");
-    for i in 0..located_lexems.len().min(10) {
-        if i > 0 {
-            let prev = &located_lexems[i - 1].lexem;
-            match prev {
-                Lexem::CloseCurly | Lexem::SemiColon | Lexem::OpenCurly => {
-                    print!("\n")
+
+    #[test]
+    fn parses_reserved() {
+        use crate::proto::package::Declaration;
+        let file = parse(
+            r#"
+            message Hello {
+                reserved 2, 9 to 11, 40 to max;
+                reserved "foo", "bar";
+                int32 id = 1;
+            }
+            enum Kind {
+                reserved 1 to max;
+                UNKNOWN = 0;
+            }
+        "#,
+        );
+        match &file.declarations[..] {
+            [Declaration::Message(m), Declaration::Enum(e)] => {
+                assert_eq!(
+                    format!("{}", m.reserved[0]),
+                    "reserved 2, 9 to 11, 40 to 536870911;"
+                );
+                assert_eq!(format!("{}", m.reserved[1]), "reserved \"foo\", \"bar\";");
+                assert!(e.reserved[0].contains_tag(i32::MAX as i64));
+            }
+            _ => panic!("Expected message and enum"),
+        }
+
+        let err =
+            try_parse("message Hello {\n  reserved 9 to 11;\n  int32 id = 10;\n}").unwrap_err();
+        assert_eq!(
+            format!("{}", err),
+            "error[E0201]: Field id in message Hello uses reserved number 10\n \
+             --> test.proto:3:3\n  \
+             = test.proto:2:3: reserved here"
+        );
+        assert!(try_parse("message M {\n  oneof Hello {\n    reserved 1;\n  }\n}").is_err());
+        assert!(try_parse("enum Kind {\n  reserved \"A\";\n  A = 0;\n}").is_err());
+    }
+
+    #[test]
+    fn reports_every_reserved_collision() {
+        use crate::proto::error::ProtoError;
+        let messages = |content: &str| match try_parse(content) {
+            Err(ProtoError::Multiple(errors)) => errors
+                .iter()
+                .map(|error| error.message())
+                .collect::<Vec<_>>(),
+            res => panic!("Expected multiple errors, got {:?}", res),
+        };
+        assert_eq!(
+            messages(
+                r#"
+                message Hello {
+                    reserved 2, 9 to 11, 40 to max;
+                    reserved "foo";
+                    int32 foo = 1;
+                    int32 b = 10;
+                    int32 c = 50;
                 }
+            "#
+            ),
+            vec![
+                "Field name foo in message Hello is reserved",
+                "Field b in message Hello uses reserved number 10",
+                "Field c in message Hello uses reserved number 50",
+            ]
+        );
+        assert_eq!(
+            messages("enum Kind {\n  reserved 1, \"A\";\n  A = 0;\n  B = 1;\n}"),
+            vec![
+                "Value name A in enum Kind is reserved",
+                "Value B in enum Kind uses reserved number 1",
+            ]
+        );
+    }
 
-                _ => match &located_lexems[i].lexem {
-                    Lexem::SemiColon => {}
-                    _ => {
-                        print!(" ")
+    #[test]
+    fn parses_optional_label() {
+        use crate::proto::package::{Declaration, FieldLabel, MessageDeclarationEntry};
+        let file = parse("message Hello { optional int32 id = 1; int32 count = 2; }");
+        let labels = match &file.declarations[..] {
+            [Declaration::Message(m)] => m
+                .entries
+                .iter()
+                .map(|entry| match entry {
+                    MessageDeclarationEntry::Field(field) => field.label,
+                    _ => panic!("Expected field"),
+                })
+                .collect::<Vec<_>>(),
+            _ => panic!("Expected single message"),
+        };
+        assert_eq!(labels, vec![Some(FieldLabel::Optional), None]);
+
+        assert!(try_parse("message Hello { optional repeated int32 id = 1; }").is_err());
+        assert!(try_parse("message Hello { oneof v { optional int32 id = 1; } }").is_err());
+    }
+
+    #[test]
+    fn parses_proto2_labels() {
+        use crate::proto::package::{Declaration, MessageDeclarationEntry};
+        let file = parse(
+            r#"
+            syntax = "proto2";
+            message Hello {
+                required int32 id = 1;
+                optional string name = 2 [default = "none"];
+            }
+        "#,
+        );
+        let fields = match &file.declarations[..] {
+            [Declaration::Message(m)] => m
+                .entries
+                .iter()
+                .map(|entry| match entry {
+                    MessageDeclarationEntry::Field(field) => format!("{}", field),
+                    _ => panic!("Expected field"),
+                })
+                .collect::<Vec<_>>(),
+            _ => panic!("Expected single message"),
+        };
+        assert_eq!(
+            fields,
+            vec![
+                "required int32 id = 1",
+                "optional string name = 2 [default = \"none\"]"
+            ]
+        );
+
+        assert!(try_parse("message Hello { required int32 id = 1; }").is_err());
+        assert!(try_parse("message Hello { int32 id = 1 [default = 5]; }").is_err());
+    }
+
+    #[test]
+    fn parses_group() {
+        use crate::proto::package::{Declaration, MessageDeclarationEntry};
+        let file = parse(
+            r#"
+            syntax = "proto2";
+            message Search {
+                repeated group Result = 1 {
+                    required string url = 2;
+                }
+            }
+        "#,
+        );
+        let entries = match &file.declarations[..] {
+            [Declaration::Message(m)] => &m.entries,
+            _ => panic!("Expected single message"),
+        };
+        match &entries[..] {
+            [MessageDeclarationEntry::Field(field), MessageDeclarationEntry::Declaration(Declaration::Message(group))] =>
+            {
+                assert_eq!(format!("{}", field), "repeated Result result = 1");
+                assert!(field.delimited);
+                assert_eq!(group.name.as_ref(), "Result");
+                assert_eq!(group.entries.len(), 1);
+            }
+            _ => panic!("Expected group field and message"),
+        }
+
+        assert!(try_parse("message Search { optional group Result = 1 {} }").is_err());
+    }
+
+    #[test]
+    fn parses_extensions() {
+        use crate::proto::package::{Declaration, MessageDeclarationEntry};
+        let file = parse(
+            r#"
+            syntax = "proto2";
+            message Base {
+                extensions 100 to 199, 500 to max;
+                extend Other {
+                    optional int32 nested = 120;
+                }
+            }
+            extend Base {
+                optional string label = 100;
+            }
+        "#,
+        );
+        match &file.declarations[..] {
+            [Declaration::Message(base), Declaration::Extend(extend)] => {
+                assert_eq!(base.extensions.len(), 1);
+                assert_eq!(
+                    format!("{}", base.extensions[0]),
+                    "extensions 100 to 199, 500 to 536870911;"
+                );
+                match &base.entries[..] {
+                    [MessageDeclarationEntry::Declaration(Declaration::Extend(nested))] => {
+                        assert_eq!(nested.extendee.to_string(), "Other");
+                        assert_eq!(nested.fields.len(), 1);
                     }
-                },
+                    _ => panic!("Expected nested extend"),
+                }
+                assert_eq!(extend.extendee.to_string(), "Base");
+                assert_eq!(
+                    format!("{}", extend.fields[0]),
+                    "optional string label = 100"
+                );
+            }
+            _ => panic!("Expected message and extend"),
+        }
+
+        assert!(try_parse("message Base { extensions 100 to 199; }").is_err());
+        assert!(try_parse(
+            r#"syntax = "proto2";
+            message Base { optional int32 id = 150; extensions 100 to 199; }"#
+        )
+        .is_err());
+        assert!(try_parse(r#"syntax = "proto2"; extend Base { message Inner {} }"#).is_err());
+    }
+
+    #[test]
+    fn parses_service() {
+        use crate::proto::package::{Declaration, OptionValue};
+        let file = parse(
+            r#"
+            service Greeter {
+                option deprecated = true;
+                rpc Say(Hello) returns (stream foo.Reply);
+                rpc Listen(stream Hello) returns (Reply) {
+                    option (google.api.http) = { post: "/v1/listen" body: "*" };
+                }
+            }
+        "#,
+        );
+        let service = match &file.declarations[..] {
+            [Declaration::Service(service)] => service,
+            _ => panic!("Expected single service"),
+        };
+        assert_eq!(service.name.as_ref(), "Greeter");
+        assert_eq!(service.options[0].to_string(), "deprecated = true");
+        assert_eq!(service.methods.len(), 2);
+        let say = &service.methods[0];
+        assert!(!say.client_streaming && say.server_streaming);
+        assert_eq!(say.output_type_ref.to_string(), "foo.Reply");
+        let listen = &service.methods[1];
+        assert!(listen.client_streaming && !listen.server_streaming);
+        assert_eq!(listen.options[0].name.to_string(), "(google.api.http)");
+        assert_eq!(
+            listen.options[0].value,
+            OptionValue::Aggregate(vec![
+                (
+                    "post".into(),
+                    OptionValue::String("/v1/listen".as_bytes().into())
+                ),
+                ("body".into(), OptionValue::String("*".as_bytes().into())),
+            ])
+        );
+    }
+
+    #[test]
+    fn parses_streams_of_fully_qualified_types() {
+        use crate::proto::package::{Declaration, FieldTypeReference};
+        let file = parse(
+            r#"
+            service Greeter {
+                rpc Say(stream .my.pkg.Msg) returns (stream .Reply);
+                rpc Listen(.my.pkg.Msg) returns (stream.Reply);
+            }
+        "#,
+        );
+        let service = match &file.declarations[..] {
+            [Declaration::Service(service)] => service,
+            _ => panic!("Expected single service"),
+        };
+        let say = &service.methods[0];
+        assert!(say.client_streaming && say.server_streaming);
+        assert_eq!(
+            say.input_type_ref,
+            FieldTypeReference::AbsoluteIdPath(vec!["my".into(), "pkg".into(), "Msg".into()])
+        );
+        assert_eq!(say.output_type_ref.to_string(), ".Reply");
+        let listen = &service.methods[1];
+        assert!(!listen.client_streaming && listen.server_streaming);
+        assert_eq!(listen.input_type_ref.to_string(), ".my.pkg.Msg");
+        // Like protoc does, `stream` is a keyword even without space before the dot
+        assert_eq!(listen.output_type_ref.to_string(), ".Reply");
+    }
+
+    #[test]
+    fn parses_fully_qualified_types() {
+        use crate::proto::package::{Declaration, FieldTypeReference, MessageDeclarationEntry};
+        let file = parse(
+            r#"
+            message Hello {
+                .foo.Bar bar = 1;
+                repeated .Baz baz = 2;
+                map<string, .foo.Bar> bars = 3;
+            }
+        "#,
+        );
+        let message = match &file.declarations[..] {
+            [Declaration::Message(message)] => message,
+            _ => panic!("Expected single message"),
+        };
+        let types = message
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                MessageDeclarationEntry::Field(field) => field.field_type_ref.to_string(),
+                _ => panic!("Expected field"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![".foo.Bar", "repeated .Baz", "map<string, .foo.Bar>"]
+        );
+        assert!(matches!(
+            &message.entries[0],
+            MessageDeclarationEntry::Field(field)
+                if field.field_type_ref == FieldTypeReference::AbsoluteIdPath(vec!["foo".into(), "Bar".into()])
+        ));
+    }
+
+    #[test]
+    fn parses_options() {
+        use crate::proto::package::{Declaration, MessageDeclarationEntry};
+        let file = parse(
+            r#"
+            option java_package = "com.example";
+            message Hello {
+                option deprecated = true;
+                int32 id = 1 [packed = false, json_name = "ID"];
+                oneof kind {
+                    option (my.oneof_opt) = 1;
+                    string name = 2;
+                }
             }
+            enum Kind {
+                option allow_alias = true;
+                A = 0;
+                B = 1 [deprecated = true];
+            }
+        "#,
+        );
+        assert_eq!(
+            file.options[0].to_string(),
+            "java_package = \"com.example\""
+        );
+        let (message, enum_declaration) = match &file.declarations[..] {
+            [Declaration::Message(m), Declaration::Enum(e)] => (m, e),
+            _ => panic!("Expected message and enum"),
+        };
+        assert_eq!(message.options[0].to_string(), "deprecated = true");
+        match &message.entries[..] {
+            [MessageDeclarationEntry::Field(field), MessageDeclarationEntry::OneOf(one_of)] => {
+                assert_eq!(field.options.len(), 2);
+                assert_eq!(one_of.options[0].to_string(), "(my.oneof_opt) = 1");
+                assert_eq!(one_of.fields.len(), 1);
+            }
+            _ => panic!("Expected field and oneof"),
         }
-        print!("{}", located_lexems[i].lexem);
+        assert_eq!(
+            enum_declaration.options[0].to_string(),
+            "allow_alias = true"
+        );
+        assert_eq!(
+            enum_declaration.entries[1].options[0].to_string(),
+            "deprecated = true"
+        );
+    }
+
+    #[test]
+    fn parses_typed_field_options() {
+        use crate::proto::package::{
+            Declaration, MessageDeclarationEntry, OptionNamePart, OptionValue,
+        };
+        let file = parse(
+            r#"
+            syntax = "proto2";
+            message Hello {
+                int32 id = 1 [
+                    default = -5,
+                    deprecated = true,
+                    (validate.rules).string.min_len = 1,
+                    (my.ext) = { name: "x" nested { kind: FOO } }
+                ];
+            }
+        "#,
+        );
+        let field = match &file.declarations[..] {
+            [Declaration::Message(m)] => match &m.entries[..] {
+                [MessageDeclarationEntry::Field(field)] => field,
+                _ => panic!("Expected single field"),
+            },
+            _ => panic!("Expected single message"),
+        };
+        let values = field
+            .options
+            .iter()
+            .map(|o| o.value.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                OptionValue::Int(-5),
+                OptionValue::Bool(true),
+                OptionValue::Int(1),
+                OptionValue::Aggregate(vec![
+                    ("name".into(), OptionValue::String("x".as_bytes().into())),
+                    (
+                        "nested".into(),
+                        OptionValue::Aggregate(vec![(
+                            "kind".into(),
+                            OptionValue::Identifier("FOO".into())
+                        )])
+                    ),
+                ]),
+            ]
+        );
+        assert_eq!(
+            field.options[2].name.parts,
+            vec![
+                OptionNamePart {
+                    name: "validate.rules".into(),
+                    is_extension: true
+                },
+                OptionNamePart {
+                    name: "string".into(),
+                    is_extension: false
+                },
+                OptionNamePart {
+                    name: "min_len".into(),
+                    is_extension: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_all_syntax_errors() {
+        use crate::proto::error::ProtoError;
+        let errors = match try_parse(
+            r#"
+            syntax = "proto3";
+            mesage Foo { int32 a = 1; }
+            message Bar {
+                string = 2;
+            }
+            message Baz { int32 x = 1; }
+            enum E { A = 0; B = ; }
+        "#,
+        ) {
+            Err(ProtoError::Multiple(errors)) => errors,
+            res => panic!("Expected multiple errors, got {:?}", res),
+        };
+        let lines = errors
+            .iter()
+            .map(|error| match error {
+                ProtoError::SyntaxError { range, .. } => range.start.line,
+                _ => panic!("Expected syntax error, got {:?}", error),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![3, 5, 8]);
+    }
+
+    #[test]
+    fn parses_literals() {
+        use crate::proto::package::{Declaration, MessageDeclarationEntry, OptionValue};
+        let file = parse(
+            r#"
+            syntax = "proto2";
+            message Hello {
+                optional int32 a = 1 [default = 0x1F];
+                optional int32 b = 2 [default = -017];
+                optional double c = 3 [default = 1.5e3];
+                optional double d = 4 [default = -inf];
+                optional float e = 5 [default = .5];
+                optional string f = 6 [default = 'it\'s' " \x41\101é\n"];
+            }
+        "#,
+        );
+        let values = match &file.declarations[..] {
+            [Declaration::Message(m)] => m
+                .entries
+                .iter()
+                .map(|entry| match entry {
+                    MessageDeclarationEntry::Field(field) => field.options[0].value.clone(),
+                    _ => panic!("Expected field"),
+                })
+                .collect::<Vec<_>>(),
+            _ => panic!("Expected single message"),
+        };
+        assert_eq!(
+            values,
+            vec![
+                OptionValue::Int(31),
+                OptionValue::Int(-15),
+                OptionValue::Float(1500.0),
+                OptionValue::Float(f64::NEG_INFINITY),
+                OptionValue::Float(0.5),
+                OptionValue::String("it's AAé\n".as_bytes().into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_comments() {
+        use crate::proto::package::{Declaration, MessageDeclarationEntry};
+        let file = parse(
+            r#"
+            syntax = "proto3";
+
+            // Detached comment
+
+            // Hello message
+            // spans two lines
+            message Hello {
+                /* Field a */
+                int32 a = 1; // trailing a
+                int32 b = 2;
+            }
+
+            enum Kind {
+                // First value
+                FIRST = 0;
+            }
+        "#,
+        );
+        let (message, enum_declaration) = match &file.declarations[..] {
+            [Declaration::Message(m), Declaration::Enum(e)] => (m, e),
+            _ => panic!("Expected message and enum"),
+        };
+        assert_eq!(
+            message.comments.leading.as_deref(),
+            Some("Hello message\nspans two lines")
+        );
+        assert_eq!(
+            message.comments.detached,
+            vec![std::rc::Rc::<str>::from("Detached comment")]
+        );
+        let fields = message
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                MessageDeclarationEntry::Field(field) => field,
+                _ => panic!("Expected field"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(fields[0].comments.leading.as_deref(), Some("Field a"));
+        assert_eq!(fields[0].comments.trailing.as_deref(), Some("trailing a"));
+        assert_eq!(fields[1].comments, Default::default());
+        assert_eq!(
+            enum_declaration.entries[0].comments.leading.as_deref(),
+            Some("First value")
+        );
     }
-    println!("\n");
-    println!("source: {:?}\n", located_lexems[0].range.start);
 }
 
 fn assert_enough_length<M>(
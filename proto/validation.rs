@@ -0,0 +1,378 @@
+use std::{collections::HashMap, ops::Deref, rc::Rc};
+
+use super::{
+    error::{located_error, ErrorCode, ProtoError},
+    lexems::SourceRange,
+    package::{EnumEntry, Field, MessageEntry, Type, MAX_FIELD_NUMBER},
+    proto_scope::{
+        enum_scope::EnumScope, message::MessageScope, root_scope::RootScope,
+        traits::ChildrenScopes, ProtoScope,
+    },
+};
+
+/// Field numbers which protobuf reserves for its implementation
+const IMPLEMENTATION_RESERVED_NUMBERS: std::ops::RangeInclusive<i64> = 19000..=19999;
+
+/// Checks semantic rules of protobuf which are not enforced by parsing
+/// and name resolution. Reports all violations at once
+pub(crate) fn validate_root_scope(root: &RootScope) -> Result<(), ProtoError> {
+    let mut errors = Vec::new();
+    validate_package_names(root.children(), &[], &mut errors);
+    for child in root.children() {
+        validate_scope(child, &mut errors);
+    }
+    for extension in root.extensions.values().flatten() {
+        let extendee = root
+            .get_declaration_name(extension.extendee)
+            .unwrap_or_else(|| "".into());
+        validate_field_number(&extension.field, &extendee, &mut errors);
+    }
+    match ProtoError::from_errors(errors) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+fn validate_scope(scope: &ProtoScope, errors: &mut Vec<ProtoError>) {
+    match scope {
+        ProtoScope::Message(message) => validate_message(message, errors),
+        ProtoScope::Enum(enum_scope) => validate_enum(enum_scope, errors),
+        _ => {}
+    }
+    for child in scope.children() {
+        validate_scope(child, errors);
+    }
+}
+
+/// Declarations of all files of a package share its namespace.
+/// `children` are files and subpackages of the package
+fn validate_package_names(
+    children: &[Rc<ProtoScope>],
+    package_path: &[Rc<str>],
+    errors: &mut Vec<ProtoError>,
+) {
+    let declarations = children
+        .iter()
+        .filter(|child| matches!(child.as_ref(), ProtoScope::File(_)))
+        .flat_map(|file| file.children());
+    let scope_name = if package_path.is_empty() {
+        "the root package".to_string()
+    } else {
+        format!("package {}", package_path.join("."))
+    };
+    validate_unique_names(declarations, &scope_name, errors);
+    for child in children {
+        if let ProtoScope::Package(package) = child.deref() {
+            let mut path = package_path.to_vec();
+            path.push(Rc::clone(&package.name));
+            validate_package_names(package.children(), &path, errors);
+        }
+    }
+}
+
+fn validate_unique_names<'a>(
+    declarations: impl Iterator<Item = &'a Rc<ProtoScope>>,
+    scope_name: &str,
+    errors: &mut Vec<ProtoError>,
+) {
+    let mut names: HashMap<Rc<str>, &ProtoScope> = HashMap::new();
+    for declaration in declarations {
+        let name = declaration.name();
+        match names.get(&name) {
+            Some(first) => errors.push(with_optional_label(
+                located_error(
+                    ErrorCode::DuplicateDeclaration,
+                    format!("{} is already defined in {}", name, scope_name),
+                    declaration_range(declaration),
+                ),
+                declaration_range(first),
+                "first defined here",
+            )),
+            None => {
+                names.insert(name, declaration);
+            }
+        }
+    }
+}
+
+fn declaration_range(declaration: &ProtoScope) -> Option<&SourceRange> {
+    match declaration {
+        ProtoScope::Message(message) => message.range.as_ref(),
+        ProtoScope::Enum(enum_scope) => enum_scope.range.as_ref(),
+        ProtoScope::Service(service) => service.range.as_ref(),
+        _ => None,
+    }
+}
+
+fn validate_message(message: &MessageScope, errors: &mut Vec<ProtoError>) {
+    validate_unique_names(
+        message.children().iter(),
+        &format!("message {}", message.name),
+        errors,
+    );
+    let mut numbers: HashMap<i64, &Field> = HashMap::new();
+    let mut names: HashMap<&str, &Field> = HashMap::new();
+    for field in message_fields(message) {
+        validate_field_number(field, &message.name, errors);
+        if let Some(first) = numbers.get(&field.tag) {
+            errors.push(with_optional_label(
+                located_error(
+                    ErrorCode::DuplicateFieldNumber,
+                    format!(
+                        "Field {} in message {} uses number {} already used by field {}",
+                        field.name, message.name, field.tag, first.name
+                    ),
+                    field.range.as_ref(),
+                ),
+                first.range.as_ref(),
+                "first used here",
+            ));
+        } else {
+            numbers.insert(field.tag, field);
+        }
+        if let Some(first) = names.get(field.name.deref()) {
+            errors.push(with_optional_label(
+                located_error(
+                    ErrorCode::DuplicateFieldName,
+                    format!(
+                        "Field {} is already defined in message {}",
+                        field.name, message.name
+                    ),
+                    field.range.as_ref(),
+                ),
+                first.range.as_ref(),
+                "first defined here",
+            ));
+        } else {
+            names.insert(&field.name, field);
+        }
+    }
+    for entry in &message.entries {
+        let one_of = match entry {
+            MessageEntry::OneOf(one_of) => one_of,
+            MessageEntry::Field(_) => continue,
+        };
+        for field in &one_of.fields {
+            let kind = match field.field_type {
+                Type::Repeated(_) => "repeated",
+                Type::Map(_, _) => "map",
+                _ => continue,
+            };
+            errors.push(located_error(
+                ErrorCode::InvalidOneofField,
+                format!(
+                    "Field {} in oneof {} of message {} cannot be {}",
+                    field.name, one_of.name, message.name, kind
+                ),
+                field.range.as_ref(),
+            ));
+        }
+    }
+}
+
+/// Fields in declaration order, including ones from oneofs
+fn message_fields(message: &MessageScope) -> impl Iterator<Item = &Field> {
+    message.entries.iter().flat_map(|entry| match entry {
+        MessageEntry::Field(field) => vec![field],
+        MessageEntry::OneOf(one_of) => one_of.fields.iter().collect(),
+    })
+}
+
+fn validate_field_number(field: &Field, message_name: &str, errors: &mut Vec<ProtoError>) {
+    if field.tag < 1 || field.tag > MAX_FIELD_NUMBER {
+        errors.push(located_error(
+            ErrorCode::InvalidFieldNumber,
+            format!(
+                "Field {} of {} has number {} which is not in range 1 to {}",
+                field.name, message_name, field.tag, MAX_FIELD_NUMBER
+            ),
+            field.range.as_ref(),
+        ));
+    } else if IMPLEMENTATION_RESERVED_NUMBERS.contains(&field.tag) {
+        errors.push(located_error(
+            ErrorCode::InvalidFieldNumber,
+            format!(
+                "Field {} of {} has number {}, numbers {} to {} are reserved for protobuf implementation",
+                field.name,
+                message_name,
+                field.tag,
+                IMPLEMENTATION_RESERVED_NUMBERS.start(),
+                IMPLEMENTATION_RESERVED_NUMBERS.end()
+            ),
+            field.range.as_ref(),
+        ));
+    }
+}
+
+fn validate_enum(enum_scope: &EnumScope, errors: &mut Vec<ProtoError>) {
+    if let Some(first) = enum_scope.entries.first() {
+        // Open enums use zero as default value, so it must be declared first
+        if !enum_scope.closed && first.value != 0 {
+            errors.push(located_error(
+                ErrorCode::InvalidEnumZeroValue,
+                format!(
+                    "First value {} of open enum {} must be zero",
+                    first.name, enum_scope.name
+                ),
+                first.range.as_ref(),
+            ));
+        }
+    }
+    if enum_scope.allow_alias {
+        return;
+    }
+    let mut values: HashMap<i64, &EnumEntry> = HashMap::new();
+    for entry in &enum_scope.entries {
+        match values.get(&entry.value) {
+            Some(first) => errors.push(with_optional_label(
+                located_error(
+                    ErrorCode::DuplicateEnumValue,
+                    format!(
+                        "Value {} in enum {} uses number {} already used by {}, \
+                         set option allow_alias = true to allow aliases",
+                        entry.name, enum_scope.name, entry.value, first.name
+                    ),
+                    entry.range.as_ref(),
+                ),
+                first.range.as_ref(),
+                "first used here",
+            )),
+            None => {
+                values.insert(entry.value, entry);
+            }
+        }
+    }
+}
+
+fn with_optional_label(
+    error: ProtoError,
+    range: Option<&SourceRange>,
+    message: &str,
+) -> ProtoError {
+    match range {
+        Some(range) => error.with_label(range, message),
+        None => error,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::proto::{
+        error::{ErrorCode, ProtoError},
+        id_generator::IdGenerator,
+        lexems::read_lexems,
+        package::{ProtoFile, ProtoVersion},
+        proto_scope::builder::{ScopeBuilder, ScopeBuilderTrait},
+        syntax::parse_package,
+    };
+
+    fn validate(content: &str) -> Result<(), ProtoError> {
+        let lexems = read_lexems("test.proto", content).unwrap();
+        let mut file = ProtoFile {
+            version: ProtoVersion::Proto3,
+            declarations: vec![],
+            imports: vec![],
+            path: vec![],
+            name: "test.proto".into(),
+            import_path: "test.proto".into(),
+            options: vec![],
+        };
+        parse_package(&mut IdGenerator::new(), &lexems, &mut file).unwrap();
+        let builder = ScopeBuilder::new_ref();
+        builder.load(file).unwrap();
+        super::validate_root_scope(&builder.finish().unwrap())
+    }
+
+    #[test]
+    fn reports_all_violations() {
+        let error = validate(
+            r#"
+            syntax = "proto3";
+            message Hello {
+                int32 a = 1;
+                int32 b = 1;
+                string a = 2;
+                int32 c = 19500;
+                oneof o { map<string, int32> m = 3; }
+            }
+            enum E { X = 1; Y = 1; }
+            enum F { option allow_alias = true; F0 = 0; F1 = 0; }
+        "#,
+        )
+        .unwrap_err();
+        let errors = match error {
+            ProtoError::Multiple(errors) => errors,
+            error => vec![error],
+        };
+        assert_eq!(
+            errors.iter().map(|error| error.code()).collect::<Vec<_>>(),
+            vec![
+                Some(ErrorCode::DuplicateFieldNumber),
+                Some(ErrorCode::DuplicateFieldName),
+                Some(ErrorCode::InvalidFieldNumber),
+                Some(ErrorCode::InvalidOneofField),
+                Some(ErrorCode::InvalidEnumZeroValue),
+                Some(ErrorCode::DuplicateEnumValue),
+            ]
+        );
+        assert_eq!(errors[0].labels()[0].range.start.line, 4);
+    }
+
+    #[test]
+    fn reports_duplicate_declarations() {
+        let error = validate(
+            r#"
+            syntax = "proto3";
+            message M {}
+            message M {}
+            enum E { A = 0; }
+            service E {}
+            message Outer { message N {} enum N { B = 0; } }
+        "#,
+        )
+        .unwrap_err();
+        let errors = match error {
+            ProtoError::Multiple(errors) => errors,
+            error => vec![error],
+        };
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| (error.code(), error.message()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Some(ErrorCode::DuplicateDeclaration),
+                    "M is already defined in the root package".to_string()
+                ),
+                (
+                    Some(ErrorCode::DuplicateDeclaration),
+                    "E is already defined in the root package".to_string()
+                ),
+                (
+                    Some(ErrorCode::DuplicateDeclaration),
+                    "N is already defined in message Outer".to_string()
+                ),
+            ]
+        );
+        assert_eq!(errors[0].range().unwrap().start.line, 4);
+        assert_eq!(errors[0].labels()[0].message, "first defined here");
+        assert_eq!(errors[0].labels()[0].range.start.line, 3);
+    }
+
+    #[test]
+    fn accepts_valid_schema() {
+        assert!(validate(
+            r#"
+            syntax = "proto2";
+            message Hello {
+                optional int32 a = 1;
+                repeated string b = 2;
+                oneof o { int32 c = 3; string d = 4; }
+            }
+            enum E { X = 1; Y = 2; }
+        "#,
+        )
+        .is_ok());
+    }
+}